@@ -0,0 +1,297 @@
+//! Responses of endpoints that return binary data.
+//!
+//! `stream`, `download`, `hls`, `getCoverArt`, `getAvatar` and `getCaptions` return the requested
+//! media on success and a `subsonic-response` document on failure.
+//! [`BinaryResponse::parse`] distinguishes between the two.
+//!
+//! # Example
+//! ```rust
+//! use subsonic_types::{binary::BinaryResponse, response::ErrorCode};
+//!
+//! let response = BinaryResponse::parse(Some("image/png"), None, b"\x89PNG".to_vec());
+//! let media = response.into_result().unwrap();
+//! assert_eq!("image/png", media.content_type);
+//! assert_eq!(4, media.len());
+//!
+//! let response = BinaryResponse::parse(Some("audio/mpeg"), Some("bytes 4-7/8"), vec![0; 4]);
+//! let media = response.into_result().unwrap();
+//! assert_eq!(Some(4), media.range.map(|range| range.start));
+//!
+//! let document = r#"<subsonic-response status="failed" version="1.16.1"><error code="70" message="Not found"/></subsonic-response>"#;
+//! let response = BinaryResponse::parse(Some("text/xml; charset=utf-8"), None, document.as_bytes().to_vec());
+//! let error = response.into_result().unwrap_err();
+//! assert_eq!(ErrorCode::DataNotFound, error.code);
+//! ```
+
+use std::str::FromStr;
+
+use crate::response::{Error, Response, ResponseBody};
+
+/// The content type used when the server does not specify one.
+pub const DEFAULT_CONTENT_TYPE: &str = "application/octet-stream";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BinaryResponse {
+    Media(Media),
+    Error(Error),
+}
+
+impl BinaryResponse {
+    /// Parses the body of a binary endpoint response.
+    ///
+    /// If the content type is xml or json, or is missing and the body looks like a document,
+    /// the body is parsed as a `subsonic-response`. Failed responses yield [`BinaryResponse::Error`],
+    /// everything else is considered media.
+    /// `content_range` is the `Content-Range` header of partial responses, an invalid value is
+    /// ignored.
+    pub fn parse(content_type: Option<&str>, content_range: Option<&str>, bytes: Vec<u8>) -> Self {
+        if let Some(error) = parse_error(content_type, &bytes) {
+            return BinaryResponse::Error(error);
+        }
+        let content_type = content_type
+            .filter(|c| !c.trim().is_empty())
+            .unwrap_or(DEFAULT_CONTENT_TYPE)
+            .to_string();
+        let mut media = Media::new(content_type, bytes);
+        media.range = content_range.and_then(|range| range.parse().ok());
+        BinaryResponse::Media(media)
+    }
+
+    pub fn is_media(&self) -> bool {
+        matches!(self, BinaryResponse::Media(_))
+    }
+
+    pub fn is_error(&self) -> bool {
+        matches!(self, BinaryResponse::Error(_))
+    }
+
+    pub fn into_result(self) -> Result<Media, Error> {
+        match self {
+            BinaryResponse::Media(media) => Ok(media),
+            BinaryResponse::Error(error) => Err(error),
+        }
+    }
+}
+
+impl From<Media> for BinaryResponse {
+    fn from(media: Media) -> Self {
+        BinaryResponse::Media(media)
+    }
+}
+
+impl From<Error> for BinaryResponse {
+    fn from(error: Error) -> Self {
+        BinaryResponse::Error(error)
+    }
+}
+
+/// Media returned by a binary endpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Media {
+    /// The MIME type of the media, for example `audio/mpeg`.
+    pub content_type: String,
+    pub data: Vec<u8>,
+    /// The range of the media contained in `data`, for partial responses.
+    pub range: Option<ContentRange>,
+}
+
+impl Media {
+    pub fn new(content_type: impl Into<String>, data: Vec<u8>) -> Self {
+        Self {
+            content_type: content_type.into(),
+            data,
+            range: None,
+        }
+    }
+
+    pub fn with_range(mut self, range: ContentRange) -> Self {
+        self.range = Some(range);
+        self
+    }
+
+    /// The MIME type without parameters, for example `text/vtt` for `text/vtt; charset=utf-8`.
+    pub fn mime_type(&self) -> &str {
+        mime_type(&self.content_type)
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
+#[derive(Debug)]
+pub struct InvalidContentRange;
+
+impl std::fmt::Display for InvalidContentRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid content range")
+    }
+}
+
+impl std::error::Error for InvalidContentRange {}
+
+/// The value of a `Content-Range` header, for example `bytes 0-1023/4096`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ContentRange {
+    /// The first byte, inclusive.
+    pub start: u64,
+    /// The last byte, inclusive.
+    pub end: u64,
+    /// The total length of the media, if known.
+    pub total: Option<u64>,
+}
+
+impl ContentRange {
+    /// The number of bytes in the range.
+    pub fn size(&self) -> u64 {
+        self.end - self.start + 1
+    }
+}
+
+impl std::fmt::Display for ContentRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "bytes {}-{}/", self.start, self.end)?;
+        match self.total {
+            Some(total) => write!(f, "{}", total),
+            None => write!(f, "*"),
+        }
+    }
+}
+
+impl FromStr for ContentRange {
+    type Err = InvalidContentRange;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let range = s.trim().strip_prefix("bytes ").ok_or(InvalidContentRange)?;
+        let (range, total) = range.split_once('/').ok_or(InvalidContentRange)?;
+        let (start, end) = range.split_once('-').ok_or(InvalidContentRange)?;
+        let start = start.trim().parse().map_err(|_| InvalidContentRange)?;
+        let end = end.trim().parse().map_err(|_| InvalidContentRange)?;
+        let total = match total.trim() {
+            "*" => None,
+            total => Some(total.parse().map_err(|_| InvalidContentRange)?),
+        };
+        if start > end || total.map(|total| end >= total).unwrap_or(false) {
+            return Err(InvalidContentRange);
+        }
+        Ok(Self { start, end, total })
+    }
+}
+
+fn mime_type(content_type: &str) -> &str {
+    content_type.split(';').next().unwrap_or_default().trim()
+}
+
+/// Returns the error if the body is a failed `subsonic-response` document.
+fn parse_error(content_type: Option<&str>, bytes: &[u8]) -> Option<Error> {
+    let mime = content_type.map(mime_type).unwrap_or_default();
+    let mime = mime.to_ascii_lowercase();
+    if !mime.is_empty() && !mime.ends_with("json") && !mime.ends_with("xml") {
+        return None;
+    }
+    let (response, _) = Response::from_bytes(bytes, content_type).ok()?;
+    match response.body {
+        ResponseBody::Error(error) => Some(error),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{common::Version, response::ErrorCode};
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let error = Error::with_message(ErrorCode::DataNotFound, "Not found");
+        let failed = Response::failed(Version::LATEST, error.clone());
+        let xml = failed.to_xml().unwrap().into_bytes();
+        let json = failed.to_json().unwrap().into_bytes();
+
+        for (content_type, body) in [
+            (Some("text/xml"), xml.clone()),
+            (Some("application/xml; charset=UTF-8"), xml.clone()),
+            (Some("application/json"), json.clone()),
+            (None, xml.clone()),
+            (None, json.clone()),
+            (Some(""), json.clone()),
+        ] {
+            assert_eq!(
+                BinaryResponse::parse(content_type, None, body),
+                BinaryResponse::Error(error.clone()),
+                "{:?}",
+                content_type
+            );
+        }
+
+        // error documents sent with a media content type are media
+        let response = BinaryResponse::parse(Some("audio/mpeg"), None, xml.clone());
+        assert_eq!(
+            response,
+            BinaryResponse::Media(Media::new("audio/mpeg", xml))
+        );
+
+        // successful documents are not errors
+        let ok = Response::ok(Version::LATEST, ResponseBody::Empty);
+        let body = ok.to_xml().unwrap().into_bytes();
+        assert!(BinaryResponse::parse(Some("text/xml"), None, body).is_media());
+
+        // binary data without a content type
+        let response = BinaryResponse::parse(None, None, vec![0xff, 0xd8, 0xff]);
+        let media = response.into_result().unwrap();
+        assert_eq!(media.content_type, DEFAULT_CONTENT_TYPE);
+        assert_eq!(media.len(), 3);
+
+        let media =
+            BinaryResponse::parse(Some("text/vtt; charset=utf-8"), None, b"WEBVTT".to_vec())
+                .into_result()
+                .unwrap();
+        assert_eq!(media.mime_type(), "text/vtt");
+
+        // partial responses
+        let media = BinaryResponse::parse(Some("audio/mpeg"), Some("bytes 2-3/4"), vec![0, 1])
+            .into_result()
+            .unwrap();
+        assert_eq!(
+            Some(ContentRange {
+                start: 2,
+                end: 3,
+                total: Some(4)
+            }),
+            media.range
+        );
+        let media = BinaryResponse::parse(Some("audio/mpeg"), Some("bytes 2-"), vec![0, 1])
+            .into_result()
+            .unwrap();
+        assert_eq!(None, media.range);
+    }
+
+    #[test]
+    fn test_content_range() {
+        let range: ContentRange = "bytes 0-1023/4096".parse().unwrap();
+        assert_eq!(
+            range,
+            ContentRange {
+                start: 0,
+                end: 1023,
+                total: Some(4096)
+            }
+        );
+        assert_eq!(range.size(), 1024);
+        assert_eq!(range.to_string(), "bytes 0-1023/4096");
+
+        let range: ContentRange = "bytes 10-19/*".parse().unwrap();
+        assert_eq!(range.total, None);
+        assert_eq!(range.to_string(), "bytes 10-19/*");
+
+        assert!("bytes 10-9/100".parse::<ContentRange>().is_err());
+        assert!("bytes 0-100/100".parse::<ContentRange>().is_err());
+        assert!("bytes */100".parse::<ContentRange>().is_err());
+        assert!("0-10/100".parse::<ContentRange>().is_err());
+    }
+}
@@ -0,0 +1,574 @@
+//! Caching of responses, keyed on canonicalized requests.
+//!
+//! [`canonicalize`] turns a request into a key that does not depend on the user, the client or
+//! the order of the parameters. [`CachedService`] uses it to cache the responses of the endpoints
+//! given a time to live with [`CachedService::ttl`]. Every other endpoint is forwarded unchanged.
+//!
+//! Cached responses are shared by every user, so only endpoints whose responses are the same for
+//! every user should be cached. Any call to an endpoint that modifies the server, e.g. `star`,
+//! `scrobble`, `updatePlaylist` or `startScan`, clears the whole cache. These endpoints are the
+//! requests whose [`SubsonicRequest::MUTATING`] is set.
+//!
+//! The cache holds at most [`CachedService::capacity`] responses and evicts the least recently
+//! used one when it is full. Expired responses are removed whenever a response is cached.
+//!
+//! `getIndexes` responses that expired are revalidated with `ifModifiedSince` and are kept if the
+//! server reports no changes. The `ifModifiedSince` of a client is answered from the cache.
+//!
+//! # Example
+//! ```rust
+//! use std::time::Duration;
+//!
+//! use subsonic_types::{
+//!     cache::{self, CachedService},
+//!     request::browsing::{GetArtists, GetIndexes},
+//!     service::SubsonicService,
+//! };
+//!
+//! assert_eq!(
+//!     "/rest/getAlbum?f=json&id=1&v=1.16.1",
+//!     cache::canonicalize("/rest/getAlbum.view", "v=1.16.1&u=admin&p=admin&id=1&c=example&f=json")
+//! );
+//!
+//! struct Server;
+//! impl SubsonicService for Server {}
+//!
+//! let service = CachedService::new(Server)
+//!     .ttl::<GetIndexes>(Duration::from_secs(60))
+//!     .ttl::<GetArtists>(Duration::from_secs(60));
+//! ```
+
+use std::{
+    any::Any,
+    collections::HashMap,
+    future::Future,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    binary::Media,
+    common::Milliseconds,
+    query::{self, QueryBuilder, QueryBuilderString},
+    request::{
+        annotation, bookmark, browsing, chat, jukebox, lists, playlists, podcast, radio, retrieval,
+        scan, search, sharing, system, user, Request, SubsonicRequest,
+    },
+    response::{Indexes, Response, ResponseBody},
+    service::{self, service_methods, ServiceOutput, SubsonicService},
+};
+
+/// Parameters that do not change the response: user, password, token, salt, the OpenSubsonic
+/// API key and client.
+const IGNORED_KEYS: &[&str] = &["u", "p", "t", "s", "apiKey", "c"];
+
+/// The default maximum number of cached responses.
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// Returns the canonical form of a request: the path without the `.view` suffix and the query
+/// without the authentication and client parameters, sorted by key.
+/// Repeated keys keep their order since it can be meaningful, e.g. the songs of a play queue.
+pub fn canonicalize(path: &str, query: &str) -> String {
+    let path = path.strip_suffix(".view").unwrap_or(path);
+    let mut pairs = query::parse_query(query)
+        .filter_map(Result::ok)
+        .filter(|pair| !IGNORED_KEYS.contains(&pair.key.as_ref()))
+        .collect::<Vec<_>>();
+    pairs.sort_by(|a, b| a.key.cmp(&b.key));
+
+    let mut builder = QueryBuilderString::default();
+    for pair in pairs {
+        builder.emit(pair.key, pair.value);
+    }
+    format!("{}?{}", path, builder.into_query())
+}
+
+#[derive(Debug)]
+struct Entry {
+    /// The [`ServiceOutput`] of the endpoint.
+    output: Box<dyn Any + Send + Sync>,
+    expires: Instant,
+    /// The last time the entry was read or written, to evict the least recently used entry.
+    used: Instant,
+}
+
+/// A [`SubsonicService`] that caches the successful responses of the wrapped service.
+#[derive(Debug)]
+pub struct CachedService<S> {
+    service: S,
+    ttls: HashMap<&'static str, Duration>,
+    capacity: usize,
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl<S: SubsonicService> CachedService<S> {
+    /// Creates a cache that does not cache any endpoint yet.
+    pub fn new(service: S) -> Self {
+        Self {
+            service,
+            ttls: HashMap::new(),
+            capacity: DEFAULT_CAPACITY,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Caches the responses of the endpoint of `R` for `ttl`.
+    pub fn ttl<R: SubsonicRequest>(mut self, ttl: Duration) -> Self {
+        self.ttls.insert(R::PATH, ttl);
+        self
+    }
+
+    /// Keeps at most `capacity` responses, 1024 by default.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    pub fn service(&self) -> &S {
+        &self.service
+    }
+
+    pub fn into_inner(self) -> S {
+        self.service
+    }
+
+    /// Removes every cached response.
+    pub fn invalidate(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// Returns the cached output and whether it has not expired yet.
+    fn get<O: ServiceOutput>(&self, key: &str) -> Option<(O, bool)> {
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries.get_mut(key)?;
+        let output = entry.output.downcast_ref::<O>()?.clone();
+        let now = Instant::now();
+        entry.used = now;
+        Some((output, entry.expires > now))
+    }
+
+    /// Caches `output` after removing the expired entries and, if the cache is still full,
+    /// the least recently used entry.
+    fn insert<O: ServiceOutput>(&self, key: String, ttl: Duration, output: O) {
+        if self.capacity == 0 {
+            return;
+        }
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, entry| entry.expires > now);
+        entries.remove(&key);
+        if entries.len() >= self.capacity {
+            let lru = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.used)
+                .map(|(key, _)| key.clone());
+            if let Some(lru) = lru {
+                entries.remove(&lru);
+            }
+        }
+        let entry = Entry {
+            output: Box::new(output),
+            expires: now + ttl,
+            used: now,
+        };
+        entries.insert(key, entry);
+    }
+
+    async fn call<'s, R, O, F, Fut>(&'s self, request: Request<R>, call: F) -> service::Result<O>
+    where
+        R: SubsonicRequest,
+        O: ServiceOutput,
+        F: FnOnce(&'s S, Request<R>) -> Fut,
+        Fut: Future<Output = service::Result<O>>,
+    {
+        if R::MUTATING {
+            let output = call(&self.service, request).await;
+            self.invalidate();
+            return output;
+        }
+        let Some(&ttl) = self.ttls.get(R::PATH) else {
+            return call(&self.service, request).await;
+        };
+
+        let key = canonicalize(R::PATH, &SubsonicRequest::to_query(&request));
+        if let Some((output, true)) = self.get(&key) {
+            return Ok(output);
+        }
+        let output = call(&self.service, request).await?;
+        if is_cacheable(&output) {
+            self.insert(key, ttl, output.clone());
+        }
+        Ok(output)
+    }
+
+    async fn get_indexes_cached(
+        &self,
+        mut request: Request<browsing::GetIndexes>,
+    ) -> service::Result<Response> {
+        let Some(&ttl) = self.ttls.get(browsing::GetIndexes::PATH) else {
+            return self.service.get_indexes(request).await;
+        };
+
+        // Every client shares the same entry, whatever it has seen before.
+        let if_modified_since = request.body.if_modified_since.take();
+        let key = canonicalize(
+            browsing::GetIndexes::PATH,
+            &SubsonicRequest::to_query(&request),
+        );
+        let response = match self.get::<Response>(&key) {
+            Some((response, true)) => response,
+            cached => {
+                let cached = cached.map(|(response, _)| response);
+                let mut inner = request.clone();
+                inner.body.if_modified_since = cached.as_ref().and_then(last_modified);
+                let response = self.service.get_indexes(inner).await?;
+                let response = match cached {
+                    Some(cached) if is_unchanged(&cached, &response) => cached,
+                    _ => response,
+                };
+                if is_cacheable(&response) {
+                    self.insert(key, ttl, response.clone());
+                }
+                response
+            }
+        };
+
+        match (&response.body, if_modified_since) {
+            (ResponseBody::Indexes(indexes), Some(since)) if indexes.last_modified <= since => {
+                let unchanged = Indexes {
+                    last_modified: indexes.last_modified,
+                    ignored_articles: indexes.ignored_articles.clone(),
+                    ..Default::default()
+                };
+                Ok(Response::indexes(response.version, unchanged))
+            }
+            _ => Ok(response),
+        }
+    }
+}
+
+/// Failed responses are not cached.
+fn is_cacheable<O: ServiceOutput>(output: &O) -> bool {
+    output
+        .as_response()
+        .is_none_or(|response| !matches!(response.body, ResponseBody::Error(_)))
+}
+
+fn last_modified(response: &Response) -> Option<Milliseconds> {
+    match &response.body {
+        ResponseBody::Indexes(indexes) => Some(indexes.last_modified),
+        _ => None,
+    }
+}
+
+/// Servers answer an `ifModifiedSince` that is up to date with empty indexes.
+fn is_unchanged(cached: &Response, response: &Response) -> bool {
+    match (&cached.body, &response.body) {
+        (ResponseBody::Indexes(cached), ResponseBody::Indexes(indexes)) => {
+            indexes.last_modified <= cached.last_modified
+                && indexes.shortcut.is_empty()
+                && indexes.index.is_empty()
+                && indexes.child.is_empty()
+        }
+        _ => false,
+    }
+}
+
+macro_rules! cached_call {
+    ($self:ident, get_indexes, $request:ident) => {
+        $self.get_indexes_cached($request).await
+    };
+    ($self:ident, $name:ident, $request:ident) => {
+        $self.call($request, |s, r| s.$name(r)).await
+    };
+}
+
+macro_rules! impl_cached_service {
+    ($($(#[$comment:meta])* ($name:ident $request:path => $output:ty)),*) => {
+        #[async_trait::async_trait]
+        impl<S: SubsonicService> SubsonicService for CachedService<S> {
+            $(
+                async fn $name(&self, request: Request<$request>) -> service::Result<$output> {
+                    cached_call!(self, $name, request)
+                }
+            )*
+        }
+    };
+}
+
+service_methods!(impl_cached_service);
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+    use crate::{
+        request::Authentication,
+        response::{Artist, ArtistsID3, Error, ErrorCode, Index},
+        test_support,
+    };
+
+    use super::*;
+
+    #[test]
+    fn canonical_requests() {
+        assert_eq!(
+            "/rest/getAlbum?f=json&id=1&v=1.16.1",
+            canonicalize(
+                "/rest/getAlbum.view",
+                "v=1.16.1&u=admin&t=abc&s=def&id=1&c=example&f=json"
+            )
+        );
+        assert_eq!(
+            canonicalize("/rest/star", "u=a&p=a&id=2&albumId=3&id=1"),
+            canonicalize("/rest/star.view", "id=2&id=1&u=b&p=b&albumId=3")
+        );
+        assert_eq!(
+            "/rest/ping?v=1.16.1",
+            canonicalize("/rest/ping", "apiKey=d6f0b8a4&v=1.16.1")
+        );
+        assert_ne!(
+            canonicalize("/rest/star", "id=2&id=1"),
+            canonicalize("/rest/star", "id=1&id=2")
+        );
+        assert_eq!(
+            "/rest/search3?query=a%20b",
+            canonicalize("/rest/search3", "query=a%20b")
+        );
+    }
+
+    #[derive(Default)]
+    struct Server {
+        calls: AtomicUsize,
+        last_modified: AtomicU64,
+        fail: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl SubsonicService for Server {
+        async fn get_artists(
+            &self,
+            request: Request<browsing::GetArtists>,
+        ) -> service::Result<Response> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.fail {
+                return Err(Error::new(ErrorCode::Generic));
+            }
+            Ok(Response::artists(request.version, ArtistsID3::default()))
+        }
+
+        async fn get_indexes(
+            &self,
+            request: Request<browsing::GetIndexes>,
+        ) -> service::Result<Response> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let last_modified = Milliseconds::new(self.last_modified.load(Ordering::SeqCst));
+            let mut indexes = Indexes {
+                last_modified,
+                ignored_articles: "The".into(),
+                ..Default::default()
+            };
+            if request.body.if_modified_since < Some(last_modified) {
+                indexes.index.push(Index {
+                    name: "A".into(),
+                    artist: vec![Artist {
+                        id: "1".into(),
+                        name: "Artist".into(),
+                        ..Default::default()
+                    }],
+                });
+            }
+            Ok(Response::indexes(request.version, indexes))
+        }
+
+        async fn get_cover_art(
+            &self,
+            _: Request<retrieval::GetCoverArt>,
+        ) -> service::Result<Media> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(Media {
+                content_type: "image/png".into(),
+                data: vec![1, 2, 3],
+                range: None,
+            })
+        }
+
+        async fn star(&self, request: Request<annotation::Star>) -> service::Result<Response> {
+            Ok(Response::ok_empty(request.version))
+        }
+    }
+
+    impl CachedService<Server> {
+        fn calls(&self) -> usize {
+            self.service().calls.load(Ordering::SeqCst)
+        }
+    }
+
+    fn request<R: SubsonicRequest>(username: &str, body: R) -> Request<R> {
+        Request {
+            username: username.into(),
+            authentication: Authentication::random_token(username).unwrap(),
+            client: username.into(),
+            ..test_support::request(body)
+        }
+    }
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    #[tokio::test]
+    async fn cached_endpoints() {
+        let service = CachedService::new(Server::default())
+            .ttl::<browsing::GetArtists>(HOUR)
+            .ttl::<retrieval::GetCoverArt>(HOUR);
+
+        let artists = browsing::GetArtists::builder().build();
+        let first = service.get_artists(request("a", artists.clone())).await;
+        let second = service.get_artists(request("b", artists)).await;
+        assert_eq!(first, second);
+        assert_eq!(1, service.calls());
+
+        // A different query is a different entry.
+        let artists = browsing::GetArtists::builder().music_folder_id(1).build();
+        service.get_artists(request("a", artists)).await.unwrap();
+        assert_eq!(2, service.calls());
+
+        let cover_art = retrieval::GetCoverArt::builder("al-1").build();
+        let first = service.get_cover_art(request("a", cover_art.clone())).await;
+        let second = service.get_cover_art(request("a", cover_art)).await;
+        assert_eq!(first, second);
+        assert_eq!(3, service.calls());
+
+        // Endpoints without a ttl are not cached.
+        let service = CachedService::new(Server::default());
+        for _ in 0..2 {
+            let artists = browsing::GetArtists::builder().build();
+            service.get_artists(request("a", artists)).await.unwrap();
+        }
+        assert_eq!(2, service.calls());
+    }
+
+    #[tokio::test]
+    async fn expiration_and_invalidation() {
+        let artists = || request("a", browsing::GetArtists::builder().build());
+
+        let service =
+            CachedService::new(Server::default()).ttl::<browsing::GetArtists>(Duration::ZERO);
+        service.get_artists(artists()).await.unwrap();
+        service.get_artists(artists()).await.unwrap();
+        assert_eq!(2, service.calls());
+
+        let service = CachedService::new(Server::default()).ttl::<browsing::GetArtists>(HOUR);
+        service.get_artists(artists()).await.unwrap();
+        let star = annotation::Star::builder().id("1").build();
+        service.star(request("a", star)).await.unwrap();
+        service.get_artists(artists()).await.unwrap();
+        assert_eq!(2, service.calls());
+
+        // Failures are not cached.
+        let service = CachedService::new(Server {
+            fail: true,
+            ..Default::default()
+        })
+        .ttl::<browsing::GetArtists>(HOUR);
+        assert!(service.get_artists(artists()).await.is_err());
+        assert!(service.get_artists(artists()).await.is_err());
+        assert_eq!(2, service.calls());
+    }
+
+    #[tokio::test]
+    async fn eviction() {
+        let artists = |folder: u32| {
+            let body = browsing::GetArtists::builder()
+                .music_folder_id(folder)
+                .build();
+            request("a", body)
+        };
+
+        // The least recently used entry is evicted when the cache is full.
+        let service = CachedService::new(Server::default())
+            .ttl::<browsing::GetArtists>(HOUR)
+            .capacity(2);
+        service.get_artists(artists(1)).await.unwrap();
+        service.get_artists(artists(2)).await.unwrap();
+        service.get_artists(artists(1)).await.unwrap();
+        service.get_artists(artists(3)).await.unwrap();
+        assert_eq!(3, service.calls());
+        service.get_artists(artists(1)).await.unwrap();
+        assert_eq!(3, service.calls());
+        service.get_artists(artists(2)).await.unwrap();
+        assert_eq!(4, service.calls());
+
+        // Expired entries are removed when another response is cached.
+        let service = CachedService::new(Server::default())
+            .ttl::<browsing::GetArtists>(Duration::ZERO)
+            .ttl::<retrieval::GetCoverArt>(HOUR);
+        service.get_artists(artists(1)).await.unwrap();
+        assert_eq!(1, service.entries.lock().unwrap().len());
+        let cover_art = retrieval::GetCoverArt::builder("al-1").build();
+        service
+            .get_cover_art(request("a", cover_art))
+            .await
+            .unwrap();
+        assert_eq!(1, service.entries.lock().unwrap().len());
+    }
+
+    #[test]
+    fn mutating_endpoints() {
+        // Every endpoint that does not only read the server clears the cache.
+        macro_rules! endpoints {
+            ($($(#[$comment:meta])* ($name:ident $request:path => $output:ty)),*) => {
+                fn endpoints() -> Vec<(&'static str, bool)> {
+                    vec![$((<$request>::PATH, <$request>::MUTATING)),*]
+                }
+            };
+        }
+        service_methods!(endpoints);
+
+        const READING: &[&str] = &["ping", "stream", "download", "hls"];
+        for (path, mutating) in endpoints() {
+            let name = path.strip_prefix("/rest/").unwrap();
+            let reading =
+                name.starts_with("get") || name.starts_with("search") || READING.contains(&name);
+            assert_eq!(!reading, mutating, "{}", path);
+        }
+    }
+
+    #[tokio::test]
+    async fn indexes_if_modified_since() {
+        let indexes = |since: Option<u64>| {
+            let mut body = browsing::GetIndexes::builder().build();
+            body.if_modified_since = since.map(Milliseconds::new);
+            request("a", body)
+        };
+        let index_len = |response: &Response| match &response.body {
+            ResponseBody::Indexes(indexes) => indexes.index.len(),
+            body => panic!("{:?}", body),
+        };
+
+        let server = Server::default();
+        server.last_modified.store(10, Ordering::SeqCst);
+        let service = CachedService::new(server).ttl::<browsing::GetIndexes>(Duration::ZERO);
+
+        let response = service.get_indexes(indexes(None)).await.unwrap();
+        assert_eq!(1, index_len(&response));
+
+        // Expired, but the server reports no changes.
+        let response = service.get_indexes(indexes(Some(5))).await.unwrap();
+        assert_eq!(1, index_len(&response));
+        let response = service.get_indexes(indexes(Some(10))).await.unwrap();
+        assert_eq!(0, index_len(&response));
+        assert_eq!(3, service.calls());
+
+        service.service().last_modified.store(20, Ordering::SeqCst);
+        let response = service.get_indexes(indexes(Some(10))).await.unwrap();
+        assert_eq!(1, index_len(&response));
+        assert_eq!(Some(Milliseconds::new(20)), last_modified(&response));
+
+        // Fresh entries answer every client.
+        let service = CachedService::new(Server::default()).ttl::<browsing::GetIndexes>(HOUR);
+        service.get_indexes(indexes(None)).await.unwrap();
+        let response = service.get_indexes(indexes(Some(0))).await.unwrap();
+        assert_eq!(0, index_len(&response));
+        assert_eq!(1, service.calls());
+    }
+}
@@ -0,0 +1,350 @@
+//! Describes what a server speaking a given API version supports.
+//!
+//! # Example
+//! ```rust
+//! use subsonic_types::{
+//!     capabilities::Capabilities,
+//!     common::Version,
+//!     request::{browsing::GetTopSongs, search::{Search2, Search3}},
+//!     response::Child,
+//! };
+//!
+//! let capabilities = Capabilities::new(Version::V1_8_0);
+//! assert!(capabilities.supports::<Search3>());
+//! assert!(capabilities.supports::<Search2>());
+//! assert!(!capabilities.supports::<GetTopSongs>());
+//! assert!(capabilities.supports_path("/rest/search3.view"));
+//!
+//! // `Child::album_id` was added in 1.8.0, `Child::bookmark_position` in 1.10.1.
+//! assert_eq!(Some(true), capabilities.has_field::<Child>("album_id"));
+//! assert_eq!(Some(false), capabilities.has_field::<Child>("bookmarkPosition"));
+//! assert_eq!(None, capabilities.has_field::<Child>("unknown"));
+//! ```
+
+use crate::{
+    common::Version,
+    request::{
+        annotation, bookmark, browsing, chat, jukebox, lists, playlists, podcast, radio, retrieval,
+        scan, search, sharing, system, user, SubsonicRequest,
+    },
+    service::service_methods,
+};
+
+/// Metadata about a field of a response type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FieldInfo {
+    /// The name of the field in the rust type.
+    pub name: &'static str,
+    /// The key used for the field in the serialized format.
+    pub key: &'static str,
+    /// Is this field serialized as an xml attribute?
+    pub attribute: bool,
+    /// The version since this field is present.
+    /// `None` means the field is present in all versions.
+    pub since: Option<Version>,
+    /// The version in which this field was removed.
+    /// `None` means the field was never removed.
+    pub until: Option<Version>,
+    /// The version since this field is deprecated.
+    pub deprecated: Option<Version>,
+}
+
+impl FieldInfo {
+    /// Is this field present in the given version?
+    pub fn is_present(&self, version: Version) -> bool {
+        self.since.map(|since| version >= since).unwrap_or(true)
+            && self.until.map(|until| version < until).unwrap_or(true)
+    }
+
+    /// Is this field deprecated in the given version?
+    pub fn is_deprecated(&self, version: Version) -> bool {
+        self.deprecated
+            .map(|deprecated| version >= deprecated)
+            .unwrap_or(false)
+    }
+}
+
+/// Trait implemented by response types that exposes metadata about their fields.
+/// This is implemented by `#[derive(SubsonicType)]`.
+pub trait SubsonicFields {
+    /// The fields of this type, not including the ones of flattened types.
+    const FIELDS: &'static [FieldInfo];
+
+    /// Find a field by its rust name or its serialized key.
+    /// This also looks into flattened fields.
+    fn find_field(name: &str) -> Option<&'static FieldInfo> {
+        Self::FIELDS
+            .iter()
+            .find(|f| f.name == name || f.key == name)
+    }
+}
+
+/// An API endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Endpoint {
+    /// The path of the endpoint, e.g. `/rest/ping`.
+    pub path: &'static str,
+    /// The version since this endpoint is available.
+    pub since: Version,
+}
+
+impl Endpoint {
+    /// The endpoint of the request type `R`.
+    pub const fn of<R: SubsonicRequest>() -> Self {
+        Self {
+            path: R::PATH,
+            since: R::SINCE,
+        }
+    }
+
+    /// The name of the endpoint, e.g. `ping`.
+    pub fn name(&self) -> &'static str {
+        self.path.trim_start_matches("/rest/")
+    }
+}
+
+macro_rules! endpoints {
+    ($(($name:ident $request:path => $output:ty)),*) => {
+        /// All known API endpoints, one for each [`SubsonicService`](crate::service::SubsonicService)
+        /// method.
+        pub const ENDPOINTS: &[Endpoint] = &[$(Endpoint::of::<$request>()),*];
+    };
+}
+
+service_methods!(endpoints);
+
+/// An OpenSubsonic extension advertised by a server.
+/// <https://opensubsonic.netlify.app/docs/endpoints/getopensubsonicextensions/>
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Extension {
+    /// The name of the extension, e.g. `transcodeOffset`.
+    pub name: String,
+    /// The versions of the extension supported by the server.
+    pub versions: Vec<u32>,
+}
+
+impl Extension {
+    pub fn new(name: impl Into<String>, versions: impl Into<Vec<u32>>) -> Self {
+        Self {
+            name: name.into(),
+            versions: versions.into(),
+        }
+    }
+}
+
+/// The capabilities of a server speaking a given API version.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Capabilities {
+    version: Version,
+    extensions: Vec<Extension>,
+}
+
+impl Capabilities {
+    pub fn new(version: Version) -> Self {
+        Self {
+            version,
+            extensions: Vec::new(),
+        }
+    }
+
+    /// Add OpenSubsonic extensions advertised by the server.
+    pub fn with_extensions(mut self, extensions: impl IntoIterator<Item = Extension>) -> Self {
+        self.extensions.extend(extensions);
+        self
+    }
+
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    pub fn extensions(&self) -> &[Extension] {
+        &self.extensions
+    }
+
+    /// Does the server advertise the OpenSubsonic extension `name`?
+    pub fn has_extension(&self, name: &str) -> bool {
+        self.extension(name).is_some()
+    }
+
+    /// Does the server advertise version `version` of the OpenSubsonic extension `name`?
+    pub fn has_extension_version(&self, name: &str, version: u32) -> bool {
+        self.extension(name)
+            .map(|e| e.versions.contains(&version))
+            .unwrap_or(false)
+    }
+
+    pub fn extension(&self, name: &str) -> Option<&Extension> {
+        self.extensions.iter().find(|e| e.name == name)
+    }
+
+    /// Is the endpoint of the request type `R` supported?
+    pub fn supports<R: SubsonicRequest>(&self) -> bool {
+        self.version >= R::SINCE
+    }
+
+    /// Is the endpoint at `path` supported?
+    /// The path can be given with or without the `/rest/` prefix and the `.view` suffix.
+    /// Returns false for unknown endpoints.
+    pub fn supports_path(&self, path: &str) -> bool {
+        find_endpoint(path)
+            .map(|e| self.version >= e.since)
+            .unwrap_or(false)
+    }
+
+    /// Will the field `name` of `T` be present in responses?
+    /// The field can be given by its rust name or its serialized key.
+    /// Returns `None` if `T` has no such field.
+    pub fn has_field<T: SubsonicFields>(&self, name: &str) -> Option<bool> {
+        T::find_field(name).map(|f| f.is_present(self.version))
+    }
+
+    /// All the endpoints supported.
+    pub fn endpoints(&self) -> impl Iterator<Item = &'static Endpoint> + '_ {
+        ENDPOINTS.iter().filter(move |e| self.version >= e.since)
+    }
+}
+
+impl From<Version> for Capabilities {
+    fn from(version: Version) -> Self {
+        Self::new(version)
+    }
+}
+
+/// Find an endpoint by its path.
+/// The path can be given with or without the `/rest/` prefix and the `.view` suffix.
+pub fn find_endpoint(path: &str) -> Option<&'static Endpoint> {
+    let name = path.trim_start_matches('/');
+    let name = name.strip_prefix("rest/").unwrap_or(name);
+    let name = name.strip_suffix(".view").unwrap_or(name);
+    ENDPOINTS.iter().find(|e| e.name() == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use subsonic_types_macro::SubsonicType;
+
+    use super::*;
+    use crate::{
+        common::Format,
+        deser::{SubsonicDeserialize, SubsonicSerializeWrapper},
+        response::{AlbumID3, Child},
+    };
+
+    #[derive(Debug, Default, Clone, PartialEq, SubsonicType)]
+    pub struct Versioned {
+        #[subsonic(attribute)]
+        pub id: String,
+        #[subsonic(attribute, since = "1.8.0", until = "1.13.0", deprecated = "1.10.2")]
+        pub legacy: Option<String>,
+        #[subsonic(attribute, until = "1.14.0")]
+        pub count: u32,
+    }
+
+    #[test]
+    fn until() {
+        let value = Versioned {
+            id: "1".into(),
+            legacy: Some("legacy".into()),
+            count: 5,
+        };
+        let to_json = |version| {
+            serde_json::to_string(&SubsonicSerializeWrapper(&value, Format::Json, version))
+                .unwrap()
+        };
+        let from_json = |content: &str, version| {
+            let seed = <Versioned as SubsonicDeserialize>::Seed::from((Format::Json, version));
+            serde::de::DeserializeSeed::deserialize(
+                seed,
+                &mut serde_json::Deserializer::from_str(content),
+            )
+            .unwrap()
+        };
+
+        assert_eq!(r#"{"id":"1","count":5}"#, to_json(Version::V1_6_0));
+        assert_eq!(
+            r#"{"id":"1","legacy":"legacy","count":5}"#,
+            to_json(Version::V1_12_0)
+        );
+        assert_eq!(r#"{"id":"1","count":5}"#, to_json(Version::V1_13_0));
+        assert_eq!(r#"{"id":"1"}"#, to_json(Version::V1_14_0));
+
+        let content = r#"{"id":"1","legacy":"legacy","count":5}"#;
+        assert_eq!(value, from_json(content, Version::V1_12_0));
+        assert_eq!(
+            Versioned {
+                id: "1".into(),
+                legacy: None,
+                count: 0,
+            },
+            from_json(content, Version::LATEST)
+        );
+        // `count` is not required once it has been removed.
+        assert_eq!(
+            Versioned {
+                id: "1".into(),
+                ..Default::default()
+            },
+            from_json(r#"{"id":"1"}"#, Version::LATEST)
+        );
+
+        let legacy = Versioned::find_field("legacy").unwrap();
+        assert!(!legacy.is_present(Version::V1_13_0));
+        assert!(!legacy.is_deprecated(Version::V1_9_0));
+        assert!(legacy.is_deprecated(Version::V1_10_2));
+        assert_eq!(
+            Some(false),
+            Capabilities::new(Version::LATEST).has_field::<Versioned>("count")
+        );
+    }
+
+    #[test]
+    fn endpoints_are_unique() {
+        for (i, e) in ENDPOINTS.iter().enumerate() {
+            assert!(
+                !ENDPOINTS[i + 1..].iter().any(|o| o.path == e.path),
+                "duplicate endpoint {}",
+                e.path
+            );
+        }
+    }
+
+    #[test]
+    fn supports_endpoints() {
+        let capabilities = Capabilities::new(Version::V1_4_0);
+        assert!(capabilities.supports::<search::Search2>());
+        assert!(!capabilities.supports::<search::Search3>());
+        assert!(capabilities.supports_path("search2"));
+        assert!(capabilities.supports_path("/rest/search2.view"));
+        assert!(!capabilities.supports_path("/rest/search3"));
+        assert!(!capabilities.supports_path("/rest/unknown"));
+        assert!(capabilities.endpoints().all(|e| e.since <= Version::V1_4_0));
+        assert_eq!(
+            ENDPOINTS.len(),
+            Capabilities::new(Version::LATEST).endpoints().count()
+        );
+    }
+
+    #[test]
+    fn has_field() {
+        let capabilities = Capabilities::new(Version::V1_12_0);
+        assert_eq!(Some(true), capabilities.has_field::<Child>("id"));
+        assert_eq!(Some(true), capabilities.has_field::<Child>("isVideo"));
+        assert_eq!(Some(true), capabilities.has_field::<Child>("media_type"));
+        assert_eq!(
+            Some(false),
+            capabilities.has_field::<Child>("original_width")
+        );
+        assert_eq!(None, capabilities.has_field::<Child>("nope"));
+        assert_eq!(Some(true), capabilities.has_field::<AlbumID3>("year"));
+    }
+
+    #[test]
+    fn extensions() {
+        let capabilities = Capabilities::new(Version::LATEST)
+            .with_extensions([Extension::new("transcodeOffset", [1])]);
+        assert!(capabilities.has_extension("transcodeOffset"));
+        assert!(capabilities.has_extension_version("transcodeOffset", 1));
+        assert!(!capabilities.has_extension_version("transcodeOffset", 2));
+        assert!(!capabilities.has_extension("formPost"));
+    }
+}
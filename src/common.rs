@@ -6,8 +6,6 @@ use serde::{Deserialize, Serialize};
 use subsonic_types_macro::SubsonicType;
 use time::{OffsetDateTime, PrimitiveDateTime};
 
-use crate::{impl_from_query_value_for_parse, impl_to_query_value_for_display};
-
 #[derive(Debug)]
 pub struct InvalidFormat;
 
@@ -584,6 +582,7 @@ impl<'de> Deserialize<'de> for AudioBitrate {
 pub enum MediaType {
     Music,
     Podcast,
+    #[serde(rename = "audiobook")]
     AudioBook,
     Video,
 }
@@ -767,17 +766,45 @@ impl Version {
     pub const V1_12_0: Self = Self::new(1, 12, 0);
     pub const V1_11_0: Self = Self::new(1, 11, 0);
     pub const V1_10_2: Self = Self::new(1, 10, 2);
+    pub const V1_10_1: Self = Self::new(1, 10, 1);
+    pub const V1_10_0: Self = Self::new(1, 10, 0);
     pub const V1_9_0: Self = Self::new(1, 9, 0);
     pub const V1_8_0: Self = Self::new(1, 8, 0);
     pub const V1_7_0: Self = Self::new(1, 7, 0);
     pub const V1_6_0: Self = Self::new(1, 6, 0);
     pub const V1_5_0: Self = Self::new(1, 5, 0);
+    pub const V1_4_1: Self = Self::new(1, 4, 1);
     pub const V1_4_0: Self = Self::new(1, 4, 0);
     pub const V1_3_0: Self = Self::new(1, 3, 0);
     pub const V1_2_0: Self = Self::new(1, 2, 0);
     pub const V1_1_1: Self = Self::new(1, 1, 1);
     pub const V1_1_0: Self = Self::new(1, 1, 0);
 
+    /// All known versions, from oldest to newest.
+    pub const ALL: &'static [Self] = &[
+        Self::V1_1_0,
+        Self::V1_1_1,
+        Self::V1_2_0,
+        Self::V1_3_0,
+        Self::V1_4_0,
+        Self::V1_4_1,
+        Self::V1_5_0,
+        Self::V1_6_0,
+        Self::V1_7_0,
+        Self::V1_8_0,
+        Self::V1_9_0,
+        Self::V1_10_0,
+        Self::V1_10_1,
+        Self::V1_10_2,
+        Self::V1_11_0,
+        Self::V1_12_0,
+        Self::V1_13_0,
+        Self::V1_14_0,
+        Self::V1_15_0,
+        Self::V1_16_0,
+        Self::V1_16_1,
+    ];
+
     pub const fn new(major: u8, minor: u8, patch: u8) -> Self {
         Self {
             major,
@@ -851,6 +878,401 @@ impl<'de> serde::Deserialize<'de> for Version {
     }
 }
 
+#[derive(Debug)]
+pub struct InvalidMusicFolderId;
+
+impl std::fmt::Display for InvalidMusicFolderId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid music folder id")
+    }
+}
+
+impl std::error::Error for InvalidMusicFolderId {}
+
+/// The ID of a music folder. See [`crate::response::MusicFolder`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, SubsonicType)]
+#[subsonic(serde)]
+pub struct MusicFolderId(u32);
+impl_to_query_value_for_display!(MusicFolderId);
+impl_from_query_value_for_parse!(MusicFolderId);
+
+impl MusicFolderId {
+    pub const fn new(id: u32) -> Self {
+        Self(id)
+    }
+
+    pub fn value(self) -> u32 {
+        self.0
+    }
+}
+
+impl std::fmt::Display for MusicFolderId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for MusicFolderId {
+    type Err = InvalidMusicFolderId;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self).map_err(|_| InvalidMusicFolderId)
+    }
+}
+
+impl From<u32> for MusicFolderId {
+    fn from(id: u32) -> Self {
+        Self(id)
+    }
+}
+
+impl From<MusicFolderId> for u32 {
+    fn from(id: MusicFolderId) -> Self {
+        id.0
+    }
+}
+
+impl Serialize for MusicFolderId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_u32(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for MusicFolderId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = MusicFolderId;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a music folder id")
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                u32::try_from(v)
+                    .map(MusicFolderId)
+                    .map_err(|_| E::custom(InvalidMusicFolderId))
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                u32::try_from(v)
+                    .map(MusicFolderId)
+                    .map_err(|_| E::custom(InvalidMusicFolderId))
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                v.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+macro_rules! impl_id {
+    ($($(#[$meta:meta])* $t:ident),* $(,)?) => {
+        $(
+            $(#[$meta])*
+            #[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, SubsonicType)]
+            #[subsonic(serde)]
+            pub struct $t(String);
+            impl_to_query_value_for_display!($t);
+            impl_from_query_value_for_parse!($t);
+
+            impl $t {
+                pub fn new(id: impl Into<String>) -> Self {
+                    Self(id.into())
+                }
+
+                pub fn as_str(&self) -> &str {
+                    &self.0
+                }
+
+                pub fn into_inner(self) -> String {
+                    self.0
+                }
+
+                pub(crate) fn as_mut_string(&mut self) -> &mut String {
+                    &mut self.0
+                }
+            }
+
+            impl std::fmt::Display for $t {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.write_str(&self.0)
+                }
+            }
+
+            impl FromStr for $t {
+                type Err = std::convert::Infallible;
+
+                fn from_str(s: &str) -> Result<Self, Self::Err> {
+                    Ok(Self(s.to_string()))
+                }
+            }
+
+            impl AsRef<str> for $t {
+                fn as_ref(&self) -> &str {
+                    &self.0
+                }
+            }
+
+            impl From<String> for $t {
+                fn from(id: String) -> Self {
+                    Self(id)
+                }
+            }
+
+            impl From<&str> for $t {
+                fn from(id: &str) -> Self {
+                    Self(id.to_string())
+                }
+            }
+
+            impl From<$t> for String {
+                fn from(id: $t) -> Self {
+                    id.0
+                }
+            }
+
+            impl PartialEq<str> for $t {
+                fn eq(&self, other: &str) -> bool {
+                    self.0 == other
+                }
+            }
+
+            impl PartialEq<&str> for $t {
+                fn eq(&self, other: &&str) -> bool {
+                    self.0 == *other
+                }
+            }
+
+            impl Serialize for $t {
+                fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    S: serde::Serializer,
+                {
+                    serializer.serialize_str(&self.0)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $t {
+                fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+                where
+                    D: serde::Deserializer<'de>,
+                {
+                    deserialize_id(deserializer).map(Self)
+                }
+            }
+        )*
+    };
+}
+
+/// Some servers encode IDs as numbers instead of strings.
+fn deserialize_id<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct Visitor;
+
+    impl<'de> serde::de::Visitor<'de> for Visitor {
+        type Value = String;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a string or integer id")
+        }
+
+        fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+            Ok(v.to_string())
+        }
+
+        fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Self::Value, E> {
+            Ok(v.to_string())
+        }
+
+        fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+            Ok(v.to_string())
+        }
+
+        fn visit_string<E: serde::de::Error>(self, v: String) -> Result<Self::Value, E> {
+            Ok(v)
+        }
+    }
+
+    deserializer.deserialize_any(Visitor)
+}
+
+impl_id!(
+    /// An ID of unknown kind. Used by endpoints that accept the ID of a song, album, artist
+    /// or directory. All other IDs can be converted into it.
+    Id,
+    /// The ID of a media file: a song, video or podcast episode.
+    /// See [`crate::response::Child`].
+    SongId,
+    /// The ID of a music directory. Used by the file structure based endpoints like
+    /// [`crate::request::browsing::GetMusicDirectory`].
+    DirectoryId,
+    /// The ID of an album organized by ID3 tags. See [`crate::response::AlbumID3`].
+    AlbumId,
+    /// The ID of an artist organized by ID3 tags. See [`crate::response::ArtistID3`].
+    ArtistId,
+    /// The ID of a playlist. See [`crate::response::Playlist`].
+    PlaylistId,
+    /// The ID of a cover art image. See [`crate::request::retrieval::GetCoverArt`].
+    CoverArtId,
+    /// The ID of a podcast channel. See [`crate::response::PodcastChannel`].
+    PodcastChannelId,
+    /// The ID of a podcast episode. See [`crate::response::PodcastEpisode`].
+    PodcastEpisodeId,
+    /// The ID of a share. See [`crate::response::Share`].
+    ShareId,
+    /// The ID of an internet radio station. See [`crate::response::InternetRadioStation`].
+    InternetRadioStationId,
+);
+
+macro_rules! impl_from_id_for_id {
+    ($($t:ident),*) => {
+        $(
+            impl From<$t> for Id {
+                fn from(id: $t) -> Self {
+                    Self(id.0)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_id_for_id!(SongId, DirectoryId, AlbumId, ArtistId, PodcastEpisodeId);
+
+/// The kind of entity a [`CoverArtId`] refers to.
+/// Some servers, like Navidrome, prefix cover art IDs with the kind of entity they belong to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CoverArtKind {
+    /// Prefixed with `al-`.
+    Album,
+    /// Prefixed with `ar-`.
+    Artist,
+    /// Prefixed with `mf-`.
+    MediaFile,
+    /// Prefixed with `pl-`.
+    Playlist,
+}
+
+impl CoverArtKind {
+    pub fn prefix(self) -> &'static str {
+        match self {
+            CoverArtKind::Album => "al-",
+            CoverArtKind::Artist => "ar-",
+            CoverArtKind::MediaFile => "mf-",
+            CoverArtKind::Playlist => "pl-",
+        }
+    }
+}
+
+/// The parts of a prefixed [`CoverArtId`], e.g. `pl-<uuid>_<hex>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CoverArtParts<'a> {
+    pub kind: CoverArtKind,
+    /// The ID of the entity the cover art belongs to.
+    pub id: &'a str,
+    /// The hexadecimal suffix after the last `_`, usually a timestamp used for cache busting.
+    pub suffix: Option<&'a str>,
+}
+
+impl CoverArtId {
+    /// Build a prefixed cover art ID.
+    /// ```
+    /// use subsonic_types::common::{CoverArtId, CoverArtKind};
+    ///
+    /// let id = CoverArtId::from_parts(CoverArtKind::Album, "123", Some("64b2a1f0"));
+    /// assert_eq!("al-123_64b2a1f0", id.as_str());
+    /// ```
+    pub fn from_parts(kind: CoverArtKind, id: &str, suffix: Option<&str>) -> Self {
+        match suffix {
+            Some(suffix) => Self(format!("{}{}_{}", kind.prefix(), id, suffix)),
+            None => Self(format!("{}{}", kind.prefix(), id)),
+        }
+    }
+
+    /// Parse the known prefix schemes.
+    /// Returns `None` if this ID has no known prefix.
+    /// ```
+    /// use subsonic_types::common::{CoverArtId, CoverArtKind};
+    ///
+    /// let id = CoverArtId::from("pl-5a1cb5c1-2bb8-4d6c-a4a4-6d53c7dd2a12_64b2a1f0");
+    /// let parts = id.parts().unwrap();
+    /// assert_eq!(CoverArtKind::Playlist, parts.kind);
+    /// assert_eq!("5a1cb5c1-2bb8-4d6c-a4a4-6d53c7dd2a12", parts.id);
+    /// assert_eq!(Some("64b2a1f0"), parts.suffix);
+    ///
+    /// assert!(CoverArtId::from("123").parts().is_none());
+    /// ```
+    pub fn parts(&self) -> Option<CoverArtParts<'_>> {
+        let kind = [
+            CoverArtKind::Album,
+            CoverArtKind::Artist,
+            CoverArtKind::MediaFile,
+            CoverArtKind::Playlist,
+        ]
+        .into_iter()
+        .find(|k| self.0.starts_with(k.prefix()))?;
+        let rest = &self.0[kind.prefix().len()..];
+        let (id, suffix) = match rest.rsplit_once('_') {
+            Some((id, suffix))
+                if !id.is_empty()
+                    && !suffix.is_empty()
+                    && suffix.bytes().all(|b| b.is_ascii_hexdigit()) =>
+            {
+                (id, Some(suffix))
+            }
+            _ => (rest, None),
+        };
+        if id.is_empty() {
+            return None;
+        }
+        Some(CoverArtParts { kind, id, suffix })
+    }
+
+    /// The kind of entity this cover art belongs to, if the ID uses a known prefix.
+    pub fn kind(&self) -> Option<CoverArtKind> {
+        self.parts().map(|p| p.kind)
+    }
+}
+
+macro_rules! impl_from_id_for_cover_art_id {
+    ($($t:ident),*) => {
+        $(
+            /// Servers that don't use prefixed cover art IDs use the ID of the entity itself.
+            impl From<$t> for CoverArtId {
+                fn from(id: $t) -> Self {
+                    Self(id.0)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_id_for_cover_art_id!(SongId, DirectoryId, AlbumId, ArtistId, PlaylistId);
+
+impl From<SongId> for PodcastEpisodeId {
+    fn from(id: SongId) -> Self {
+        Self(id.0)
+    }
+}
+
+impl From<SongId> for DirectoryId {
+    fn from(id: SongId) -> Self {
+        Self(id.0)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -901,4 +1323,52 @@ mod tests {
         assert_eq!(b.bitrate, 123456789);
         assert_eq!(b.size, Some(VideoSize::new(1920, 1080)));
     }
+
+    #[test]
+    fn test_ids() {
+        let id = SongId::from("123");
+        assert_eq!(id, "123");
+        assert_eq!(serde_json::to_string(&id).unwrap(), "\"123\"");
+        assert_eq!(serde_json::from_str::<SongId>("\"123\"").unwrap(), id);
+        assert_eq!(serde_json::from_str::<SongId>("123").unwrap(), id);
+        assert_eq!(Id::from(id), Id::new("123"));
+
+        let id = MusicFolderId::new(3);
+        assert_eq!(serde_json::to_string(&id).unwrap(), "3");
+        assert_eq!(serde_json::from_str::<MusicFolderId>("3").unwrap(), id);
+        assert_eq!(serde_json::from_str::<MusicFolderId>("\"3\"").unwrap(), id);
+        assert!("abc".parse::<MusicFolderId>().is_err());
+    }
+
+    #[test]
+    fn test_cover_art_id() {
+        let parts = |id: &str| {
+            CoverArtId::from(id)
+                .parts()
+                .map(|p| (p.kind, p.id.to_string(), p.suffix.map(str::to_string)))
+        };
+        assert_eq!(
+            parts("al-57fd6b27_0"),
+            Some((CoverArtKind::Album, "57fd6b27".into(), Some("0".into())))
+        );
+        assert_eq!(
+            parts("ar-57fd6b27"),
+            Some((CoverArtKind::Artist, "57fd6b27".into(), None))
+        );
+        assert_eq!(
+            parts("mf-a_b_64b2a1f0"),
+            Some((CoverArtKind::MediaFile, "a_b".into(), Some("64b2a1f0".into())))
+        );
+        assert_eq!(
+            parts("mf-a_xyz"),
+            Some((CoverArtKind::MediaFile, "a_xyz".into(), None))
+        );
+        assert_eq!(parts("al-"), None);
+        assert_eq!(parts("xx-123"), None);
+        assert_eq!(parts("123"), None);
+
+        let id = CoverArtId::from_parts(CoverArtKind::Playlist, "abc", None);
+        assert_eq!(id, "pl-abc");
+        assert_eq!(id.kind(), Some(CoverArtKind::Playlist));
+    }
 }
@@ -0,0 +1,886 @@
+//! Semantic comparison of responses, e.g. to find where two servers disagree.
+//!
+//! [`diff`] compares two [`Response`]s field by field and reports every [`Difference`] with its
+//! path, like `album.song[id=3].year`. Lists are compared ignoring their order, matching their
+//! elements by `id`, `name`, `value` or `username`, except for the lists whose order is defined,
+//! see [`DiffOptions::ordered`]. An empty list is the same as an absent one and date times are
+//! equal if they are within [`DiffOptions::date_time_tolerance`] of each other.
+//!
+//! [`Comparison`] sends a list of requests to two transports and collects the differences of
+//! their responses in a [`Report`].
+//!
+//! # Example
+//! ```rust
+//! use subsonic_types::{
+//!     common::Version,
+//!     diff,
+//!     response::{Genre, Genres, Response},
+//! };
+//!
+//! let genre = |name: &str, song_count| Genre { song_count, album_count: 1, name: name.into() };
+//! let left = Response::genres(Version::LATEST, Genres { genre: vec![genre("Rock", 1), genre("Pop", 2)] });
+//! let right = Response::genres(Version::LATEST, Genres { genre: vec![genre("Pop", 3), genre("Rock", 1)] });
+//!
+//! let differences = diff::diff(&left, &right);
+//! assert_eq!(1, differences.len());
+//! assert_eq!("genres.genre[name=Pop].songCount: 2 != 3", differences[0].to_string());
+//! ```
+
+use std::{collections::BTreeMap, convert::Infallible, fmt::Write, time::Duration};
+
+use serde_json::Value;
+use time::OffsetDateTime;
+
+use crate::{
+    binary::{BinaryResponse, Media},
+    common::{DateTime, Format, Version},
+    response::Response,
+    transport::{self, AsyncTransport, Transport, TransportRequest, TransportResponse},
+};
+
+/// Fields used to match the elements of unordered lists, in order of preference.
+const KEY_FIELDS: &[&str] = &["id", "name", "value", "username"];
+
+/// A field that differs between two responses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difference {
+    /// The path of the field, e.g. `album.song[id=3].year`.
+    pub path: String,
+    /// The value of the left response, `None` if absent.
+    pub left: Option<Value>,
+    /// The value of the right response, `None` if absent.
+    pub right: Option<Value>,
+}
+
+impl Difference {
+    /// The path without the list selectors, e.g. `album.song.year`.
+    pub fn field(&self) -> String {
+        let mut field = String::new();
+        let mut depth = 0;
+        for c in self.path.chars() {
+            match c {
+                '[' => depth += 1,
+                ']' => depth -= 1,
+                c if depth == 0 => field.push(c),
+                _ => {}
+            }
+        }
+        field
+    }
+}
+
+impl std::fmt::Display for Difference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = |value: &Option<Value>| match value {
+            Some(value) => value.to_string(),
+            None => "absent".to_string(),
+        };
+        write!(
+            f,
+            "{}: {} != {}",
+            self.path,
+            value(&self.left),
+            value(&self.right)
+        )
+    }
+}
+
+/// Options of [`DiffOptions::diff`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffOptions {
+    ordered: Vec<String>,
+    ignored: Vec<String>,
+    date_time_tolerance: Duration,
+}
+
+impl Default for DiffOptions {
+    /// The entries of playlists, play queues and jukebox playlists are ordered and date times
+    /// can differ by a second.
+    fn default() -> Self {
+        Self {
+            ordered: vec![
+                "playlist.entry".to_string(),
+                "playQueue.entry".to_string(),
+                "jukeboxPlaylist.entry".to_string(),
+            ],
+            ignored: Vec::new(),
+            date_time_tolerance: Duration::from_secs(1),
+        }
+    }
+}
+
+impl DiffOptions {
+    /// Compares the order of the list at `field`, e.g. `albumList2.album` for a sorted list type.
+    pub fn ordered(mut self, field: impl Into<String>) -> Self {
+        self.ordered.push(field.into());
+        self
+    }
+
+    /// Ignores `field` and everything below it, e.g. `version`.
+    pub fn ignore(mut self, field: impl Into<String>) -> Self {
+        self.ignored.push(field.into());
+        self
+    }
+
+    /// The maximum difference between two date times that are considered equal.
+    pub fn date_time_tolerance(mut self, tolerance: Duration) -> Self {
+        self.date_time_tolerance = tolerance;
+        self
+    }
+
+    /// Returns the differences between two responses.
+    pub fn diff(&self, left: &Response, right: &Response) -> Vec<Difference> {
+        let mut differences = Vec::new();
+        self.compare(
+            "",
+            "",
+            &response_value(left),
+            &response_value(right),
+            &mut differences,
+        );
+        differences
+    }
+
+    fn compare(
+        &self,
+        path: &str,
+        field: &str,
+        left: &Value,
+        right: &Value,
+        differences: &mut Vec<Difference>,
+    ) {
+        if self.ignored.iter().any(|ignored| ignored == field) {
+            return;
+        }
+        match (left, right) {
+            (Value::Object(l), Value::Object(r)) => {
+                let mut keys = l.keys().chain(r.keys()).collect::<Vec<_>>();
+                keys.sort();
+                keys.dedup();
+                for key in keys {
+                    self.compare(
+                        &join(path, key),
+                        &join(field, key),
+                        l.get(key).unwrap_or(&Value::Null),
+                        r.get(key).unwrap_or(&Value::Null),
+                        differences,
+                    );
+                }
+            }
+            // An absent list is the same as an empty one.
+            (Value::Array(_) | Value::Null, Value::Array(_) | Value::Null) => {
+                let (l, r) = (as_slice(left), as_slice(right));
+                if self.ordered.iter().any(|ordered| ordered == field) {
+                    self.compare_ordered(path, field, l, r, differences);
+                } else {
+                    self.compare_unordered(path, field, l, r, differences);
+                }
+            }
+            (Value::Number(l), Value::Number(r)) if l.as_f64() == r.as_f64() => {}
+            (Value::String(l), Value::String(r)) if self.same_date_time(l, r) => {}
+            _ if left == right => {}
+            _ => differences.push(Difference {
+                path: path.to_string(),
+                left: present(left),
+                right: present(right),
+            }),
+        }
+    }
+
+    fn compare_ordered(
+        &self,
+        path: &str,
+        field: &str,
+        left: &[Value],
+        right: &[Value],
+        differences: &mut Vec<Difference>,
+    ) {
+        for i in 0..left.len().max(right.len()) {
+            self.compare(
+                &format!("{}[{}]", path, i),
+                field,
+                left.get(i).unwrap_or(&Value::Null),
+                right.get(i).unwrap_or(&Value::Null),
+                differences,
+            );
+        }
+    }
+
+    /// Elements are matched by key if every element has one, otherwise equal elements are
+    /// matched and the rest are compared in order.
+    fn compare_unordered(
+        &self,
+        path: &str,
+        field: &str,
+        left: &[Value],
+        right: &[Value],
+        differences: &mut Vec<Difference>,
+    ) {
+        if let Some(key) = KEY_FIELDS.iter().find(|key| {
+            left.iter()
+                .chain(right)
+                .all(|value| element_key(value, key).is_some())
+        }) {
+            let mut groups = BTreeMap::<String, (Vec<&Value>, Vec<&Value>)>::new();
+            for value in left {
+                groups
+                    .entry(element_key(value, key).unwrap())
+                    .or_default()
+                    .0
+                    .push(value);
+            }
+            for value in right {
+                groups
+                    .entry(element_key(value, key).unwrap())
+                    .or_default()
+                    .1
+                    .push(value);
+            }
+            for (id, (l, r)) in groups {
+                let element = format!("{}[{}={}]", path, key, id);
+                for i in 0..l.len().max(r.len()) {
+                    let element = match i {
+                        0 => element.clone(),
+                        i => format!("{}[{}]", element, i),
+                    };
+                    self.compare(
+                        &element,
+                        field,
+                        l.get(i).copied().unwrap_or(&Value::Null),
+                        r.get(i).copied().unwrap_or(&Value::Null),
+                        differences,
+                    );
+                }
+            }
+            return;
+        }
+
+        let mut unmatched = right.iter().collect::<Vec<_>>();
+        let mut remaining = Vec::new();
+        for (i, l) in left.iter().enumerate() {
+            match unmatched.iter().position(|r| self.is_same(field, l, r)) {
+                Some(position) => {
+                    unmatched.remove(position);
+                }
+                None => remaining.push((i, l)),
+            }
+        }
+        for i in 0..remaining.len().max(unmatched.len()) {
+            let index = remaining.get(i).map_or(left.len() + i, |(index, _)| *index);
+            self.compare(
+                &format!("{}[{}]", path, index),
+                field,
+                remaining.get(i).map_or(&Value::Null, |(_, value)| value),
+                unmatched.get(i).copied().unwrap_or(&Value::Null),
+                differences,
+            );
+        }
+    }
+
+    fn is_same(&self, field: &str, left: &Value, right: &Value) -> bool {
+        let mut differences = Vec::new();
+        self.compare("", field, left, right, &mut differences);
+        differences.is_empty()
+    }
+
+    fn same_date_time(&self, left: &str, right: &str) -> bool {
+        match (left.parse::<DateTime>(), right.parse::<DateTime>()) {
+            (Ok(l), Ok(r)) => {
+                let difference = OffsetDateTime::from(l) - OffsetDateTime::from(r);
+                difference.unsigned_abs() <= self.date_time_tolerance
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Returns the differences between two responses using the default [`DiffOptions`].
+pub fn diff(left: &Response, right: &Response) -> Vec<Difference> {
+    DiffOptions::default().diff(left, right)
+}
+
+/// The json representation of a response, with every field of the latest version.
+fn response_value(response: &Response) -> Value {
+    let json = response
+        .to_json_versioned(Version::LATEST)
+        .ok()
+        .and_then(|json| serde_json::from_str::<Value>(&json).ok());
+    match json {
+        Some(Value::Object(mut map)) => map.remove("subsonic-response").unwrap_or(Value::Null),
+        _ => Value::Null,
+    }
+}
+
+fn as_slice(value: &Value) -> &[Value] {
+    match value {
+        Value::Array(values) => values,
+        _ => &[],
+    }
+}
+
+fn element_key(value: &Value, key: &str) -> Option<String> {
+    match value.get(key)? {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+fn join(path: &str, key: &str) -> String {
+    match path.is_empty() {
+        true => key.to_string(),
+        false => format!("{}.{}", path, key),
+    }
+}
+
+fn present(value: &Value) -> Option<Value> {
+    match value {
+        Value::Null => None,
+        value => Some(value.clone()),
+    }
+}
+
+/// The outcome of a request sent to both transports of a [`Comparison`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// The responses are the same, or both requests failed the same way.
+    Same,
+    Different(Vec<Difference>),
+    /// The request failed on at least one side, with the error of each side that failed.
+    Failed {
+        left: Option<String>,
+        right: Option<String>,
+    },
+}
+
+/// A request and its [`Outcome`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub request: TransportRequest,
+    pub outcome: Outcome,
+}
+
+/// The result of a [`Comparison`].
+///
+/// Its [`Display`](std::fmt::Display) implementation summarizes the outcomes per endpoint,
+/// with the number of times each field differs.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Report {
+    pub entries: Vec<Entry>,
+}
+
+impl Report {
+    /// Returns `true` if every request had the same outcome on both sides.
+    pub fn is_same(&self) -> bool {
+        self.entries
+            .iter()
+            .all(|entry| entry.outcome == Outcome::Same)
+    }
+}
+
+#[derive(Default)]
+struct EndpointSummary {
+    same: usize,
+    different: usize,
+    failed: usize,
+    fields: BTreeMap<String, usize>,
+}
+
+impl std::fmt::Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut endpoints = BTreeMap::<&str, EndpointSummary>::new();
+        for entry in &self.entries {
+            let path = entry.request.path.as_str();
+            let summary = endpoints
+                .entry(path.strip_suffix(".view").unwrap_or(path))
+                .or_default();
+            match &entry.outcome {
+                Outcome::Same => summary.same += 1,
+                Outcome::Different(differences) => {
+                    summary.different += 1;
+                    for difference in differences {
+                        *summary.fields.entry(difference.field()).or_default() += 1;
+                    }
+                }
+                Outcome::Failed { .. } => summary.failed += 1,
+            }
+        }
+
+        let count = |f: fn(&EndpointSummary) -> usize| endpoints.values().map(f).sum::<usize>();
+        writeln!(
+            f,
+            "{} requests: {} same, {} different, {} failed",
+            self.entries.len(),
+            count(|s| s.same),
+            count(|s| s.different),
+            count(|s| s.failed)
+        )?;
+        for (path, summary) in &endpoints {
+            if summary.different == 0 && summary.failed == 0 {
+                continue;
+            }
+            let mut line = format!("{}:", path);
+            for (count, name) in [(summary.different, "different"), (summary.failed, "failed")] {
+                if count > 0 {
+                    write!(line, " {} {}", count, name)?;
+                }
+            }
+            writeln!(f, "{}", line)?;
+            for (field, count) in &summary.fields {
+                writeln!(f, "  {}: {}", field, count)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A parsed response of a transport.
+#[allow(clippy::large_enum_variant)]
+enum Body {
+    Response(Response),
+    Media(Media),
+}
+
+impl Body {
+    /// Documents are parsed as [`Response`]s, anything else is media.
+    fn parse(format: Format, response: TransportResponse) -> Result<Self, String> {
+        let is_document = response
+            .content_type
+            .as_deref()
+            .is_none_or(|c| c.contains("xml") || c.contains("json"));
+        if is_document {
+            return transport::parse_response::<Infallible>(format, response)
+                .map(Body::Response)
+                .map_err(|err| err.to_string());
+        }
+        match transport::parse_binary_response::<Infallible>(response) {
+            Ok(BinaryResponse::Media(media)) => Ok(Body::Media(media)),
+            Ok(BinaryResponse::Error(err)) => Err(err.to_string()),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    fn diff(&self, other: &Self, options: &DiffOptions) -> Vec<Difference> {
+        let difference = |path: &str, left: Value, right: Value| Difference {
+            path: path.to_string(),
+            left: Some(left),
+            right: Some(right),
+        };
+        match (self, other) {
+            (Body::Response(l), Body::Response(r)) => options.diff(l, r),
+            (Body::Media(l), Body::Media(r)) => {
+                let mut differences = Vec::new();
+                if l.content_type != r.content_type {
+                    differences.push(difference(
+                        "contentType",
+                        l.content_type.clone().into(),
+                        r.content_type.clone().into(),
+                    ));
+                }
+                if l.data != r.data {
+                    differences.push(difference(
+                        "data",
+                        format!("{} bytes", l.data.len()).into(),
+                        format!("{} bytes", r.data.len()).into(),
+                    ));
+                }
+                differences
+            }
+            (l, r) => vec![difference("", l.kind().into(), r.kind().into())],
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Body::Response(_) => "response",
+            Body::Media(_) => "media",
+        }
+    }
+}
+
+/// Sends the same requests to two transports and compares their responses.
+#[derive(Debug, Clone)]
+pub struct Comparison<L, R> {
+    left: L,
+    right: R,
+    options: DiffOptions,
+}
+
+impl<L, R> Comparison<L, R> {
+    pub fn new(left: L, right: R) -> Self {
+        Self {
+            left,
+            right,
+            options: DiffOptions::default(),
+        }
+    }
+
+    pub fn options(mut self, options: DiffOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    fn outcome(
+        &self,
+        format: Format,
+        left: Result<TransportResponse, String>,
+        right: Result<TransportResponse, String>,
+    ) -> Outcome {
+        let left = left.and_then(|response| Body::parse(format, response));
+        let right = right.and_then(|response| Body::parse(format, response));
+        match (left, right) {
+            (Ok(l), Ok(r)) => {
+                let differences = l.diff(&r, &self.options);
+                match differences.is_empty() {
+                    true => Outcome::Same,
+                    false => Outcome::Different(differences),
+                }
+            }
+            (Err(l), Err(r)) if l == r => Outcome::Same,
+            (l, r) => Outcome::Failed {
+                left: l.err(),
+                right: r.err(),
+            },
+        }
+    }
+}
+
+impl<L, R> Comparison<L, R>
+where
+    L: Transport,
+    R: Transport,
+    L::Error: std::fmt::Display,
+    R::Error: std::fmt::Display,
+{
+    /// Sends every request to both transports, one after another.
+    pub fn run(&self, requests: impl IntoIterator<Item = TransportRequest>) -> Report {
+        let entries = requests
+            .into_iter()
+            .map(|request| {
+                let left = self.left.execute(request.clone());
+                let right = self.right.execute(request.clone());
+                let outcome = self.outcome(
+                    request.format,
+                    left.map_err(|err| err.to_string()),
+                    right.map_err(|err| err.to_string()),
+                );
+                Entry { request, outcome }
+            })
+            .collect();
+        Report { entries }
+    }
+}
+
+impl<L, R> Comparison<L, R>
+where
+    L: AsyncTransport,
+    R: AsyncTransport,
+    L::Error: std::fmt::Display,
+    R::Error: std::fmt::Display,
+{
+    /// Sends every request to both transports, one after another.
+    pub async fn run_async(&self, requests: impl IntoIterator<Item = TransportRequest>) -> Report {
+        let mut entries = Vec::new();
+        for request in requests {
+            let left = self.left.execute(request.clone()).await;
+            let right = self.right.execute(request.clone()).await;
+            let outcome = self.outcome(
+                request.format,
+                left.map_err(|err| err.to_string()),
+                right.map_err(|err| err.to_string()),
+            );
+            entries.push(Entry { request, outcome });
+        }
+        Report { entries }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        request::{browsing, system, Request, SubsonicRequest},
+        response::{
+            AlbumID3, AlbumList2, Child, Genre, Genres, Playlist, PlaylistWithSongs, ResponseBody,
+        },
+        service::{self, ServiceTransport, SubsonicService},
+        test_support,
+        transport::Parameters,
+    };
+
+    use super::*;
+
+    fn compare(options: &DiffOptions, left: Value, right: Value) -> Vec<String> {
+        let mut differences = Vec::new();
+        options.compare("", "", &left, &right, &mut differences);
+        differences.iter().map(ToString::to_string).collect()
+    }
+
+    fn album(id: &str, year: u32) -> AlbumID3 {
+        AlbumID3 {
+            id: id.into(),
+            name: format!("Album {}", id),
+            year: Some(year),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn unordered_lists() {
+        let list = |albums| Response::album_list2(Version::LATEST, AlbumList2 { album: albums });
+        let left = list(vec![album("1", 2000), album("2", 2001)]);
+        let right = list(vec![album("2", 2001), album("1", 2000)]);
+        assert!(diff(&left, &right).is_empty());
+
+        let right = list(vec![album("2", 2001), album("1", 1999), album("3", 2002)]);
+        let differences = diff(&left, &right);
+        assert_eq!(
+            vec![
+                "albumList2.album[id=1].year: 2000 != 1999",
+                r#"albumList2.album[id=3]: absent != {"duration":0,"id":"3","name":"Album 3","songCount":0,"year":2002}"#,
+            ],
+            differences
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!("albumList2.album.year", differences[0].field());
+
+        // Sorted list types define the order.
+        let options = DiffOptions::default().ordered("albumList2.album");
+        let right = list(vec![album("2", 2001), album("1", 2000)]);
+        assert_eq!(6, options.diff(&left, &right).len());
+
+        // Elements without a key are matched by value.
+        assert_eq!(
+            vec![r#"[1]: "b" != "c""#],
+            compare(
+                &DiffOptions::default(),
+                json!(["a", "b", "x"]),
+                json!(["x", "c", "a"])
+            )
+        );
+    }
+
+    #[test]
+    fn ordered_lists() {
+        let playlist = |ids: &[&str]| {
+            let playlist = PlaylistWithSongs {
+                playlist: Playlist {
+                    id: "p".into(),
+                    name: "Playlist".into(),
+                    ..Default::default()
+                },
+                entry: ids
+                    .iter()
+                    .map(|id| Child::builder(*id, *id).build())
+                    .collect(),
+            };
+            Response::playlist(Version::LATEST, playlist)
+        };
+        assert!(diff(&playlist(&["1", "2"]), &playlist(&["1", "2"])).is_empty());
+        assert_eq!(
+            vec![
+                r#"playlist.entry[0].id: "1" != "2""#,
+                r#"playlist.entry[0].title: "1" != "2""#,
+                r#"playlist.entry[1].id: "2" != "1""#,
+                r#"playlist.entry[1].title: "2" != "1""#,
+            ],
+            diff(&playlist(&["1", "2"]), &playlist(&["2", "1"]))
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn equivalent_encodings() {
+        let options = DiffOptions::default();
+        assert!(compare(&options, json!({ "a": [] }), json!({})).is_empty());
+        assert!(compare(&options, json!({ "a": 1 }), json!({ "a": 1.0 })).is_empty());
+        assert!(compare(
+            &options,
+            json!({ "created": "2020-01-01T00:00:00" }),
+            json!({ "created": "2020-01-01T00:00:00.999Z" })
+        )
+        .is_empty());
+        assert_eq!(
+            vec![r#"created: "2020-01-01T00:00:00" != "2020-01-01T00:00:02""#],
+            compare(
+                &options,
+                json!({ "created": "2020-01-01T00:00:00" }),
+                json!({ "created": "2020-01-01T00:00:02" })
+            )
+        );
+        assert_eq!(
+            vec!["a: absent != 1"],
+            compare(&options, json!({}), json!({ "a": 1 }))
+        );
+
+        let options = options.ignore("version");
+        assert!(compare(
+            &options,
+            json!({ "version": "1.15.0" }),
+            json!({ "version": "1.16.1" })
+        )
+        .is_empty());
+    }
+
+    /// A blocking transport that answers every path with the same response.
+    struct Static(Vec<(&'static str, Result<TransportResponse, &'static str>)>);
+
+    impl Transport for Static {
+        type Error = String;
+
+        fn execute(&self, request: TransportRequest) -> Result<TransportResponse, Self::Error> {
+            let (_, response) = self
+                .0
+                .iter()
+                .find(|(path, _)| *path == request.path)
+                .expect("unexpected request");
+            response.clone().map_err(str::to_string)
+        }
+    }
+
+    fn document(body: &str) -> Result<TransportResponse, &'static str> {
+        Ok(TransportResponse {
+            status: 200,
+            content_type: Some("application/json".into()),
+            content_range: None,
+            body: format!(
+                r#"{{"subsonic-response":{{"status":"ok","version":"1.16.1"{}}}}}"#,
+                body
+            )
+            .into_bytes(),
+        })
+    }
+
+    fn media(data: &[u8]) -> Result<TransportResponse, &'static str> {
+        Ok(TransportResponse {
+            status: 200,
+            content_type: Some("image/png".into()),
+            content_range: None,
+            body: data.to_vec(),
+        })
+    }
+
+    fn request(path: &str) -> TransportRequest {
+        TransportRequest {
+            path: path.into(),
+            parameters: Parameters::Query("f=json".into()),
+            format: Format::Json,
+        }
+    }
+
+    #[test]
+    fn comparison() {
+        let left = Static(vec![
+            ("/rest/ping", document("")),
+            (
+                "/rest/getGenres",
+                document(r#","genres":{"genre":[{"songCount":1,"albumCount":1,"name":"Rock"}]}"#),
+            ),
+            ("/rest/getCoverArt", media(&[1, 2])),
+            ("/rest/getSong", Err("connection refused")),
+        ]);
+        let right = Static(vec![
+            ("/rest/ping", document("")),
+            (
+                "/rest/getGenres",
+                document(r#","genres":{"genre":[{"songCount":2,"albumCount":1,"name":"Rock"}]}"#),
+            ),
+            ("/rest/getCoverArt", media(&[1, 2])),
+            (
+                "/rest/getSong",
+                document(r#","song":{"id":"1","isDir":false,"title":"Song"}"#),
+            ),
+        ]);
+        let requests = [
+            "/rest/ping",
+            "/rest/getGenres",
+            "/rest/getCoverArt",
+            "/rest/getSong",
+        ];
+        let report = Comparison::new(left, right).run(requests.map(request));
+
+        let outcomes = report
+            .entries
+            .iter()
+            .map(|entry| entry.outcome.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                Outcome::Same,
+                Outcome::Different(vec![Difference {
+                    path: "genres.genre[name=Rock].songCount".into(),
+                    left: Some(json!(1)),
+                    right: Some(json!(2)),
+                }]),
+                Outcome::Same,
+                Outcome::Failed {
+                    left: Some("connection refused".into()),
+                    right: None,
+                },
+            ],
+            outcomes
+        );
+        assert!(!report.is_same());
+        assert_eq!(
+            "4 requests: 2 same, 1 different, 1 failed\n\
+             /rest/getGenres: 1 different\n  genres.genre.songCount: 1\n\
+             /rest/getSong: 1 failed\n",
+            report.to_string()
+        );
+    }
+
+    struct Server(u32);
+
+    #[async_trait::async_trait]
+    impl SubsonicService for Server {
+        async fn ping(&self, request: Request<system::Ping>) -> service::Result<Response> {
+            Ok(Response::ok(request.version, ResponseBody::Empty))
+        }
+
+        async fn get_genres(
+            &self,
+            request: Request<browsing::GetGenres>,
+        ) -> service::Result<Response> {
+            let genre = Genre {
+                song_count: self.0,
+                album_count: 1,
+                name: "Rock".into(),
+            };
+            Ok(Response::genres(
+                request.version,
+                Genres { genre: vec![genre] },
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn comparison_async() {
+        let ping = TransportRequest::query(&test_support::request(system::Ping));
+        let genres = TransportRequest {
+            path: browsing::GetGenres::PATH.into(),
+            ..TransportRequest::query(&test_support::request(system::Ping))
+        };
+
+        let comparison = Comparison::new(
+            ServiceTransport::new(Server(1)),
+            ServiceTransport::new(Server(1)),
+        );
+        assert!(comparison
+            .run_async([ping.clone(), genres.clone()])
+            .await
+            .is_same());
+
+        let comparison = Comparison::new(
+            ServiceTransport::new(Server(1)),
+            ServiceTransport::new(Server(2)),
+        );
+        let report = comparison.run_async([ping, genres]).await;
+        assert_eq!(Outcome::Same, report.entries[0].outcome);
+        assert!(matches!(report.entries[1].outcome, Outcome::Different(_)));
+    }
+}
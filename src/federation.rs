@@ -0,0 +1,877 @@
+//! Aggregation of several Subsonic servers behind one [`SubsonicService`].
+//!
+//! [`FederatedService`] sends `search3`, `getAlbumList2`, `getStarred2`, `getPlaylists`,
+//! `getGenres` and `getRandomSongs` to every backend and merges the results. Every other request
+//! is routed to the backend that owns its IDs, requests without IDs go to the first backend.
+//! The IDs of each backend are rewritten with its own [`IdMapper`] so they never collide,
+//! see [`idmap`](crate::idmap).
+//!
+//! Merged artists, albums and songs are deduplicated by artist, album and title, compared
+//! case-insensitively, and the entry of the first backend is kept. These entries have no
+//! MusicBrainz ID in the Subsonic API so it cannot be used to match them.
+//! Genres with the same name are merged by summing their counts.
+//!
+//! `getAlbumList2` lists are merged in the order of their list type, except `random`, `highest`
+//! and `recent` whose order depends on data missing from the response: their albums are taken
+//! from each backend in turn.
+//!
+//! Backends are queried concurrently. A backend that fails is left out of the merged
+//! result, the request only fails if every backend fails.
+//!
+//! # Example
+//! ```rust
+//! use async_trait::async_trait;
+//! use subsonic_types::{
+//!     common::Version,
+//!     federation::FederatedService,
+//!     idmap::PrefixIdMapper,
+//!     request::{browsing::GetGenres, Authentication, Request},
+//!     response::{Genre, Genres, Response, ResponseBody},
+//!     service::{self, SubsonicService},
+//! };
+//!
+//! struct Server(u32);
+//!
+//! #[async_trait]
+//! impl SubsonicService for Server {
+//!     async fn get_genres(&self, request: Request<GetGenres>) -> service::Result<Response> {
+//!         let genre = Genre { song_count: self.0, album_count: 1, name: "Rock".into() };
+//!         Ok(Response::genres(request.version, Genres { genre: vec![genre] }))
+//!     }
+//! }
+//!
+//! let service = FederatedService::new()
+//!     .backend(PrefixIdMapper::new("a-"), Server(2))
+//!     .backend(PrefixIdMapper::new("b-"), Server(3));
+//!
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! let request = Request {
+//!     username: "admin".into(),
+//!     authentication: Authentication::Password("admin".into()),
+//!     version: Version::LATEST,
+//!     client: "example".into(),
+//!     format: None,
+//!     body: GetGenres,
+//! };
+//! let response = service.get_genres(request).await.unwrap();
+//! let ResponseBody::Genres(genres) = response.body else { unreachable!() };
+//! assert_eq!(5, genres.genre[0].song_count);
+//! assert_eq!(2, genres.genre[0].album_count);
+//! # });
+//! ```
+
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    future::{self, Future},
+    hash::Hash,
+    task::Poll,
+};
+
+use crate::{
+    binary::Media,
+    idmap::{map_ids, map_output, unmap_ids, IdMapper},
+    library::album_list,
+    request::{
+        annotation, bookmark, browsing, chat, jukebox, lists, playlists, podcast, radio, retrieval,
+        scan, search, sharing, system, user, Request, SubsonicRequest,
+    },
+    response::{
+        AlbumID3, AlbumList2, ArtistID3, Child, Error, ErrorCode, Genres, Playlists, Response,
+        ResponseBody, SearchResult3, Songs, Starred2,
+    },
+    service::{self, service_methods, SubsonicService},
+};
+
+struct Backend {
+    mapper: Box<dyn IdMapper + Send + Sync>,
+    service: Box<dyn SubsonicService>,
+}
+
+/// A [`SubsonicService`] that combines several backends into one server.
+#[derive(Default)]
+pub struct FederatedService {
+    backends: Vec<Backend>,
+}
+
+impl FederatedService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a backend. `mapper` must not accept the IDs created by the mappers of other backends,
+    /// e.g. [`PrefixIdMapper`](crate::idmap::PrefixIdMapper)s with distinct prefixes.
+    pub fn backend<M, S>(mut self, mapper: M, service: S) -> Self
+    where
+        M: IdMapper + Send + Sync + 'static,
+        S: SubsonicService,
+    {
+        self.backends.push(Backend {
+            mapper: Box::new(mapper),
+            service: Box::new(service),
+        });
+        self
+    }
+
+    /// Finds the first backend whose mapper accepts every ID of the request.
+    fn route<R>(&self, request: &Request<R>) -> service::Result<(&Backend, Request<R>)>
+    where
+        R: SubsonicRequest + Clone,
+    {
+        let mut error = None;
+        for backend in &self.backends {
+            let mut routed = request.clone();
+            match unmap_ids(&*backend.mapper, &mut routed) {
+                Ok(()) => return Ok((backend, routed)),
+                Err(err) => {
+                    error.get_or_insert(err);
+                }
+            }
+        }
+        Err(error.map_or_else(no_backends, Error::from))
+    }
+
+    /// Sends the request to every backend concurrently and returns the bodies of the successful
+    /// responses, in the order of the backends.
+    async fn fan_out<'s, R, F, Fut>(
+        &'s self,
+        request: &Request<R>,
+        call: F,
+    ) -> service::Result<Vec<ResponseBody>>
+    where
+        R: SubsonicRequest + Clone,
+        F: Fn(&'s dyn SubsonicService, Request<R>) -> Fut,
+        Fut: Future<Output = service::Result<Response>>,
+    {
+        let calls = self
+            .backends
+            .iter()
+            .map(|backend| call(&*backend.service, request.clone()));
+        let outputs = join_all(calls).await;
+
+        let mut bodies = Vec::new();
+        let mut error = None;
+        for (backend, output) in self.backends.iter().zip(outputs) {
+            match output {
+                Ok(Response {
+                    body: ResponseBody::Error(err),
+                    ..
+                })
+                | Err(err) => {
+                    error.get_or_insert(err);
+                }
+                Ok(mut response) => {
+                    map_ids(&*backend.mapper, &mut response);
+                    bodies.push(response.body);
+                }
+            }
+        }
+        match error {
+            Some(err) if bodies.is_empty() => Err(err),
+            _ => Ok(bodies),
+        }
+    }
+
+    async fn merge_search3(&self, request: Request<search::Search3>) -> service::Result<Response> {
+        let body = &request.body;
+        let (artist_offset, artist_count) = (body.artist_offset, body.artist_count);
+        let (album_offset, album_count) = (body.album_offset, body.album_count);
+        let (song_offset, song_count) = (body.song_offset, body.song_count);
+
+        // Every backend has to return everything up to the end of the requested page.
+        let mut inner = request.clone();
+        inner.body.artist_offset = Some(0);
+        inner.body.artist_count = Some(page_end(artist_offset, artist_count, 20));
+        inner.body.album_offset = Some(0);
+        inner.body.album_count = Some(page_end(album_offset, album_count, 20));
+        inner.body.song_offset = Some(0);
+        inner.body.song_count = Some(page_end(song_offset, song_count, 20));
+
+        let (mut artists, mut albums, mut songs) = (Vec::new(), Vec::new(), Vec::new());
+        for body in self.fan_out(&inner, |s, r| s.search3(r)).await? {
+            if let ResponseBody::SearchResult3(result) = body {
+                artists.push(result.artist);
+                albums.push(result.album);
+                songs.push(result.song);
+            }
+        }
+
+        let result = SearchResult3 {
+            artist: page(merge(artists, artist_key), artist_offset, artist_count, 20),
+            album: page(merge(albums, album_key), album_offset, album_count, 20),
+            song: page(merge(songs, song_key), song_offset, song_count, 20),
+        };
+        Ok(Response::search_result3(request.version, result))
+    }
+
+    async fn merge_get_album_list2(
+        &self,
+        request: Request<lists::GetAlbumList2>,
+    ) -> service::Result<Response> {
+        let (offset, size) = (request.body.offset, request.body.size);
+        let mut inner = request.clone();
+        inner.body.offset = Some(0);
+        inner.body.size = Some(page_end(offset, size, 10));
+
+        let mut albums = Vec::new();
+        for body in self.fan_out(&inner, |s, r| s.get_album_list2(r)).await? {
+            if let ResponseBody::AlbumList2(list) = body {
+                albums.push(list.album);
+            }
+        }
+
+        // The sort is stable: lists ordered by data that is not part of the response compare
+        // equal and stay interleaved.
+        let mut albums = merge(albums, album_key);
+        albums.sort_by(|a, b| album_list::compare(&request.body, a, b).unwrap_or(Ordering::Equal));
+        let list = AlbumList2 {
+            album: page(albums, offset, size, 10),
+        };
+        Ok(Response::album_list2(request.version, list))
+    }
+
+    async fn merge_get_random_songs(
+        &self,
+        request: Request<lists::GetRandomSongs>,
+    ) -> service::Result<Response> {
+        let mut songs = Vec::new();
+        for body in self.fan_out(&request, |s, r| s.get_random_songs(r)).await? {
+            if let ResponseBody::RandomSongs(list) = body {
+                songs.push(list.song);
+            }
+        }
+        let songs = Songs {
+            song: page(merge(songs, song_key), None, request.body.size, 10),
+        };
+        Ok(Response::random_songs(request.version, songs))
+    }
+
+    async fn merge_get_starred2(
+        &self,
+        request: Request<lists::GetStarred2>,
+    ) -> service::Result<Response> {
+        let (mut artists, mut albums, mut songs) = (Vec::new(), Vec::new(), Vec::new());
+        for body in self.fan_out(&request, |s, r| s.get_starred2(r)).await? {
+            if let ResponseBody::Starred2(starred) = body {
+                artists.push(starred.artist);
+                albums.push(starred.album);
+                songs.push(starred.song);
+            }
+        }
+        let starred = Starred2 {
+            artist: merge(artists, artist_key),
+            album: merge(albums, album_key),
+            song: merge(songs, song_key),
+        };
+        Ok(Response::starred2(request.version, starred))
+    }
+
+    /// Playlists belong to a single server so they are never merged.
+    async fn merge_get_playlists(
+        &self,
+        request: Request<playlists::GetPlaylists>,
+    ) -> service::Result<Response> {
+        let mut playlists = Playlists::default();
+        for body in self.fan_out(&request, |s, r| s.get_playlists(r)).await? {
+            if let ResponseBody::Playlists(list) = body {
+                playlists.playlist.extend(list.playlist);
+            }
+        }
+        Ok(Response::playlists(request.version, playlists))
+    }
+
+    async fn merge_get_genres(
+        &self,
+        request: Request<browsing::GetGenres>,
+    ) -> service::Result<Response> {
+        let mut genres = Genres::default();
+        let mut positions = HashMap::<String, usize>::new();
+        for body in self.fan_out(&request, |s, r| s.get_genres(r)).await? {
+            let ResponseBody::Genres(list) = body else {
+                continue;
+            };
+            for genre in list.genre {
+                match positions.get(&genre.name) {
+                    Some(&position) => {
+                        let merged = &mut genres.genre[position];
+                        merged.song_count += genre.song_count;
+                        merged.album_count += genre.album_count;
+                    }
+                    None => {
+                        positions.insert(genre.name.clone(), genres.genre.len());
+                        genres.genre.push(genre);
+                    }
+                }
+            }
+        }
+        Ok(Response::genres(request.version, genres))
+    }
+}
+
+fn no_backends() -> Error {
+    Error::with_message(ErrorCode::Generic, "no backends")
+}
+
+fn page_end(offset: Option<u32>, count: Option<u32>, default_count: u32) -> u32 {
+    offset
+        .unwrap_or(0)
+        .saturating_add(count.unwrap_or(default_count))
+}
+
+fn page<T>(items: Vec<T>, offset: Option<u32>, count: Option<u32>, default_count: u32) -> Vec<T> {
+    items
+        .into_iter()
+        .skip(offset.unwrap_or(0) as usize)
+        .take(count.unwrap_or(default_count) as usize)
+        .collect()
+}
+
+/// Awaits the futures concurrently and returns their outputs in the same order.
+async fn join_all<F: Future>(futures: impl IntoIterator<Item = F>) -> Vec<F::Output> {
+    let mut futures = futures
+        .into_iter()
+        .map(|future| Some(Box::pin(future)))
+        .collect::<Vec<_>>();
+    let mut outputs = futures.iter().map(|_| None).collect::<Vec<_>>();
+    future::poll_fn(|cx| {
+        let mut done = true;
+        for (slot, output) in futures.iter_mut().zip(&mut outputs) {
+            let Some(future) = slot else { continue };
+            match future.as_mut().poll(cx) {
+                Poll::Ready(value) => {
+                    *output = Some(value);
+                    *slot = None;
+                }
+                Poll::Pending => done = false,
+            }
+        }
+        match done {
+            true => Poll::Ready(()),
+            false => Poll::Pending,
+        }
+    })
+    .await;
+    outputs.into_iter().map(Option::unwrap).collect()
+}
+
+/// Takes one item of each list in turn, keeping the order of every list.
+fn interleave<T>(lists: Vec<Vec<T>>) -> Vec<T> {
+    let mut iters = lists.into_iter().map(Vec::into_iter).collect::<Vec<_>>();
+    let mut items = Vec::new();
+    loop {
+        let len = items.len();
+        items.extend(iters.iter_mut().filter_map(Iterator::next));
+        if items.len() == len {
+            return items;
+        }
+    }
+}
+
+/// Removes the items whose key was already seen in the same or a previous list and
+/// interleaves the rest, so duplicates keep the item of the first backend.
+fn merge<T, K, F>(lists: Vec<Vec<T>>, key: F) -> Vec<T>
+where
+    K: Hash + Eq,
+    F: Fn(&T) -> K,
+{
+    let mut seen = HashSet::new();
+    let lists = lists
+        .into_iter()
+        .map(|list| {
+            list.into_iter()
+                .filter(|item| seen.insert(key(item)))
+                .collect()
+        })
+        .collect();
+    interleave(lists)
+}
+
+fn normalize(value: &str) -> String {
+    value.trim().to_lowercase()
+}
+
+fn normalize_opt(value: &Option<String>) -> String {
+    value.as_deref().map(normalize).unwrap_or_default()
+}
+
+fn artist_key(artist: &ArtistID3) -> String {
+    normalize(&artist.name)
+}
+
+fn album_key(album: &AlbumID3) -> (String, String) {
+    (normalize_opt(&album.artist), normalize(&album.name))
+}
+
+fn song_key(song: &Child) -> (String, String, String) {
+    (
+        normalize_opt(&song.artist),
+        normalize_opt(&song.album),
+        normalize(&song.title),
+    )
+}
+
+macro_rules! federated_call {
+    ($self:ident, search3, $request:ident) => {
+        $self.merge_search3($request).await
+    };
+    ($self:ident, get_album_list2, $request:ident) => {
+        $self.merge_get_album_list2($request).await
+    };
+    ($self:ident, get_random_songs, $request:ident) => {
+        $self.merge_get_random_songs($request).await
+    };
+    ($self:ident, get_starred2, $request:ident) => {
+        $self.merge_get_starred2($request).await
+    };
+    ($self:ident, get_playlists, $request:ident) => {
+        $self.merge_get_playlists($request).await
+    };
+    ($self:ident, get_genres, $request:ident) => {
+        $self.merge_get_genres($request).await
+    };
+    ($self:ident, $name:ident, $request:ident) => {{
+        let (backend, request) = $self.route(&$request)?;
+        let mut output = backend.service.$name(request).await?;
+        map_output(&*backend.mapper, &mut output);
+        Ok(output)
+    }};
+}
+
+macro_rules! impl_federated_service {
+    ($($(#[$comment:meta])* ($name:ident $request:path => $output:ty)),*) => {
+        #[async_trait::async_trait]
+        impl SubsonicService for FederatedService {
+            $(
+                async fn $name(&self, request: Request<$request>) -> service::Result<$output> {
+                    federated_call!(self, $name, request)
+                }
+            )*
+        }
+    };
+}
+
+service_methods!(impl_federated_service);
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use tokio::sync::Barrier;
+
+    use crate::{
+        common::Version,
+        idmap::PrefixIdMapper,
+        response::{AlbumWithSongsID3, Genre, Playlist},
+        test_support::{album, artist, request, song},
+    };
+
+    use super::*;
+
+    fn genre(name: &str, song_count: u32, album_count: u32) -> Genre {
+        Genre {
+            song_count,
+            album_count,
+            name: name.into(),
+        }
+    }
+
+    fn dated(album: AlbumID3, year: u32) -> AlbumID3 {
+        AlbumID3 {
+            year: Some(year),
+            ..album
+        }
+    }
+
+    /// A stand-in server that pages its lists like a real one.
+    #[derive(Default)]
+    struct Server {
+        artists: Vec<ArtistID3>,
+        albums: Vec<AlbumID3>,
+        songs: Vec<Child>,
+        genres: Vec<Genre>,
+        playlists: Vec<Playlist>,
+        failing: bool,
+        /// Waited on before answering `getGenres`.
+        barrier: Option<Arc<Barrier>>,
+    }
+
+    impl Server {
+        fn check(&self) -> service::Result<()> {
+            match self.failing {
+                true => Err(Error::new(ErrorCode::Generic)),
+                false => Ok(()),
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl SubsonicService for Server {
+        async fn search3(&self, request: Request<search::Search3>) -> service::Result<Response> {
+            self.check()?;
+            let body = request.body;
+            let result = SearchResult3 {
+                artist: page(
+                    self.artists.clone(),
+                    body.artist_offset,
+                    body.artist_count,
+                    20,
+                ),
+                album: page(self.albums.clone(), body.album_offset, body.album_count, 20),
+                song: page(self.songs.clone(), body.song_offset, body.song_count, 20),
+            };
+            Ok(Response::search_result3(request.version, result))
+        }
+
+        async fn get_album_list2(
+            &self,
+            request: Request<lists::GetAlbumList2>,
+        ) -> service::Result<Response> {
+            self.check()?;
+            let mut albums = self.albums.clone();
+            albums.sort_by(|a, b| {
+                album_list::compare(&request.body, a, b).unwrap_or(Ordering::Equal)
+            });
+            let album = page(albums, request.body.offset, request.body.size, 10);
+            Ok(Response::album_list2(request.version, AlbumList2 { album }))
+        }
+
+        async fn get_random_songs(
+            &self,
+            request: Request<lists::GetRandomSongs>,
+        ) -> service::Result<Response> {
+            self.check()?;
+            let song = page(self.songs.clone(), None, request.body.size, 10);
+            Ok(Response::random_songs(request.version, Songs { song }))
+        }
+
+        async fn get_starred2(
+            &self,
+            request: Request<lists::GetStarred2>,
+        ) -> service::Result<Response> {
+            self.check()?;
+            let starred = Starred2 {
+                artist: self.artists.clone(),
+                album: self.albums.clone(),
+                song: self.songs.clone(),
+            };
+            Ok(Response::starred2(request.version, starred))
+        }
+
+        async fn get_playlists(
+            &self,
+            request: Request<playlists::GetPlaylists>,
+        ) -> service::Result<Response> {
+            self.check()?;
+            let playlists = Playlists {
+                playlist: self.playlists.clone(),
+            };
+            Ok(Response::playlists(request.version, playlists))
+        }
+
+        async fn get_genres(
+            &self,
+            request: Request<browsing::GetGenres>,
+        ) -> service::Result<Response> {
+            self.check()?;
+            if let Some(barrier) = &self.barrier {
+                barrier.wait().await;
+            }
+            let genres = Genres {
+                genre: self.genres.clone(),
+            };
+            Ok(Response::genres(request.version, genres))
+        }
+
+        async fn get_album(
+            &self,
+            request: Request<browsing::GetAlbum>,
+        ) -> service::Result<Response> {
+            self.check()?;
+            let album = self
+                .albums
+                .iter()
+                .find(|album| album.id == request.body.id)
+                .ok_or_else(|| Error::new(ErrorCode::DataNotFound))?;
+            let songs = self
+                .songs
+                .iter()
+                .filter(|song| song.album.as_deref() == Some(album.name.as_str()))
+                .cloned()
+                .collect();
+            let album = AlbumWithSongsID3 {
+                album: album.clone(),
+                song: songs,
+            };
+            Ok(Response::album(request.version, album))
+        }
+
+        async fn ping(&self, request: Request<system::Ping>) -> service::Result<Response> {
+            self.check()?;
+            Ok(Response::ok_empty(request.version))
+        }
+    }
+
+    fn federation() -> FederatedService {
+        let a = Server {
+            artists: vec![artist("1", "Artist A"), artist("2", "Shared")],
+            albums: vec![
+                dated(album("1", "Artist A", "Zebra"), 2001),
+                dated(album("2", "Shared", "Common"), 1999),
+            ],
+            songs: vec![
+                song("1", "Artist A", "Zebra", "One"),
+                song("2", "Shared", "Common", "Two"),
+            ],
+            genres: vec![genre("Rock", 2, 1), genre("Jazz", 1, 1)],
+            playlists: vec![Playlist {
+                id: "1".into(),
+                name: "Mine".into(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let b = Server {
+            artists: vec![artist("1", "shared"), artist("2", "Artist B")],
+            albums: vec![
+                dated(album("1", "SHARED", "common "), 1999),
+                dated(album("2", "Artist B", "Apple"), 2005),
+            ],
+            songs: vec![
+                song("1", "shared", "common", "two"),
+                song("2", "Artist B", "Apple", "Three"),
+            ],
+            genres: vec![genre("Pop", 4, 2), genre("Rock", 3, 1)],
+            playlists: vec![Playlist {
+                id: "1".into(),
+                name: "Mine".into(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        FederatedService::new()
+            .backend(PrefixIdMapper::new("a-"), a)
+            .backend(PrefixIdMapper::new("b-"), b)
+    }
+
+    fn ids<T>(items: &[T], id: impl Fn(&T) -> String) -> Vec<String> {
+        items.iter().map(id).collect()
+    }
+
+    #[tokio::test]
+    async fn search3() {
+        let service = federation();
+        let response = service
+            .search3(request(search::Search3::builder("").build()))
+            .await
+            .unwrap();
+        let ResponseBody::SearchResult3(result) = response.body else {
+            panic!("{:?}", response.body)
+        };
+        assert_eq!(
+            vec!["a-1", "b-2", "a-2"],
+            ids(&result.artist, |a| a.id.to_string())
+        );
+        assert_eq!(
+            vec!["a-1", "b-2", "a-2"],
+            ids(&result.album, |a| a.id.to_string())
+        );
+        assert_eq!(
+            vec!["a-1", "b-2", "a-2"],
+            ids(&result.song, |s| s.id.to_string())
+        );
+
+        // The page is taken from the merged results.
+        let search = search::Search3::builder("")
+            .song_offset(1)
+            .song_count(1)
+            .build();
+        let response = service.search3(request(search)).await.unwrap();
+        let ResponseBody::SearchResult3(result) = response.body else {
+            panic!("{:?}", response.body)
+        };
+        assert_eq!(vec!["b-2"], ids(&result.song, |s| s.id.to_string()));
+    }
+
+    #[tokio::test]
+    async fn album_list2() {
+        let service = federation();
+        let list = lists::GetAlbumList2::builder(lists::ListType::AlphabeticalByName).build();
+        let response = service.get_album_list2(request(list)).await.unwrap();
+        let ResponseBody::AlbumList2(list) = response.body else {
+            panic!("{:?}", response.body)
+        };
+        assert_eq!(
+            vec!["Apple", "Common", "Zebra"],
+            ids(&list.album, |a| a.name.clone())
+        );
+
+        let list = lists::GetAlbumList2::builder(lists::ListType::AlphabeticalByName)
+            .offset(1)
+            .size(1)
+            .build();
+        let response = service.get_album_list2(request(list)).await.unwrap();
+        let ResponseBody::AlbumList2(list) = response.body else {
+            panic!("{:?}", response.body)
+        };
+        assert_eq!(vec!["a-2"], ids(&list.album, |a| a.id.to_string()));
+
+        // Every list type with an order is sorted again, not only the alphabetical ones.
+        let list = lists::GetAlbumList2::builder(lists::ListType::ByYear)
+            .from_year(2010)
+            .to_year(1990)
+            .build();
+        let response = service.get_album_list2(request(list)).await.unwrap();
+        let ResponseBody::AlbumList2(list) = response.body else {
+            panic!("{:?}", response.body)
+        };
+        assert_eq!(
+            vec!["b-2", "a-1", "a-2"],
+            ids(&list.album, |a| a.id.to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn random_songs_and_starred2() {
+        let service = federation();
+        let songs = lists::GetRandomSongs::builder().size(2).build();
+        let response = service.get_random_songs(request(songs)).await.unwrap();
+        let ResponseBody::RandomSongs(songs) = response.body else {
+            panic!("{:?}", response.body)
+        };
+        assert_eq!(vec!["a-1", "b-2"], ids(&songs.song, |s| s.id.to_string()));
+
+        let starred = lists::GetStarred2::builder().build();
+        let response = service.get_starred2(request(starred)).await.unwrap();
+        let ResponseBody::Starred2(starred) = response.body else {
+            panic!("{:?}", response.body)
+        };
+        assert_eq!(3, starred.artist.len());
+        assert_eq!(3, starred.album.len());
+        assert_eq!(
+            vec!["a-1", "b-2", "a-2"],
+            ids(&starred.song, |s| s.id.to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn playlists_and_genres() {
+        let service = federation();
+        let playlists = playlists::GetPlaylists::builder().build();
+        let response = service.get_playlists(request(playlists)).await.unwrap();
+        let ResponseBody::Playlists(playlists) = response.body else {
+            panic!("{:?}", response.body)
+        };
+        assert_eq!(
+            vec!["a-1", "b-1"],
+            ids(&playlists.playlist, |p| p.id.to_string())
+        );
+
+        let response = service
+            .get_genres(request(browsing::GetGenres))
+            .await
+            .unwrap();
+        assert_eq!(
+            Response::genres(
+                Version::LATEST,
+                Genres {
+                    genre: vec![genre("Rock", 5, 2), genre("Jazz", 1, 1), genre("Pop", 4, 2)],
+                }
+            ),
+            response
+        );
+    }
+
+    #[tokio::test]
+    async fn routing() {
+        let service = federation();
+        let response = service
+            .get_album(request(browsing::GetAlbum { id: "b-2".into() }))
+            .await
+            .unwrap();
+        let ResponseBody::Album(album) = response.body else {
+            panic!("{:?}", response.body)
+        };
+        assert_eq!(album.album.id, "b-2");
+        assert_eq!(vec!["b-2"], ids(&album.song, |s| s.id.to_string()));
+
+        let err = service
+            .get_album(request(browsing::GetAlbum { id: "c-1".into() }))
+            .await
+            .unwrap_err();
+        assert_eq!(ErrorCode::DataNotFound, err.code);
+
+        // Requests without IDs go to the first backend.
+        let service = FederatedService::new()
+            .backend(
+                PrefixIdMapper::new("a-"),
+                Server {
+                    failing: true,
+                    ..Default::default()
+                },
+            )
+            .backend(PrefixIdMapper::new("b-"), Server::default());
+        assert!(service.ping(request(system::Ping)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn concurrent_backends() {
+        // Each backend waits for the other one, so querying them in turn would never finish.
+        let barrier = Arc::new(Barrier::new(2));
+        let server = |name: &str| Server {
+            genres: vec![genre(name, 1, 1)],
+            barrier: Some(barrier.clone()),
+            ..Default::default()
+        };
+        let service = FederatedService::new()
+            .backend(PrefixIdMapper::new("a-"), server("Rock"))
+            .backend(PrefixIdMapper::new("b-"), server("Jazz"));
+        let response = tokio::time::timeout(
+            Duration::from_secs(10),
+            service.get_genres(request(browsing::GetGenres)),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        let ResponseBody::Genres(genres) = response.body else {
+            panic!("{:?}", response.body)
+        };
+        assert_eq!(vec!["Rock", "Jazz"], ids(&genres.genre, |g| g.name.clone()));
+    }
+
+    #[tokio::test]
+    async fn failing_backend() {
+        let working = Server {
+            genres: vec![genre("Rock", 1, 1)],
+            ..Default::default()
+        };
+        let failing = || Server {
+            failing: true,
+            ..Default::default()
+        };
+
+        let service = FederatedService::new()
+            .backend(PrefixIdMapper::new("a-"), failing())
+            .backend(PrefixIdMapper::new("b-"), working);
+        let response = service
+            .get_genres(request(browsing::GetGenres))
+            .await
+            .unwrap();
+        assert_eq!(
+            Response::genres(
+                Version::LATEST,
+                Genres {
+                    genre: vec![genre("Rock", 1, 1)]
+                }
+            ),
+            response
+        );
+
+        let service = FederatedService::new()
+            .backend(PrefixIdMapper::new("a-"), failing())
+            .backend(PrefixIdMapper::new("b-"), failing());
+        assert!(service
+            .get_genres(request(browsing::GetGenres))
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,31 @@
+//! Lowercase hexadecimal encoding of bytes.
+
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Decodes `hex`, in either case. Returns `None` for an odd length or a non hex digit.
+pub(crate) fn decode(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        assert_eq!("00ff7f", encode(&[0, 255, 127]));
+        assert_eq!(Some(vec![0, 255, 127]), decode("00FF7f"));
+        assert_eq!(None, decode("abc"));
+        assert_eq!(None, decode("zz"));
+        assert_eq!(None, decode("é0"));
+        assert_eq!(Some(vec![]), decode(""));
+    }
+}
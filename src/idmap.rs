@@ -0,0 +1,402 @@
+//! Rewriting of IDs, for example to put several servers behind one endpoint without collisions.
+//!
+//! An [`IdMapper`] maps the IDs of a server to the IDs seen by clients and back.
+//! [`map_ids`] and [`unmap_ids`] apply it to every ID of a response or request, and
+//! [`MappedService`] applies it to every request and response of a [`SubsonicService`].
+//! [`MusicFolderId`](crate::common::MusicFolderId)s are numbers and are not mapped.
+//!
+//! # Example
+//! ```rust
+//! use subsonic_types::{
+//!     common::Version,
+//!     idmap::{self, PrefixIdMapper},
+//!     request::browsing::GetSong,
+//!     response::{Child, Response, ResponseBody},
+//! };
+//!
+//! let mapper = PrefixIdMapper::new("a-");
+//!
+//! let mut response = Response::song(Version::LATEST, Child::builder("1", "Song").album_id("2"));
+//! idmap::map_ids(&mapper, &mut response);
+//! let ResponseBody::Song(song) = &response.body else { unreachable!() };
+//! assert_eq!(song.id, "a-1");
+//! assert_eq!(song.album_id, Some("a-2".into()));
+//!
+//! let mut request = GetSong { id: "a-1".into() };
+//! idmap::unmap_ids(&mapper, &mut request).unwrap();
+//! assert_eq!(request.id, "1");
+//!
+//! let mut request = GetSong { id: "b-1".into() };
+//! assert!(idmap::unmap_ids(&mapper, &mut request).is_err());
+//! ```
+
+use std::sync::Arc;
+
+use crate::{
+    binary::Media,
+    request::{
+        annotation, bookmark, browsing, chat, jukebox, lists, playlists, podcast, radio, retrieval,
+        scan, search, sharing, system, user, Request,
+    },
+    response::{Error, ErrorCode, Response},
+    service::{self, service_methods, ServiceOutput, SubsonicService},
+    visit::{IdKind, VisitMut, VisitorMut},
+};
+
+/// A reversible mapping between the IDs of a server and the IDs seen by clients.
+///
+/// The same ID can appear with different kinds, e.g. a song ID is also used as an [`IdKind::Id`]
+/// and as an [`IdKind::CoverArt`], so implementations should map every kind the same way.
+pub trait IdMapper {
+    /// Maps an ID of the server to the ID seen by clients.
+    fn map(&self, kind: IdKind, id: &str) -> String;
+
+    /// Maps an ID seen by clients back to the ID of the server.
+    /// Returns `None` if the ID was not created by this mapper.
+    fn unmap(&self, kind: IdKind, id: &str) -> Option<String>;
+}
+
+impl<T: IdMapper + ?Sized> IdMapper for &T {
+    fn map(&self, kind: IdKind, id: &str) -> String {
+        T::map(self, kind, id)
+    }
+
+    fn unmap(&self, kind: IdKind, id: &str) -> Option<String> {
+        T::unmap(self, kind, id)
+    }
+}
+
+impl<T: IdMapper + ?Sized> IdMapper for Arc<T> {
+    fn map(&self, kind: IdKind, id: &str) -> String {
+        T::map(self, kind, id)
+    }
+
+    fn unmap(&self, kind: IdKind, id: &str) -> Option<String> {
+        T::unmap(self, kind, id)
+    }
+}
+
+/// Prepends a prefix to every ID.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PrefixIdMapper {
+    prefix: String,
+}
+
+impl PrefixIdMapper {
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+        }
+    }
+
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+}
+
+impl IdMapper for PrefixIdMapper {
+    fn map(&self, _: IdKind, id: &str) -> String {
+        format!("{}{}", self.prefix, id)
+    }
+
+    fn unmap(&self, _: IdKind, id: &str) -> Option<String> {
+        id.strip_prefix(&self.prefix).map(str::to_string)
+    }
+}
+
+/// An ID that was not created by the [`IdMapper`] it was given to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownId {
+    pub kind: IdKind,
+    pub id: String,
+}
+
+impl std::fmt::Display for UnknownId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown {:?} id: {}", self.kind, self.id)
+    }
+}
+
+impl std::error::Error for UnknownId {}
+
+impl From<UnknownId> for Error {
+    fn from(err: UnknownId) -> Self {
+        Error::with_message(ErrorCode::DataNotFound, err.to_string())
+    }
+}
+
+/// Maps every ID of `value` with [`IdMapper::map`].
+pub fn map_ids<M, T>(mapper: &M, value: &mut T)
+where
+    M: IdMapper + ?Sized,
+    T: VisitMut + ?Sized,
+{
+    struct Map<'a, M: ?Sized>(&'a M);
+
+    impl<'a, M: IdMapper + ?Sized> VisitorMut for Map<'a, M> {
+        fn visit_id_mut(&mut self, kind: IdKind, id: &mut String) {
+            *id = self.0.map(kind, id);
+        }
+    }
+
+    value.visit_mut(&mut Map(mapper));
+}
+
+/// Maps every ID of `value` with [`IdMapper::unmap`].
+///
+/// Fails with the first ID that was not created by the mapper, the other IDs are still unmapped.
+pub fn unmap_ids<M, T>(mapper: &M, value: &mut T) -> Result<(), UnknownId>
+where
+    M: IdMapper + ?Sized,
+    T: VisitMut + ?Sized,
+{
+    struct Unmap<'a, M: ?Sized> {
+        mapper: &'a M,
+        error: Option<UnknownId>,
+    }
+
+    impl<'a, M: IdMapper + ?Sized> VisitorMut for Unmap<'a, M> {
+        fn visit_id_mut(&mut self, kind: IdKind, id: &mut String) {
+            match self.mapper.unmap(kind, id) {
+                Some(unmapped) => *id = unmapped,
+                None if self.error.is_none() => {
+                    self.error = Some(UnknownId {
+                        kind,
+                        id: id.clone(),
+                    })
+                }
+                None => {}
+            }
+        }
+    }
+
+    let mut unmap = Unmap {
+        mapper,
+        error: None,
+    };
+    value.visit_mut(&mut unmap);
+    unmap.error.map_or(Ok(()), Err)
+}
+
+/// A [`SubsonicService`] that unmaps the IDs of every request and maps the IDs of every response
+/// of the wrapped service. Requests with unknown IDs fail with [`ErrorCode::DataNotFound`].
+#[derive(Debug, Clone)]
+pub struct MappedService<S, M> {
+    service: S,
+    mapper: M,
+}
+
+impl<S, M> MappedService<S, M>
+where
+    S: SubsonicService,
+    M: IdMapper + Send + Sync + 'static,
+{
+    pub fn new(service: S, mapper: M) -> Self {
+        Self { service, mapper }
+    }
+
+    pub fn service(&self) -> &S {
+        &self.service
+    }
+
+    pub fn mapper(&self) -> &M {
+        &self.mapper
+    }
+
+    pub fn into_inner(self) -> S {
+        self.service
+    }
+}
+
+/// Maps the IDs of the output of a [`SubsonicService`] method. Media has no IDs.
+pub(crate) fn map_output<M, O>(mapper: &M, output: &mut O)
+where
+    M: IdMapper + ?Sized,
+    O: ServiceOutput,
+{
+    if let Some(response) = output.as_response_mut() {
+        map_ids(mapper, response);
+    }
+}
+
+macro_rules! impl_mapped_service {
+    ($($(#[$comment:meta])* ($name:ident $request:path => $output:ty)),*) => {
+        #[async_trait::async_trait]
+        impl<S, M> SubsonicService for MappedService<S, M>
+        where
+            S: SubsonicService,
+            M: IdMapper + Send + Sync + 'static,
+        {
+            $(
+                async fn $name(&self, mut request: Request<$request>) -> service::Result<$output> {
+                    unmap_ids(&self.mapper, &mut request)?;
+                    let mut output = self.service.$name(request).await?;
+                    map_output(&self.mapper, &mut output);
+                    Ok(output)
+                }
+            )*
+        }
+    };
+}
+
+service_methods!(impl_mapped_service);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::Version,
+        response::{Child, ResponseBody},
+        service::ServiceTransport,
+        test_support::{request, sample_bodies},
+        transport,
+        visit::{Visit, Visitor},
+    };
+
+    use super::*;
+
+    fn ids<T: Visit>(value: &T) -> Vec<String> {
+        struct Ids(Vec<String>);
+
+        impl<'a> Visitor<'a> for Ids {
+            fn visit_id(&mut self, _: IdKind, id: &'a str) {
+                self.0.push(id.to_string());
+            }
+        }
+
+        let mut ids = Ids(Vec::new());
+        value.visit(&mut ids);
+        ids.0
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mapper = PrefixIdMapper::new("a:");
+        for body in sample_bodies() {
+            let original = Response::ok(Version::LATEST, body);
+            let mut response = original.clone();
+            map_ids(&mapper, &mut response);
+            let mapped = ids(&response);
+            assert!(mapped.iter().all(|id| id.starts_with("a:")), "{:?}", mapped);
+            assert_eq!(ids(&original).len(), mapped.len());
+
+            unmap_ids(&mapper, &mut response).unwrap();
+            assert_eq!(original, response);
+        }
+    }
+
+    #[test]
+    fn request_ids() {
+        let mapper = PrefixIdMapper::new("a:");
+
+        let mut star = annotation::Star {
+            id: vec!["a:1".into()],
+            album_id: vec!["a:2".into(), "a:3".into()],
+            artist_id: vec![],
+        };
+        unmap_ids(&mapper, &mut star).unwrap();
+        assert_eq!(vec!["2", "3"], ids(&star)[1..]);
+        assert_eq!(star.id, vec![crate::common::Id::from("1")]);
+
+        let mut queue = bookmark::SavePlayQueue::builder()
+            .id("a:1")
+            .id("2")
+            .current("a:3")
+            .build();
+        let err = unmap_ids(&mapper, &mut queue).unwrap_err();
+        assert_eq!(
+            UnknownId {
+                kind: IdKind::Song,
+                id: "2".into()
+            },
+            err
+        );
+        assert_eq!(vec!["1", "2", "3"], ids(&queue));
+
+        let error = Error::from(err);
+        assert_eq!(ErrorCode::DataNotFound, error.code);
+    }
+
+    #[test]
+    fn directory_ids() {
+        /// Gives directories and songs distinct prefixes.
+        struct KindMapper;
+
+        impl IdMapper for KindMapper {
+            fn map(&self, kind: IdKind, id: &str) -> String {
+                match kind {
+                    IdKind::Directory => format!("d:{}", id),
+                    _ => format!("s:{}", id),
+                }
+            }
+
+            fn unmap(&self, kind: IdKind, id: &str) -> Option<String> {
+                match kind {
+                    IdKind::Directory => id.strip_prefix("d:"),
+                    _ => id.strip_prefix("s:"),
+                }
+                .map(str::to_string)
+            }
+        }
+
+        let mut directory = crate::response::Directory {
+            id: "1".into(),
+            name: "Artist".into(),
+            child: vec![
+                Child::builder("2", "Album")
+                    .is_dir(true)
+                    .parent("1")
+                    .build(),
+                Child::builder("3", "Song").parent("1").build(),
+            ],
+            ..Default::default()
+        };
+        map_ids(&KindMapper, &mut directory);
+        assert_eq!(vec!["d:1", "d:2", "d:1", "s:3", "d:1"], ids(&directory));
+
+        let mut request = browsing::GetMusicDirectory {
+            id: directory.child[0].directory_id(),
+        };
+        unmap_ids(&KindMapper, &mut request).unwrap();
+        assert_eq!("2", request.id.as_str());
+    }
+
+    struct Server;
+
+    #[async_trait::async_trait]
+    impl SubsonicService for Server {
+        async fn get_song(&self, request: Request<browsing::GetSong>) -> service::Result<Response> {
+            let song = Child::builder(request.body.id.clone(), "Song").album_id("2");
+            Ok(Response::song(request.version, song))
+        }
+    }
+
+    #[tokio::test]
+    async fn mapped_service() {
+        let transport =
+            ServiceTransport::new(MappedService::new(Server, PrefixIdMapper::new("a:")));
+        let request = |id: &str| Request {
+            format: Some("json".into()),
+            ..request(browsing::GetSong { id: id.into() })
+        };
+
+        let response = transport::execute_async(&transport, &request("a:1"))
+            .await
+            .unwrap();
+        let expected = Response::song(
+            Version::LATEST,
+            Child::builder("a:1", "Song").album_id("a:2"),
+        );
+        assert_eq!(expected, response);
+
+        let response = transport::execute_async(&transport, &request("1"))
+            .await
+            .unwrap();
+        assert!(matches!(
+            response.body,
+            ResponseBody::Error(Error {
+                code: ErrorCode::DataNotFound,
+                ..
+            })
+        ));
+    }
+}
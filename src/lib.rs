@@ -38,9 +38,29 @@
 //! ```
 
 pub(crate) mod deser;
+pub(crate) mod hex;
 #[macro_use]
 pub(crate) mod query;
 
+pub mod binary;
+pub mod cache;
+pub mod capabilities;
 pub mod common;
+pub mod diff;
+pub mod federation;
+pub mod idmap;
+pub mod library;
+#[cfg(feature = "openapi")]
+pub mod openapi;
+pub mod pager;
+pub mod recording;
 pub mod request;
 pub mod response;
+pub mod service;
+pub mod transport;
+pub mod visit;
+
+#[cfg(test)]
+mod test_support;
+#[cfg(test)]
+mod xsd;
@@ -0,0 +1,511 @@
+//! The album lists of `getAlbumList` and `getAlbumList2`.
+//!
+//! [`AlbumLists`] holds a collection of [`AlbumID3`]s together with the [`AlbumAnnotation`]s that
+//! are not part of the response type, and answers both requests for every [`ListType`]:
+//!
+//! | List type              | Albums                      | Order                           |
+//! |------------------------|-----------------------------|---------------------------------|
+//! | `random`               | all                         | random                          |
+//! | `newest`               | all                         | `created`, newest first         |
+//! | `highest`              | with an `average_rating`    | rating, highest first           |
+//! | `frequent`             | with a `play_count` above 0 | play count, highest first       |
+//! | `recent`               | with a `last_played`        | `last_played`, most recent first|
+//! | `alphabeticalByName`   | all                         | name                            |
+//! | `alphabeticalByArtist` | all                         | artist, then name               |
+//! | `starred`              | with a `starred`            | `starred`, most recent first    |
+//! | `byYear`               | with a year in the range    | year, descending if `fromYear > toYear` |
+//! | `byGenre`              | with the genre              | name                            |
+//!
+//! Names are compared case-insensitively, albums without the date a list is ordered by come
+//! last. Ties are broken by name and then ID so every list except `random` is deterministic.
+//!
+//! # Example
+//! ```rust
+//! use subsonic_types::{
+//!     library::{
+//!         album_list::{AlbumAnnotation, AlbumLists},
+//!         Rng,
+//!     },
+//!     request::lists::{GetAlbumList2, ListType},
+//!     response::AlbumID3,
+//! };
+//!
+//! let mut lists = AlbumLists::new();
+//! for (id, name, year) in [("1", "Kid A", 2000), ("2", "OK Computer", 1997), ("3", "Amnesiac", 2001)] {
+//!     let album = AlbumID3::builder(id, name, 10, 2400).year(year).build();
+//!     lists.insert(album, AlbumAnnotation::default());
+//! }
+//!
+//! let request = GetAlbumList2::builder(ListType::ByYear).from_year(2001).to_year(1990).build();
+//! let list = lists.album_list2(&request, &mut Rng::new(0)).unwrap();
+//! let names = list.album.iter().map(|album| album.name.as_str()).collect::<Vec<_>>();
+//! assert_eq!(vec!["Amnesiac", "Kid A", "OK Computer"], names);
+//! ```
+
+use std::cmp::{Ordering, Reverse};
+
+use time::OffsetDateTime;
+
+use crate::{
+    common::{AverageRating, DateTime, MusicFolderId},
+    request::lists::{GetAlbumList, GetAlbumList2, ListType},
+    response::{AlbumID3, AlbumList, AlbumList2, Error, ErrorCode},
+};
+
+use super::Rng;
+
+/// The data about an album that is not part of [`AlbumID3`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AlbumAnnotation {
+    /// The music folder of the album, used to filter by `musicFolderId`.
+    pub music_folder_id: Option<MusicFolderId>,
+    /// Used by [`ListType::Highest`].
+    pub average_rating: Option<AverageRating>,
+    /// Used by [`ListType::Recent`].
+    pub last_played: Option<DateTime>,
+}
+
+/// A collection of albums answering `getAlbumList` and `getAlbumList2`.
+/// See the [module documentation](self) for the semantics of each list type.
+#[derive(Debug, Default, Clone)]
+pub struct AlbumLists {
+    albums: Vec<(AlbumID3, AlbumAnnotation)>,
+}
+
+/// The parameters shared by [`GetAlbumList`] and [`GetAlbumList2`].
+struct Query<'a> {
+    list_type: &'a ListType,
+    size: Option<u32>,
+    offset: Option<u32>,
+    from_year: Option<u32>,
+    to_year: Option<u32>,
+    genre: Option<&'a str>,
+    music_folder_id: Option<MusicFolderId>,
+}
+
+impl<'a> From<&'a GetAlbumList> for Query<'a> {
+    fn from(request: &'a GetAlbumList) -> Self {
+        Self {
+            list_type: &request.list_type,
+            size: request.size,
+            offset: request.offset,
+            from_year: request.from_year,
+            to_year: request.to_year,
+            genre: request.genre.as_deref(),
+            music_folder_id: request.music_folder_id,
+        }
+    }
+}
+
+impl<'a> From<&'a GetAlbumList2> for Query<'a> {
+    fn from(request: &'a GetAlbumList2) -> Self {
+        Self {
+            list_type: &request.list_type,
+            size: request.size,
+            offset: request.offset,
+            from_year: request.from_year,
+            to_year: request.to_year,
+            genre: request.genre.as_deref(),
+            music_folder_id: request.music_folder_id,
+        }
+    }
+}
+
+impl AlbumLists {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, album: AlbumID3, annotation: AlbumAnnotation) {
+        self.albums.push((album, annotation));
+    }
+
+    /// Returns the requested page of the list.
+    /// Fails with [`ErrorCode::RequiredParameterMissing`] when `byYear` has no year range or
+    /// `byGenre` has no genre.
+    pub fn album_list2(&self, request: &GetAlbumList2, rng: &mut Rng) -> Result<AlbumList2, Error> {
+        let album = self.list(Query::from(request), rng)?;
+        Ok(AlbumList2 {
+            album: album.into_iter().cloned().collect(),
+        })
+    }
+
+    /// Same as [`AlbumLists::album_list2`] with the albums as directories.
+    pub fn album_list(&self, request: &GetAlbumList, rng: &mut Rng) -> Result<AlbumList, Error> {
+        let album = self.list(Query::from(request), rng)?;
+        Ok(AlbumList {
+            album: album.into_iter().cloned().map(Into::into).collect(),
+        })
+    }
+
+    fn list(&self, query: Query<'_>, rng: &mut Rng) -> Result<Vec<&AlbumID3>, Error> {
+        let mut albums = self
+            .albums
+            .iter()
+            .filter(|(_, annotation)| {
+                query
+                    .music_folder_id
+                    .is_none_or(|folder| annotation.music_folder_id == Some(folder))
+            })
+            .collect::<Vec<_>>();
+
+        match query.list_type {
+            ListType::Highest => {
+                albums.retain(|(_, annotation)| annotation.average_rating.is_some())
+            }
+            ListType::Frequent => albums.retain(|(album, _)| album.play_count.unwrap_or(0) > 0),
+            ListType::Recent => albums.retain(|(_, annotation)| annotation.last_played.is_some()),
+            ListType::Starred => albums.retain(|(album, _)| album.starred.is_some()),
+            ListType::ByYear => {
+                let (Some(from), Some(to)) = (query.from_year, query.to_year) else {
+                    return Err(missing("fromYear and toYear"));
+                };
+                let (low, high) = (from.min(to), from.max(to));
+                albums.retain(|(album, _)| album.year.is_some_and(|y| low <= y && y <= high));
+            }
+            ListType::ByGenre => {
+                let Some(genre) = query.genre else {
+                    return Err(missing("genre"));
+                };
+                albums.retain(|(album, _)| album.genre.as_deref() == Some(genre));
+            }
+            _ => {}
+        }
+
+        match query.list_type {
+            ListType::Random => {
+                for i in 0..albums.len() {
+                    let j = i + rng.below(albums.len() - i);
+                    albums.swap(i, j);
+                }
+            }
+            ListType::Highest => {
+                albums.sort_by(|(a, x), (b, y)| {
+                    let rating = |annotation: &AlbumAnnotation| {
+                        annotation.average_rating.map_or(0.0, AverageRating::value)
+                    };
+                    rating(y).total_cmp(&rating(x)).then_with(|| by_name(a, b))
+                });
+            }
+            ListType::Recent => {
+                albums.sort_by(|(a, x), (b, y)| {
+                    Reverse(timestamp(&x.last_played))
+                        .cmp(&Reverse(timestamp(&y.last_played)))
+                        .then_with(|| by_name(a, b))
+                });
+            }
+            _ => albums.sort_by(|(a, _), (b, _)| order(&query, a, b).unwrap_or(Ordering::Equal)),
+        }
+
+        let offset = query.offset.unwrap_or(0) as usize;
+        let size = query.size.unwrap_or(10).min(500) as usize;
+        Ok(albums
+            .into_iter()
+            .skip(offset)
+            .take(size)
+            .map(|(album, _)| album)
+            .collect())
+    }
+}
+
+impl FromIterator<(AlbumID3, AlbumAnnotation)> for AlbumLists {
+    fn from_iter<T: IntoIterator<Item = (AlbumID3, AlbumAnnotation)>>(iter: T) -> Self {
+        Self {
+            albums: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// Compares two albums of a `getAlbumList2` list, `None` for the lists whose order does not
+/// only depend on [`AlbumID3`] fields: `random`, `highest` and `recent`.
+/// Used to merge the sorted lists of several servers.
+pub(crate) fn compare(request: &GetAlbumList2, a: &AlbumID3, b: &AlbumID3) -> Option<Ordering> {
+    order(&Query::from(request), a, b)
+}
+
+fn order(query: &Query<'_>, a: &AlbumID3, b: &AlbumID3) -> Option<Ordering> {
+    let order = match query.list_type {
+        ListType::Random | ListType::Highest | ListType::Recent => return None,
+        ListType::Newest => Reverse(timestamp(&a.created)).cmp(&Reverse(timestamp(&b.created))),
+        ListType::Frequent => Reverse(a.play_count).cmp(&Reverse(b.play_count)),
+        ListType::AlphabeticalByName | ListType::ByGenre => Ordering::Equal,
+        ListType::AlphabeticalByArtist => {
+            // Albums without an artist come last.
+            let artist = |album: &AlbumID3| {
+                let artist = album.artist.as_deref().map(str::to_lowercase);
+                (artist.is_none(), artist)
+            };
+            artist(a).cmp(&artist(b))
+        }
+        ListType::Starred => Reverse(timestamp(&a.starred)).cmp(&Reverse(timestamp(&b.starred))),
+        ListType::ByYear if query.from_year > query.to_year => b.year.cmp(&a.year),
+        ListType::ByYear => a.year.cmp(&b.year),
+    };
+    Some(order.then_with(|| by_name(a, b)))
+}
+
+fn by_name(a: &AlbumID3, b: &AlbumID3) -> Ordering {
+    a.name
+        .to_lowercase()
+        .cmp(&b.name.to_lowercase())
+        .then_with(|| a.id.cmp(&b.id))
+}
+
+/// The key of a date. Reversed, `None` sorts after every date.
+fn timestamp(datetime: &Option<DateTime>) -> Option<i128> {
+    datetime
+        .clone()
+        .map(|datetime| OffsetDateTime::from(datetime).unix_timestamp_nanos())
+}
+
+fn missing(parameters: &str) -> Error {
+    Error::with_message(
+        ErrorCode::RequiredParameterMissing,
+        format!("missing {parameters}"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::request::lists::{GetAlbumList, GetAlbumList2};
+
+    use super::*;
+
+    const CASES: u64 = 200;
+
+    const ORDERED: [ListType; 9] = [
+        ListType::Newest,
+        ListType::Highest,
+        ListType::Frequent,
+        ListType::Recent,
+        ListType::AlphabeticalByName,
+        ListType::AlphabeticalByArtist,
+        ListType::Starred,
+        ListType::ByYear,
+        ListType::ByGenre,
+    ];
+
+    fn date(rng: &mut Rng) -> Option<DateTime> {
+        (rng.below(4) > 0).then(|| {
+            let seconds = rng.below(5) as i64 * 86400;
+            DateTime::from(OffsetDateTime::from_unix_timestamp(seconds).unwrap())
+        })
+    }
+
+    /// Random albums with few distinct values so that ties are common.
+    fn arbitrary_albums(rng: &mut Rng) -> AlbumLists {
+        (0..rng.below(30))
+            .map(|i| {
+                let album = AlbumID3 {
+                    id: format!("al-{i}").into(),
+                    name: ["alpha", "Beta", "gamma", "Delta", "beta"][rng.below(5)].into(),
+                    artist: (rng.below(4) > 0).then(|| ["Zed", "ann", "Bob"][rng.below(3)].into()),
+                    play_count: Some(rng.below(4) as u64),
+                    created: date(rng),
+                    starred: date(rng),
+                    year: (rng.below(4) > 0).then(|| 1990 + rng.below(10) as u32),
+                    genre: (rng.below(4) > 0).then(|| ["Rock", "Jazz"][rng.below(2)].into()),
+                    ..Default::default()
+                };
+                let annotation = AlbumAnnotation {
+                    music_folder_id: Some(MusicFolderId::new(rng.below(2) as u32)),
+                    average_rating: (rng.below(4) > 0)
+                        .then(|| AverageRating::new(1.0 + rng.below(5) as f32).unwrap()),
+                    last_played: date(rng),
+                };
+                (album, annotation)
+            })
+            .collect()
+    }
+
+    fn arbitrary_request(rng: &mut Rng, list_type: ListType) -> GetAlbumList2 {
+        GetAlbumList2::builder(list_type)
+            .from_year(1990 + rng.below(10) as u32)
+            .to_year(1990 + rng.below(10) as u32)
+            .genre(["Rock", "Jazz", "Pop"][rng.below(3)])
+            .build()
+    }
+
+    fn full(mut request: GetAlbumList2) -> GetAlbumList2 {
+        request.offset = Some(0);
+        request.size = Some(500);
+        request
+    }
+
+    fn annotation<'a>(lists: &'a AlbumLists, album: &AlbumID3) -> &'a AlbumAnnotation {
+        &lists
+            .albums
+            .iter()
+            .find(|(a, _)| a.id == album.id)
+            .unwrap()
+            .1
+    }
+
+    fn included(lists: &AlbumLists, request: &GetAlbumList2, album: &AlbumID3) -> bool {
+        let annotation = annotation(lists, album);
+        let (from, to) = (request.from_year.unwrap(), request.to_year.unwrap());
+        let in_folder = request
+            .music_folder_id
+            .is_none_or(|folder| annotation.music_folder_id == Some(folder));
+        in_folder
+            && match request.list_type {
+                ListType::Highest => annotation.average_rating.is_some(),
+                ListType::Frequent => album.play_count > Some(0),
+                ListType::Recent => annotation.last_played.is_some(),
+                ListType::Starred => album.starred.is_some(),
+                ListType::ByYear => album
+                    .year
+                    .is_some_and(|year| from.min(to) <= year && year <= from.max(to)),
+                ListType::ByGenre => album.genre == request.genre,
+                _ => true,
+            }
+    }
+
+    /// How `a` has to be ordered relative to `b`, before the tie break by name.
+    fn expected_order(
+        lists: &AlbumLists,
+        request: &GetAlbumList2,
+        a: &AlbumID3,
+        b: &AlbumID3,
+    ) -> Ordering {
+        // Newest first, without a date last.
+        let newest_first = |a: &Option<DateTime>, b: &Option<DateTime>| {
+            let seconds = |d: &Option<DateTime>| {
+                d.clone()
+                    .map_or(i64::MIN, |d| OffsetDateTime::from(d).unix_timestamp())
+            };
+            seconds(b).cmp(&seconds(a))
+        };
+        let (x, y) = (annotation(lists, a), annotation(lists, b));
+        match request.list_type {
+            ListType::Newest => newest_first(&a.created, &b.created),
+            ListType::Highest => {
+                let rating =
+                    |annotation: &AlbumAnnotation| annotation.average_rating.unwrap().value();
+                rating(y).partial_cmp(&rating(x)).unwrap()
+            }
+            ListType::Frequent => b.play_count.cmp(&a.play_count),
+            ListType::Recent => newest_first(&x.last_played, &y.last_played),
+            ListType::AlphabeticalByArtist => {
+                let artist = |album: &AlbumID3| album.artist.as_ref().map(|a| a.to_lowercase());
+                match (artist(a), artist(b)) {
+                    (Some(a), Some(b)) => a.cmp(&b),
+                    (a, b) => a.is_none().cmp(&b.is_none()),
+                }
+            }
+            ListType::Starred => newest_first(&a.starred, &b.starred),
+            ListType::ByYear if request.from_year > request.to_year => b.year.cmp(&a.year),
+            ListType::ByYear => a.year.cmp(&b.year),
+            _ => Ordering::Equal,
+        }
+    }
+
+    #[test]
+    fn ordered_lists_are_filtered_sorted_and_complete() {
+        for seed in 0..CASES {
+            let rng = &mut Rng::new(seed);
+            let lists = arbitrary_albums(rng);
+            for list_type in ORDERED {
+                let mut request = full(arbitrary_request(rng, list_type));
+                if rng.below(2) == 0 {
+                    request.music_folder_id = Some(MusicFolderId::new(rng.below(2) as u32));
+                }
+                let list = lists.album_list2(&request, rng).unwrap().album;
+
+                assert!(list.iter().all(|album| included(&lists, &request, album)));
+                let expected = lists
+                    .albums
+                    .iter()
+                    .filter(|(album, _)| included(&lists, &request, album))
+                    .count();
+                assert_eq!(expected, list.len(), "{request:?}");
+
+                for pair in list.windows(2) {
+                    let (a, b) = (&pair[0], &pair[1]);
+                    let order = expected_order(&lists, &request, a, b)
+                        .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+                    assert_ne!(Ordering::Greater, order, "{request:?}: {a:?} before {b:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pages_are_slices_of_the_full_list() {
+        for seed in 0..CASES {
+            let rng = &mut Rng::new(seed);
+            let lists = arbitrary_albums(rng);
+            let list_type = ORDERED[rng.below(ORDERED.len())].clone();
+            let request = arbitrary_request(rng, list_type);
+            let all = lists
+                .album_list2(&full(request.clone()), rng)
+                .unwrap()
+                .album;
+
+            let (offset, size) = (rng.below(35), rng.below(12));
+            let mut paged = request.clone();
+            paged.offset = Some(offset as u32);
+            paged.size = Some(size as u32);
+            let page = lists.album_list2(&paged, rng).unwrap().album;
+            let end = all.len().min(offset + size);
+            assert_eq!(all.get(offset..end).unwrap_or_default(), page.as_slice());
+
+            let default = lists.album_list2(&request, rng).unwrap().album;
+            assert_eq!(&all[..all.len().min(10)], default.as_slice());
+        }
+    }
+
+    #[test]
+    fn random_lists_are_reproducible_samples() {
+        for seed in 0..CASES {
+            let rng = &mut Rng::new(seed);
+            let lists = arbitrary_albums(rng);
+            let size = rng.below(40) as u32;
+            let request = GetAlbumList2::builder(ListType::Random).size(size).build();
+
+            let list = lists.album_list2(&request, &mut Rng::new(seed)).unwrap();
+            let again = lists.album_list2(&request, &mut Rng::new(seed)).unwrap();
+            assert_eq!(list, again);
+            assert_eq!(lists.albums.len().min(size as usize), list.album.len());
+
+            let mut ids = list.album.iter().map(|album| &album.id).collect::<Vec<_>>();
+            ids.sort();
+            ids.dedup();
+            assert_eq!(list.album.len(), ids.len());
+        }
+    }
+
+    #[test]
+    fn folder_lists_match_id3_lists() {
+        for seed in 0..CASES {
+            let rng = &mut Rng::new(seed);
+            let lists = arbitrary_albums(rng);
+            let list_type = ORDERED[rng.below(ORDERED.len())].clone();
+            let request = full(arbitrary_request(rng, list_type.clone()));
+            let folder_request = GetAlbumList::builder(list_type)
+                .size(500)
+                .from_year(request.from_year.unwrap())
+                .to_year(request.to_year.unwrap())
+                .genre(request.genre.clone().unwrap())
+                .build();
+
+            let id3 = lists.album_list2(&request, rng).unwrap();
+            let folders = lists.album_list(&folder_request, rng).unwrap();
+            assert_eq!(AlbumList::from(id3), folders);
+        }
+    }
+
+    #[test]
+    fn required_parameters() {
+        let lists = AlbumLists::new();
+        let rng = &mut Rng::new(0);
+        let by_year = GetAlbumList2::builder(ListType::ByYear)
+            .from_year(2000)
+            .build();
+        let err = lists.album_list2(&by_year, rng).unwrap_err();
+        assert_eq!(ErrorCode::RequiredParameterMissing, err.code);
+        let by_genre = GetAlbumList2::builder(ListType::ByGenre).build();
+        let err = lists.album_list2(&by_genre, rng).unwrap_err();
+        assert_eq!(ErrorCode::RequiredParameterMissing, err.code);
+    }
+}
@@ -0,0 +1,426 @@
+//! Grouping of artists into the alphabetical indexes of `getIndexes` and `getArtists`.
+//!
+//! [`IndexBuilder`] removes the ignored articles from the start of each artist name, "The Beatles"
+//! is listed under "B", and groups the artists by the first letter of what is left. Names that do
+//! not start with a letter are listed under `#`, which comes after every letter.
+//!
+//! How letters are found and names are ordered is decided by a [`Collation`]. The default
+//! [`FoldedCollation`] ignores case and diacritics, "Émilie" is listed under "E". Languages that
+//! treat some accented letters as letters of their own can use an [`AlphabetCollation`].
+//!
+//! # Example
+//! ```rust
+//! use subsonic_types::{
+//!     library::index::{AlphabetCollation, Collation, IndexBuilder},
+//!     response::ArtistID3,
+//! };
+//!
+//! let artist = |id: &str, name: &str| ArtistID3 {
+//!     id: id.into(),
+//!     name: name.into(),
+//!     ..Default::default()
+//! };
+//! let builder = IndexBuilder::new()
+//!     .artist(artist("1", "The Beatles"))
+//!     .artist(artist("2", "Ärzte"))
+//!     .artist(artist("3", "2Pac"));
+//!
+//! fn names<C: Collation>(builder: &IndexBuilder<C>) -> Vec<String> {
+//!     builder.index_id3().into_iter().map(|index| index.name).collect()
+//! }
+//! assert_eq!(vec!["A", "B", "#"], names(&builder));
+//!
+//! let swedish = builder.collation(AlphabetCollation::new("ABCDEFGHIJKLMNOPQRSTUVWXYZÅÄÖ"));
+//! assert_eq!(vec!["B", "Ä", "#"], names(&swedish));
+//! ```
+
+use std::cmp::Ordering;
+
+use crate::{
+    common::Milliseconds,
+    response::{Artist, ArtistID3, ArtistsID3, Child, Index, IndexID3, Indexes},
+};
+
+use super::text::fold;
+
+/// The articles ignored by default, the same as the reference Subsonic server.
+pub const DEFAULT_IGNORED_ARTICLES: &str = "The El La Los Las Le Les";
+
+/// How artist names are grouped and ordered by an [`IndexBuilder`].
+pub trait Collation {
+    /// The name of the index `name` is listed under, `None` for `#`.
+    /// `name` has its ignored article removed.
+    fn index(&self, name: &str) -> Option<String>;
+
+    /// Orders two names, or two index names.
+    fn compare(&self, a: &str, b: &str) -> Ordering;
+}
+
+/// A [`Collation`] that ignores case and diacritics.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FoldedCollation;
+
+impl Collation for FoldedCollation {
+    fn index(&self, name: &str) -> Option<String> {
+        let first = fold(name).chars().next()?;
+        first
+            .is_alphabetic()
+            .then(|| first.to_uppercase().collect())
+    }
+
+    fn compare(&self, a: &str, b: &str) -> Ordering {
+        fold(a).cmp(&fold(b))
+    }
+}
+
+/// A [`Collation`] with its own alphabet.
+/// The letters of the alphabet are indexes of their own and sorted in the given order, other
+/// letters are folded to a letter of the alphabet when possible. Case is always ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlphabetCollation {
+    letters: Vec<char>,
+}
+
+impl AlphabetCollation {
+    /// `alphabet` lists the letters in order, e.g. `"ABCDEFGHIJKLMNOPQRSTUVWXYZÅÄÖ"` for Swedish.
+    pub fn new(alphabet: &str) -> Self {
+        Self {
+            letters: alphabet
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .flat_map(char::to_uppercase)
+                .collect(),
+        }
+    }
+
+    fn rank(&self, c: char) -> Option<usize> {
+        let upper = c.to_uppercase().next()?;
+        self.letters.iter().position(|&letter| letter == upper)
+    }
+
+    /// Letters of the alphabet sort after other characters, by rank, followed by the letters that
+    /// are not part of it.
+    fn key(&self, name: &str) -> Vec<u32> {
+        const ALPHABET: u32 = 0x110000;
+        const OTHER_LETTERS: u32 = 2 * ALPHABET;
+
+        let mut key = Vec::with_capacity(name.len());
+        for c in name.chars() {
+            if let Some(rank) = self.rank(c) {
+                key.push(ALPHABET + rank as u32);
+                continue;
+            }
+            for c in fold(&c.to_string()).chars() {
+                key.push(match self.rank(c) {
+                    Some(rank) => ALPHABET + rank as u32,
+                    None if c.is_alphabetic() => OTHER_LETTERS + c as u32,
+                    None => c as u32,
+                });
+            }
+        }
+        key
+    }
+}
+
+impl Collation for AlphabetCollation {
+    fn index(&self, name: &str) -> Option<String> {
+        let first = name.chars().next()?;
+        let rank = self
+            .rank(first)
+            .or_else(|| self.rank(fold(&first.to_string()).chars().next()?));
+        match rank {
+            Some(rank) => Some(self.letters[rank].to_string()),
+            None => FoldedCollation.index(name),
+        }
+    }
+
+    fn compare(&self, a: &str, b: &str) -> Ordering {
+        self.key(a).cmp(&self.key(b))
+    }
+}
+
+/// Builds sorted [`Index`] and [`IndexID3`] lists from artists.
+/// See the [module documentation](self) for an example.
+#[derive(Debug, Clone)]
+pub struct IndexBuilder<C = FoldedCollation> {
+    ignored_articles: String,
+    collation: C,
+    artists: Vec<ArtistID3>,
+    shortcuts: Vec<Artist>,
+    children: Vec<Child>,
+}
+
+impl Default for IndexBuilder {
+    fn default() -> Self {
+        Self {
+            ignored_articles: DEFAULT_IGNORED_ARTICLES.to_string(),
+            collation: FoldedCollation,
+            artists: Vec::new(),
+            shortcuts: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+}
+
+impl IndexBuilder {
+    /// A builder ignoring the [`DEFAULT_IGNORED_ARTICLES`] with a [`FoldedCollation`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<C: Collation> IndexBuilder<C> {
+    /// The articles to ignore, separated by spaces.
+    pub fn ignored_articles(mut self, articles: impl Into<String>) -> Self {
+        self.ignored_articles = articles.into();
+        self
+    }
+
+    pub fn collation<D: Collation>(self, collation: D) -> IndexBuilder<D> {
+        IndexBuilder {
+            ignored_articles: self.ignored_articles,
+            collation,
+            artists: self.artists,
+            shortcuts: self.shortcuts,
+            children: self.children,
+        }
+    }
+
+    pub fn artist(mut self, artist: ArtistID3) -> Self {
+        self.artists.push(artist);
+        self
+    }
+
+    pub fn artists(mut self, artists: impl IntoIterator<Item = ArtistID3>) -> Self {
+        self.artists.extend(artists);
+        self
+    }
+
+    /// Adds a shortcut of [`Indexes`]. Shortcuts keep the order they are added in.
+    pub fn shortcut(mut self, shortcut: Artist) -> Self {
+        self.shortcuts.push(shortcut);
+        self
+    }
+
+    /// Adds a file at the top level of [`Indexes`]. Children keep the order they are added in.
+    pub fn child(mut self, child: Child) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// The name of `name` without its ignored article.
+    /// Names that are only an article, like "The", are kept.
+    pub fn strip_article<'a>(&self, name: &'a str) -> &'a str {
+        let name = name.trim_start();
+        for article in self.ignored_articles.split_whitespace() {
+            let Some(prefix) = name.get(..article.len()) else {
+                continue;
+            };
+            let rest = &name[article.len()..];
+            if prefix.to_lowercase() == article.to_lowercase()
+                && rest.starts_with(char::is_whitespace)
+                && !rest.trim().is_empty()
+            {
+                return rest.trim_start();
+            }
+        }
+        name
+    }
+
+    pub fn index_id3(&self) -> Vec<IndexID3> {
+        let mut artists = self
+            .artists
+            .iter()
+            .map(|artist| {
+                let name = self.strip_article(&artist.name);
+                (self.collation.index(name), name, artist)
+            })
+            .collect::<Vec<_>>();
+        artists.sort_by(|(index_a, name_a, a), (index_b, name_b, b)| {
+            self.compare_index(index_a, index_b)
+                .then_with(|| self.collation.compare(name_a, name_b))
+                .then_with(|| a.name.cmp(&b.name))
+                .then_with(|| a.id.cmp(&b.id))
+        });
+
+        let mut indexes: Vec<IndexID3> = Vec::new();
+        for (index, _, artist) in artists {
+            let name = index.unwrap_or_else(|| "#".to_string());
+            match indexes.last_mut() {
+                Some(last) if last.name == name => last.artist.push(artist.clone()),
+                _ => indexes.push(IndexID3 {
+                    name,
+                    artist: vec![artist.clone()],
+                }),
+            }
+        }
+        indexes
+    }
+
+    pub fn index(&self) -> Vec<Index> {
+        self.index_id3()
+            .into_iter()
+            .map(|index| Index {
+                name: index.name,
+                artist: index.artist.into_iter().map(Into::into).collect(),
+            })
+            .collect()
+    }
+
+    pub fn artists_id3(&self) -> ArtistsID3 {
+        ArtistsID3 {
+            index: self.index_id3(),
+            ignored_articles: self.ignored_articles.clone(),
+        }
+    }
+
+    pub fn indexes(&self, last_modified: Milliseconds) -> Indexes {
+        Indexes {
+            last_modified,
+            ignored_articles: self.ignored_articles.clone(),
+            shortcut: self.shortcuts.clone(),
+            index: self.index(),
+            child: self.children.clone(),
+        }
+    }
+
+    /// `#` comes after every letter.
+    fn compare_index(&self, a: &Option<String>, b: &Option<String>) -> Ordering {
+        match (a, b) {
+            (Some(a), Some(b)) => self.collation.compare(a, b),
+            (a, b) => a.is_none().cmp(&b.is_none()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{common::DirectoryId, test_support::artist};
+
+    use super::*;
+
+    fn grouped<C: Collation>(builder: &IndexBuilder<C>) -> Vec<(String, Vec<String>)> {
+        builder
+            .index_id3()
+            .into_iter()
+            .map(|index| {
+                let names = index.artist.into_iter().map(|artist| artist.name).collect();
+                (index.name, names)
+            })
+            .collect()
+    }
+
+    fn group(name: &str, artists: &[&str]) -> (String, Vec<String>) {
+        (
+            name.to_string(),
+            artists.iter().map(|artist| artist.to_string()).collect(),
+        )
+    }
+
+    #[test]
+    fn ignored_articles() {
+        let builder = IndexBuilder::new().artists([
+            artist("1", "The Beatles"),
+            artist("2", "Los Lobos"),
+            artist("3", "The"),
+            artist("4", "Theatre of Tragedy"),
+            artist("5", "  the   Band"),
+            artist("6", "Lesley Gore"),
+        ]);
+        assert_eq!(
+            vec![
+                group("B", &["  the   Band", "The Beatles"]),
+                group("L", &["Lesley Gore", "Los Lobos"]),
+                group("T", &["The", "Theatre of Tragedy"]),
+            ],
+            grouped(&builder)
+        );
+        assert_eq!("Band", builder.strip_article("  the   Band"));
+
+        let builder = builder.ignored_articles("A An");
+        assert_eq!("T", builder.index_id3().last().unwrap().name);
+        assert_eq!("A An", builder.artists_id3().ignored_articles);
+    }
+
+    #[test]
+    fn accents_are_folded_and_non_letters_come_last() {
+        let builder = IndexBuilder::new().artists([
+            artist("1", "Émilie Simon"),
+            artist("2", "2Pac"),
+            artist("3", "!!!"),
+            artist("4", "eels"),
+            artist("5", "Édith Piaf"),
+            artist("6", "Ólafur Arnalds"),
+        ]);
+        assert_eq!(
+            vec![
+                group("E", &["Édith Piaf", "eels", "Émilie Simon"]),
+                group("O", &["Ólafur Arnalds"]),
+                group("#", &["!!!", "2Pac"]),
+            ],
+            grouped(&builder)
+        );
+    }
+
+    #[test]
+    fn alphabet_collation() {
+        let builder = IndexBuilder::new()
+            .artists([
+                artist("1", "Öst"),
+                artist("2", "Zara Larsson"),
+                artist("3", "Ängel"),
+                artist("4", "Åsa Jinder"),
+                artist("5", "Émile"),
+                artist("6", "Aa"),
+                artist("7", "Ab"),
+            ])
+            .collation(AlphabetCollation::new("ABCDEFGHIJKLMNOPQRSTUVWXYZÅÄÖ"));
+        assert_eq!(
+            vec![
+                group("A", &["Aa", "Ab"]),
+                group("E", &["Émile"]),
+                group("Z", &["Zara Larsson"]),
+                group("Å", &["Åsa Jinder"]),
+                group("Ä", &["Ängel"]),
+                group("Ö", &["Öst"]),
+            ],
+            grouped(&builder)
+        );
+        let swedish = AlphabetCollation::new("abcdefghijklmnopqrstuvwxyzåäö");
+        assert_eq!(Ordering::Greater, swedish.compare("ä", "z"));
+        assert_eq!(Ordering::Less, swedish.compare("e", "ë f"));
+        assert_eq!(None, swedish.index("1990s"));
+    }
+
+    #[test]
+    fn indexes() {
+        let shortcut = Artist {
+            id: DirectoryId::new("podcasts"),
+            name: "Podcasts".into(),
+            ..Default::default()
+        };
+        let child = Child::builder("so-1", "Loose File").build();
+        let indexes = IndexBuilder::new()
+            .artist(artist("ar-1", "The Who"))
+            .shortcut(shortcut.clone())
+            .child(child.clone())
+            .indexes(Milliseconds::new(42));
+        assert_eq!(
+            Indexes {
+                last_modified: Milliseconds::new(42),
+                ignored_articles: DEFAULT_IGNORED_ARTICLES.to_string(),
+                shortcut: vec![shortcut],
+                index: vec![Index {
+                    name: "W".into(),
+                    artist: vec![Artist {
+                        id: DirectoryId::new("ar-1"),
+                        name: "The Who".into(),
+                        ..Default::default()
+                    }],
+                }],
+                child: vec![child],
+            },
+            indexes
+        );
+    }
+}
@@ -0,0 +1,1305 @@
+//! An in-memory music library that answers the browsing and list endpoints.
+//!
+//! [`Library`] holds music folders, artists, albums, songs and videos and builds the
+//! [`response`](crate::response) bodies of `getMusicFolders`, `getIndexes`, `getMusicDirectory`,
+//! `getArtists`, `getArtist`, `getAlbum`, `getSong`, `getGenres`, `getAlbumList`,
+//! `getAlbumList2`, `getSongsByGenre`, `getRandomSongs`, `getVideos`, `search`, `search2` and
+//! `search3`. [`LibraryService`] exposes it as a [`SubsonicService`],
+//! which is enough for tests and small deployments.
+//!
+//! Albums belong to a music folder and an artist, songs belong to an album. Genres are not stored
+//! separately, they are collected from the albums and songs. Entries that reference a missing
+//! artist or album are still returned, only without the fields of the missing entry.
+//! The search index and the album lists are built by the first query that needs them and kept
+//! until the next insertion.
+//!
+//! Artists are grouped into indexes by an [`IndexBuilder`], see [`index`] for the rules.
+//!
+//! The directory based endpoints use the artist and album IDs as directory IDs, so artist and
+//! album IDs should not overlap.
+//!
+//! # Example
+//! ```rust
+//! use subsonic_types::{
+//!     common::MusicFolderId,
+//!     library::{Album, Artist, Library, MusicFolder, Song},
+//!     request::browsing::GetArtist,
+//! };
+//!
+//! let folder = MusicFolderId::new(1);
+//! let mut library = Library::new();
+//! library.insert_folder(MusicFolder::new(folder, "Music"));
+//! library.insert_artist(Artist::new("ar-1", "Radiohead"));
+//! library.insert_album(Album::new("al-1", "OK Computer", "ar-1", folder));
+//! library.insert_song(Song::new("so-1", "Airbag", "al-1"));
+//!
+//! let artist = library.get_artist(&GetArtist { id: "ar-1".into() }).unwrap();
+//! assert_eq!(1, artist.artist.album_count);
+//! assert_eq!("OK Computer", artist.album[0].name);
+//! assert_eq!(1, artist.album[0].song_count);
+//! ```
+
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::{Mutex, OnceLock},
+    time::SystemTime,
+};
+
+use crate::{
+    common::{
+        AlbumId, ArtistId, AverageRating, CoverArtId, DateTime, DirectoryId, MediaType,
+        Milliseconds, MusicFolderId, Seconds, SongId,
+    },
+    request::{
+        browsing, lists,
+        search::{Search, Search2, Search3},
+        Request,
+    },
+    response::{
+        self, AlbumID3, AlbumList, AlbumList2, AlbumWithSongsID3, ArtistID3, ArtistWithAlbumsID3,
+        ArtistsID3, Child, Directory, Error, ErrorCode, Genre, Genres, Indexes, MusicFolders,
+        Response, SearchResult, SearchResult2, SearchResult3, Songs, Videos,
+    },
+    service::{self, SubsonicService},
+};
+
+use self::{
+    album_list::{AlbumAnnotation, AlbumLists},
+    index::IndexBuilder,
+    search::SearchIndex,
+};
+
+pub mod album_list;
+pub mod index;
+pub mod search;
+mod text;
+
+/// A music folder of a [`Library`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MusicFolder {
+    pub id: MusicFolderId,
+    pub name: String,
+}
+
+impl MusicFolder {
+    pub fn new(id: MusicFolderId, name: impl Into<String>) -> Self {
+        Self {
+            id,
+            name: name.into(),
+        }
+    }
+}
+
+/// An artist of a [`Library`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Artist {
+    pub id: ArtistId,
+    pub name: String,
+    pub cover_art: Option<CoverArtId>,
+    pub artist_image_url: Option<String>,
+    pub starred: Option<DateTime>,
+}
+
+impl Artist {
+    pub fn new(id: impl Into<ArtistId>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// An album of a [`Library`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Album {
+    pub id: AlbumId,
+    pub name: String,
+    pub artist_id: ArtistId,
+    pub music_folder_id: MusicFolderId,
+    pub cover_art: Option<CoverArtId>,
+    pub year: Option<u32>,
+    pub genre: Option<String>,
+    pub created: Option<DateTime>,
+    pub starred: Option<DateTime>,
+    pub play_count: Option<u64>,
+    pub average_rating: Option<AverageRating>,
+    pub last_played: Option<DateTime>,
+}
+
+impl Album {
+    pub fn new(
+        id: impl Into<AlbumId>,
+        name: impl Into<String>,
+        artist_id: impl Into<ArtistId>,
+        music_folder_id: MusicFolderId,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            artist_id: artist_id.into(),
+            music_folder_id,
+            ..Default::default()
+        }
+    }
+}
+
+/// A song of a [`Library`].
+/// The song is in the music folder of its album and uses the artist, cover art, year and genre of
+/// the album when its own are `None`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Song {
+    pub id: SongId,
+    pub title: String,
+    pub album_id: AlbumId,
+    pub artist_id: Option<ArtistId>,
+    pub track: Option<u32>,
+    pub disc_number: Option<u32>,
+    pub year: Option<u32>,
+    pub genre: Option<String>,
+    pub cover_art: Option<CoverArtId>,
+    pub duration: Option<Seconds>,
+    pub bit_rate: Option<u32>,
+    pub size: Option<u64>,
+    pub content_type: Option<String>,
+    pub suffix: Option<String>,
+    pub path: Option<String>,
+    pub created: Option<DateTime>,
+    pub starred: Option<DateTime>,
+    pub play_count: Option<u64>,
+}
+
+impl Song {
+    pub fn new(
+        id: impl Into<SongId>,
+        title: impl Into<String>,
+        album_id: impl Into<AlbumId>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            title: title.into(),
+            album_id: album_id.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// A video of a [`Library`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Video {
+    pub id: SongId,
+    pub title: String,
+    pub music_folder_id: MusicFolderId,
+    pub cover_art: Option<CoverArtId>,
+    pub duration: Option<Seconds>,
+    pub bit_rate: Option<u32>,
+    pub size: Option<u64>,
+    pub content_type: Option<String>,
+    pub suffix: Option<String>,
+    pub path: Option<String>,
+    pub created: Option<DateTime>,
+    pub original_width: Option<u32>,
+    pub original_height: Option<u32>,
+}
+
+impl Video {
+    pub fn new(
+        id: impl Into<SongId>,
+        title: impl Into<String>,
+        music_folder_id: MusicFolderId,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            title: title.into(),
+            music_folder_id,
+            ..Default::default()
+        }
+    }
+}
+
+/// A seedable pseudo random number generator (SplitMix64).
+/// Used to pick the songs of [`Library::get_random_songs`] reproducibly.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// Creates a generator seeded by the operating system.
+    /// Fails if the operating system can't provide random bytes.
+    pub fn from_entropy() -> Result<Self, getrandom::Error> {
+        let mut bytes = [0u8; 8];
+        getrandom::getrandom(&mut bytes)?;
+        Ok(Self(u64::from_le_bytes(bytes)))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..bound`. `bound` must not be zero.
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+}
+
+/// An in-memory music library with ID indexes.
+/// See the [module documentation](self) for an example.
+#[derive(Debug, Default, Clone)]
+pub struct Library {
+    folders: Vec<MusicFolder>,
+    artists: Vec<Artist>,
+    albums: Vec<Album>,
+    songs: Vec<Song>,
+    videos: Vec<Video>,
+    folder_index: HashMap<MusicFolderId, usize>,
+    artist_index: HashMap<ArtistId, usize>,
+    album_index: HashMap<AlbumId, usize>,
+    song_index: HashMap<SongId, usize>,
+    video_index: HashMap<SongId, usize>,
+    artist_albums: HashMap<ArtistId, Vec<usize>>,
+    album_songs: HashMap<AlbumId, Vec<usize>>,
+    last_modified: Milliseconds,
+    /// Built on first use and cleared by every insertion.
+    search_index: OnceLock<SearchIndex>,
+    album_lists: OnceLock<AlbumLists>,
+}
+
+impl Library {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a music folder, replacing and returning the folder with the same ID.
+    pub fn insert_folder(&mut self, folder: MusicFolder) -> Option<MusicFolder> {
+        self.touch();
+        let id = folder.id;
+        upsert(&mut self.folders, &mut self.folder_index, id, folder).1
+    }
+
+    /// Adds an artist, replacing and returning the artist with the same ID.
+    pub fn insert_artist(&mut self, artist: Artist) -> Option<Artist> {
+        self.touch();
+        let id = artist.id.clone();
+        upsert(&mut self.artists, &mut self.artist_index, id, artist).1
+    }
+
+    /// Adds an album, replacing and returning the album with the same ID.
+    pub fn insert_album(&mut self, album: Album) -> Option<Album> {
+        self.touch();
+        let (id, artist_id) = (album.id.clone(), album.artist_id.clone());
+        let (position, old) = upsert(&mut self.albums, &mut self.album_index, id, album);
+        if let Some(old) = &old {
+            unlink(&mut self.artist_albums, &old.artist_id, position);
+        }
+        self.artist_albums
+            .entry(artist_id)
+            .or_default()
+            .push(position);
+        old
+    }
+
+    /// Adds a song, replacing and returning the song with the same ID.
+    pub fn insert_song(&mut self, song: Song) -> Option<Song> {
+        self.touch();
+        let (id, album_id) = (song.id.clone(), song.album_id.clone());
+        let (position, old) = upsert(&mut self.songs, &mut self.song_index, id, song);
+        if let Some(old) = &old {
+            unlink(&mut self.album_songs, &old.album_id, position);
+        }
+        self.album_songs.entry(album_id).or_default().push(position);
+        old
+    }
+
+    /// Adds a video, replacing and returning the video with the same ID.
+    pub fn insert_video(&mut self, video: Video) -> Option<Video> {
+        self.touch();
+        let id = video.id.clone();
+        upsert(&mut self.videos, &mut self.video_index, id, video).1
+    }
+
+    pub fn folder(&self, id: MusicFolderId) -> Option<&MusicFolder> {
+        self.folder_index.get(&id).map(|&i| &self.folders[i])
+    }
+
+    pub fn artist(&self, id: &ArtistId) -> Option<&Artist> {
+        self.artist_index.get(id).map(|&i| &self.artists[i])
+    }
+
+    pub fn album(&self, id: &AlbumId) -> Option<&Album> {
+        self.album_index.get(id).map(|&i| &self.albums[i])
+    }
+
+    pub fn song(&self, id: &SongId) -> Option<&Song> {
+        self.song_index.get(id).map(|&i| &self.songs[i])
+    }
+
+    pub fn video(&self, id: &SongId) -> Option<&Video> {
+        self.video_index.get(id).map(|&i| &self.videos[i])
+    }
+
+    /// The music folders in insertion order.
+    pub fn folders(&self) -> &[MusicFolder] {
+        &self.folders
+    }
+
+    /// The artists in insertion order.
+    pub fn artists(&self) -> &[Artist] {
+        &self.artists
+    }
+
+    /// The albums in insertion order.
+    pub fn albums(&self) -> &[Album] {
+        &self.albums
+    }
+
+    /// The songs in insertion order.
+    pub fn songs(&self) -> &[Song] {
+        &self.songs
+    }
+
+    /// The videos in insertion order.
+    pub fn videos(&self) -> &[Video] {
+        &self.videos
+    }
+
+    /// The albums of an artist, ordered by year and name.
+    pub fn albums_of(&self, artist_id: &ArtistId) -> Vec<&Album> {
+        let mut albums: Vec<&Album> = self
+            .artist_albums
+            .get(artist_id)
+            .map(|positions| positions.iter().map(|&i| &self.albums[i]).collect())
+            .unwrap_or_default();
+        albums.sort_by_key(|album| (album.year, album.name.to_lowercase()));
+        albums
+    }
+
+    /// The songs of an album, ordered by disc, track and title.
+    pub fn songs_of(&self, album_id: &AlbumId) -> Vec<&Song> {
+        let mut songs: Vec<&Song> = self
+            .album_songs
+            .get(album_id)
+            .map(|positions| positions.iter().map(|&i| &self.songs[i]).collect())
+            .unwrap_or_default();
+        songs.sort_by_key(|song| (song.disc_number, song.track, song.title.to_lowercase()));
+        songs
+    }
+
+    /// The time of the last change to the library, in milliseconds since the Unix epoch.
+    pub fn last_modified(&self) -> Milliseconds {
+        self.last_modified
+    }
+
+    pub fn get_music_folders(&self) -> MusicFolders {
+        MusicFolders {
+            music_folder: self
+                .folders
+                .iter()
+                .map(|folder| response::MusicFolder {
+                    id: folder.id,
+                    name: Some(folder.name.clone()),
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns empty indexes when the library did not change since `if_modified_since`.
+    pub fn get_indexes(&self, request: &browsing::GetIndexes) -> Indexes {
+        if request
+            .if_modified_since
+            .is_some_and(|since| since >= self.last_modified)
+        {
+            return IndexBuilder::new().indexes(self.last_modified);
+        }
+        self.index_builder(request.music_folder_id)
+            .indexes(self.last_modified)
+    }
+
+    /// Returns the directory of an artist or album, `None` if there is neither with that ID.
+    pub fn get_music_directory(&self, request: &browsing::GetMusicDirectory) -> Option<Directory> {
+        let artist_id = ArtistId::new(request.id.as_str());
+        if let Some(artist) = self.artist(&artist_id) {
+            return Some(Directory {
+                id: request.id.clone(),
+                name: artist.name.clone(),
+                starred: artist.starred.clone(),
+                child: self
+                    .albums_of(&artist.id)
+                    .into_iter()
+                    .map(|album| self.album_id3(album).into())
+                    .collect(),
+                ..Default::default()
+            });
+        }
+        let album = self.album(&AlbumId::new(request.id.as_str()))?;
+        Some(Directory {
+            id: request.id.clone(),
+            parent: Some(DirectoryId::new(album.artist_id.as_str())),
+            name: album.name.clone(),
+            starred: album.starred.clone(),
+            play_count: album.play_count,
+            child: self
+                .songs_of(&album.id)
+                .into_iter()
+                .map(|song| self.song_child(song))
+                .collect(),
+            ..Default::default()
+        })
+    }
+
+    pub fn get_artists(&self, request: &browsing::GetArtists) -> ArtistsID3 {
+        self.index_builder(request.music_folder_id).artists_id3()
+    }
+
+    pub fn get_artist(&self, request: &browsing::GetArtist) -> Option<ArtistWithAlbumsID3> {
+        let artist = self.artist(&request.id)?;
+        Some(ArtistWithAlbumsID3 {
+            artist: self.artist_id3(artist, None),
+            album: self
+                .albums_of(&artist.id)
+                .into_iter()
+                .map(|album| self.album_id3(album))
+                .collect(),
+        })
+    }
+
+    pub fn get_album(&self, request: &browsing::GetAlbum) -> Option<AlbumWithSongsID3> {
+        let album = self.album(&request.id)?;
+        Some(AlbumWithSongsID3 {
+            album: self.album_id3(album),
+            song: self
+                .songs_of(&album.id)
+                .into_iter()
+                .map(|song| self.song_child(song))
+                .collect(),
+        })
+    }
+
+    pub fn get_song(&self, request: &browsing::GetSong) -> Option<Child> {
+        self.song(&request.id).map(|song| self.song_child(song))
+    }
+
+    /// The genres of all albums and songs, ordered by name.
+    pub fn get_genres(&self) -> Genres {
+        let mut genres: HashMap<&str, (u32, Vec<&AlbumId>)> = HashMap::new();
+        for album in &self.albums {
+            if let Some(genre) = &album.genre {
+                genres.entry(genre).or_default().1.push(&album.id);
+            }
+        }
+        for song in &self.songs {
+            if let Some(genre) = self.song_genre(song) {
+                let (songs, albums) = genres.entry(genre).or_default();
+                *songs += 1;
+                albums.push(&song.album_id);
+            }
+        }
+        let mut genre = genres
+            .into_iter()
+            .map(|(name, (song_count, mut albums))| {
+                albums.sort();
+                albums.dedup();
+                Genre {
+                    song_count,
+                    album_count: albums.len() as u32,
+                    name: name.to_string(),
+                }
+            })
+            .collect::<Vec<_>>();
+        genre.sort_by_key(|genre| genre.name.to_lowercase());
+        Genres { genre }
+    }
+
+    /// The albums with the annotations used by [`AlbumLists`].
+    /// Built once and kept until the library changes.
+    pub fn album_lists(&self) -> &AlbumLists {
+        self.album_lists.get_or_init(|| self.build_album_lists())
+    }
+
+    fn build_album_lists(&self) -> AlbumLists {
+        self.albums
+            .iter()
+            .map(|album| {
+                let annotation = AlbumAnnotation {
+                    music_folder_id: Some(album.music_folder_id),
+                    average_rating: album.average_rating,
+                    last_played: album.last_played.clone(),
+                };
+                (self.album_id3(album), annotation)
+            })
+            .collect()
+    }
+
+    pub fn get_album_list(
+        &self,
+        request: &lists::GetAlbumList,
+        rng: &mut Rng,
+    ) -> Result<AlbumList, Error> {
+        self.album_lists().album_list(request, rng)
+    }
+
+    pub fn get_album_list2(
+        &self,
+        request: &lists::GetAlbumList2,
+        rng: &mut Rng,
+    ) -> Result<AlbumList2, Error> {
+        self.album_lists().album_list2(request, rng)
+    }
+
+    pub fn get_songs_by_genre(&self, request: &lists::GetSongsByGenre) -> Songs {
+        let offset = request.offset.unwrap_or(0) as usize;
+        let count = request.count.unwrap_or(10).min(500) as usize;
+        Songs {
+            song: self
+                .songs
+                .iter()
+                .filter(|song| self.song_genre(song) == Some(request.genre.as_str()))
+                .filter(|song| self.song_in_folder(song, request.music_folder_id))
+                .skip(offset)
+                .take(count)
+                .map(|song| self.song_child(song))
+                .collect(),
+        }
+    }
+
+    /// Picks up to `size` songs matching the filters of the request.
+    /// The same `rng` state always picks the same songs from the same library.
+    pub fn get_random_songs(&self, request: &lists::GetRandomSongs, rng: &mut Rng) -> Songs {
+        let size = request.size.unwrap_or(10).min(500) as usize;
+        let mut candidates = self
+            .songs
+            .iter()
+            .filter(|song| {
+                request
+                    .genre
+                    .as_deref()
+                    .is_none_or(|genre| self.song_genre(song) == Some(genre))
+            })
+            .filter(|song| {
+                let year = self.song_year(song);
+                request
+                    .from_year
+                    .is_none_or(|from| year.is_some_and(|y| y >= from))
+                    && request
+                        .to_year
+                        .is_none_or(|to| year.is_some_and(|y| y <= to))
+            })
+            .filter(|song| self.song_in_folder(song, request.music_folder_id))
+            .collect::<Vec<_>>();
+
+        // Partial Fisher-Yates shuffle, only the first `size` positions are needed.
+        let size = size.min(candidates.len());
+        for i in 0..size {
+            let j = i + rng.below(candidates.len() - i);
+            candidates.swap(i, j);
+        }
+        Songs {
+            song: candidates[..size]
+                .iter()
+                .map(|song| self.song_child(song))
+                .collect(),
+        }
+    }
+
+    pub fn get_videos(&self) -> Videos {
+        Videos {
+            video: self.videos.iter().map(video_child).collect(),
+        }
+    }
+
+    /// The artists, albums and songs of the library in a [`SearchIndex`].
+    /// Built once and kept until the library changes.
+    pub fn search_index(&self) -> &SearchIndex {
+        self.search_index.get_or_init(|| self.build_search_index())
+    }
+
+    fn build_search_index(&self) -> SearchIndex {
+        let mut index = SearchIndex::new();
+        for artist in &self.artists {
+            let mut folders = self
+                .albums_of(&artist.id)
+                .iter()
+                .map(|album| album.music_folder_id)
+                .collect::<Vec<_>>();
+            folders.sort();
+            folders.dedup();
+            index.insert_artist(self.artist_id3(artist, None), folders);
+        }
+        for album in &self.albums {
+            index.insert_album(self.album_id3(album), Some(album.music_folder_id));
+        }
+        for song in &self.songs {
+            let folder = self
+                .album(&song.album_id)
+                .map(|album| album.music_folder_id);
+            index.insert_song(self.song_child(song), folder);
+        }
+        index
+    }
+
+    pub fn search(&self, request: &Search) -> SearchResult {
+        self.search_index().search(request)
+    }
+
+    pub fn search2(&self, request: &Search2) -> SearchResult2 {
+        self.search_index().search2(request)
+    }
+
+    pub fn search3(&self, request: &Search3) -> SearchResult3 {
+        self.search_index().search3(request)
+    }
+
+    /// An [`IndexBuilder`] with the artists that have albums in `folder`, or every artist.
+    /// Use it to build indexes with other articles or a different [`Collation`](index::Collation).
+    pub fn index_builder(&self, folder: Option<MusicFolderId>) -> IndexBuilder {
+        IndexBuilder::new().artists(
+            self.artists
+                .iter()
+                .filter(|artist| {
+                    folder.is_none_or(|folder| {
+                        self.albums_of(&artist.id)
+                            .iter()
+                            .any(|album| album.music_folder_id == folder)
+                    })
+                })
+                .map(|artist| self.artist_id3(artist, folder)),
+        )
+    }
+
+    fn artist_id3(&self, artist: &Artist, folder: Option<MusicFolderId>) -> ArtistID3 {
+        let album_count = self
+            .albums_of(&artist.id)
+            .iter()
+            .filter(|album| folder.is_none_or(|folder| album.music_folder_id == folder))
+            .count();
+        ArtistID3 {
+            id: artist.id.clone(),
+            name: artist.name.clone(),
+            cover_art: artist.cover_art.clone(),
+            artist_image_url: artist.artist_image_url.clone(),
+            album_count: album_count as u32,
+            starred: artist.starred.clone(),
+        }
+    }
+
+    fn album_id3(&self, album: &Album) -> AlbumID3 {
+        let songs = self.songs_of(&album.id);
+        let duration = songs
+            .iter()
+            .filter_map(|song| song.duration)
+            .map(|duration| duration.to_duration().as_secs())
+            .sum::<u64>();
+        AlbumID3 {
+            id: album.id.clone(),
+            name: album.name.clone(),
+            artist: self
+                .artist(&album.artist_id)
+                .map(|artist| artist.name.clone()),
+            artist_id: Some(album.artist_id.clone()),
+            cover_art: album.cover_art.clone(),
+            song_count: songs.len() as u32,
+            duration: duration as u32,
+            play_count: album.play_count,
+            created: album.created.clone(),
+            starred: album.starred.clone(),
+            year: album.year,
+            genre: album.genre.clone(),
+        }
+    }
+
+    fn song_child(&self, song: &Song) -> Child {
+        let album = self.album(&song.album_id);
+        let artist_id = song
+            .artist_id
+            .clone()
+            .or_else(|| album.map(|album| album.artist_id.clone()));
+        Child {
+            id: song.id.clone(),
+            parent: Some(DirectoryId::new(song.album_id.as_str())),
+            is_dir: false,
+            title: song.title.clone(),
+            album: album.map(|album| album.name.clone()),
+            artist: artist_id
+                .as_ref()
+                .and_then(|id| self.artist(id))
+                .map(|artist| artist.name.clone()),
+            track: song.track,
+            year: self.song_year(song),
+            genre: self.song_genre(song).map(str::to_string),
+            cover_art: song
+                .cover_art
+                .clone()
+                .or_else(|| album.and_then(|album| album.cover_art.clone())),
+            size: song.size,
+            content_type: song.content_type.clone(),
+            suffix: song.suffix.clone(),
+            duration: song.duration,
+            bit_rate: song.bit_rate,
+            path: song.path.clone(),
+            is_video: Some(false),
+            play_count: song.play_count,
+            disc_number: song.disc_number,
+            created: song.created.clone(),
+            starred: song.starred.clone(),
+            album_id: Some(song.album_id.clone()),
+            artist_id,
+            media_type: Some(MediaType::Music),
+            ..Default::default()
+        }
+    }
+
+    fn song_year(&self, song: &Song) -> Option<u32> {
+        song.year
+            .or_else(|| self.album(&song.album_id).and_then(|album| album.year))
+    }
+
+    fn song_genre<'a>(&'a self, song: &'a Song) -> Option<&'a str> {
+        song.genre
+            .as_deref()
+            .or_else(|| self.album(&song.album_id)?.genre.as_deref())
+    }
+
+    fn song_in_folder(&self, song: &Song, folder: Option<MusicFolderId>) -> bool {
+        folder.is_none_or(|folder| {
+            self.album(&song.album_id)
+                .is_some_and(|album| album.music_folder_id == folder)
+        })
+    }
+
+    fn touch(&mut self) {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or(0);
+        // Never go back in time, `if_modified_since` relies on it.
+        self.last_modified = self.last_modified.max(Milliseconds::new(now));
+        // Any entry can change the search entries and album lists of the others, e.g. a song
+        // changes the song count of its album.
+        self.search_index = OnceLock::new();
+        self.album_lists = OnceLock::new();
+    }
+}
+
+/// A [`SubsonicService`] answering from a [`Library`].
+/// Requests the library cannot answer return the default not implemented error.
+#[derive(Debug)]
+pub struct LibraryService {
+    library: Library,
+    rng: Mutex<Rng>,
+}
+
+impl LibraryService {
+    /// Seeds the generator used for `getRandomSongs` from the operating system.
+    pub fn new(library: Library) -> Result<Self, getrandom::Error> {
+        Ok(Self::with_rng(library, Rng::from_entropy()?))
+    }
+
+    /// Uses `rng` for `getRandomSongs`, e.g. a seeded [`Rng`] for reproducible tests.
+    pub fn with_rng(library: Library, rng: Rng) -> Self {
+        Self {
+            library,
+            rng: Mutex::new(rng),
+        }
+    }
+
+    pub fn library(&self) -> &Library {
+        &self.library
+    }
+
+    pub fn into_inner(self) -> Library {
+        self.library
+    }
+}
+
+#[async_trait::async_trait]
+impl SubsonicService for LibraryService {
+    async fn get_music_folders(
+        &self,
+        request: Request<browsing::GetMusicFolders>,
+    ) -> service::Result<Response> {
+        Ok(Response::music_folders(
+            request.version,
+            self.library.get_music_folders(),
+        ))
+    }
+
+    async fn get_indexes(
+        &self,
+        request: Request<browsing::GetIndexes>,
+    ) -> service::Result<Response> {
+        Ok(Response::indexes(
+            request.version,
+            self.library.get_indexes(&request.body),
+        ))
+    }
+
+    async fn get_music_directory(
+        &self,
+        request: Request<browsing::GetMusicDirectory>,
+    ) -> service::Result<Response> {
+        let directory = self
+            .library
+            .get_music_directory(&request.body)
+            .ok_or_else(|| not_found("directory"))?;
+        Ok(Response::directory(request.version, directory))
+    }
+
+    async fn get_genres(&self, request: Request<browsing::GetGenres>) -> service::Result<Response> {
+        Ok(Response::genres(request.version, self.library.get_genres()))
+    }
+
+    async fn get_artists(
+        &self,
+        request: Request<browsing::GetArtists>,
+    ) -> service::Result<Response> {
+        Ok(Response::artists(
+            request.version,
+            self.library.get_artists(&request.body),
+        ))
+    }
+
+    async fn get_artist(&self, request: Request<browsing::GetArtist>) -> service::Result<Response> {
+        let artist = self
+            .library
+            .get_artist(&request.body)
+            .ok_or_else(|| not_found("artist"))?;
+        Ok(Response::artist(request.version, artist))
+    }
+
+    async fn get_album(&self, request: Request<browsing::GetAlbum>) -> service::Result<Response> {
+        let album = self
+            .library
+            .get_album(&request.body)
+            .ok_or_else(|| not_found("album"))?;
+        Ok(Response::album(request.version, album))
+    }
+
+    async fn get_song(&self, request: Request<browsing::GetSong>) -> service::Result<Response> {
+        let song = self
+            .library
+            .get_song(&request.body)
+            .ok_or_else(|| not_found("song"))?;
+        Ok(Response::song(request.version, song))
+    }
+
+    async fn get_videos(&self, request: Request<browsing::GetVideos>) -> service::Result<Response> {
+        Ok(Response::videos(request.version, self.library.get_videos()))
+    }
+
+    async fn get_album_list(
+        &self,
+        request: Request<lists::GetAlbumList>,
+    ) -> service::Result<Response> {
+        let mut rng = self.rng.lock().unwrap_or_else(|err| err.into_inner());
+        let list = self.library.get_album_list(&request.body, &mut rng)?;
+        Ok(Response::album_list(request.version, list))
+    }
+
+    async fn get_album_list2(
+        &self,
+        request: Request<lists::GetAlbumList2>,
+    ) -> service::Result<Response> {
+        let mut rng = self.rng.lock().unwrap_or_else(|err| err.into_inner());
+        let list = self.library.get_album_list2(&request.body, &mut rng)?;
+        Ok(Response::album_list2(request.version, list))
+    }
+
+    async fn search(&self, request: Request<Search>) -> service::Result<Response> {
+        Ok(Response::search_result(
+            request.version,
+            self.library.search(&request.body),
+        ))
+    }
+
+    async fn search2(&self, request: Request<Search2>) -> service::Result<Response> {
+        Ok(Response::search_result2(
+            request.version,
+            self.library.search2(&request.body),
+        ))
+    }
+
+    async fn search3(&self, request: Request<Search3>) -> service::Result<Response> {
+        Ok(Response::search_result3(
+            request.version,
+            self.library.search3(&request.body),
+        ))
+    }
+
+    async fn get_random_songs(
+        &self,
+        request: Request<lists::GetRandomSongs>,
+    ) -> service::Result<Response> {
+        let mut rng = self.rng.lock().unwrap_or_else(|err| err.into_inner());
+        let songs = self.library.get_random_songs(&request.body, &mut rng);
+        Ok(Response::random_songs(request.version, songs))
+    }
+
+    async fn get_songs_by_genre(
+        &self,
+        request: Request<lists::GetSongsByGenre>,
+    ) -> service::Result<Response> {
+        Ok(Response::songs_by_genre(
+            request.version,
+            self.library.get_songs_by_genre(&request.body),
+        ))
+    }
+}
+
+fn not_found(what: &str) -> Error {
+    Error::with_message(ErrorCode::DataNotFound, format!("{what} not found"))
+}
+
+/// Inserts `item` at the position of `key`, or at the end if `key` is new.
+/// Returns the position and the replaced item.
+fn upsert<K, T>(
+    items: &mut Vec<T>,
+    index: &mut HashMap<K, usize>,
+    key: K,
+    item: T,
+) -> (usize, Option<T>)
+where
+    K: Hash + Eq,
+{
+    match index.get(&key) {
+        Some(&position) => (
+            position,
+            Some(std::mem::replace(&mut items[position], item)),
+        ),
+        None => {
+            index.insert(key, items.len());
+            items.push(item);
+            (items.len() - 1, None)
+        }
+    }
+}
+
+fn unlink<K: Hash + Eq>(children: &mut HashMap<K, Vec<usize>>, parent: &K, position: usize) {
+    if let Some(positions) = children.get_mut(parent) {
+        positions.retain(|&p| p != position);
+    }
+}
+
+fn video_child(video: &Video) -> Child {
+    Child {
+        id: video.id.clone(),
+        is_dir: false,
+        title: video.title.clone(),
+        cover_art: video.cover_art.clone(),
+        size: video.size,
+        content_type: video.content_type.clone(),
+        suffix: video.suffix.clone(),
+        duration: video.duration,
+        bit_rate: video.bit_rate,
+        path: video.path.clone(),
+        is_video: Some(true),
+        created: video.created.clone(),
+        media_type: Some(MediaType::Video),
+        original_width: video.original_width,
+        original_height: video.original_height,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        request::{browsing::*, lists::*, search::*},
+        test_support::request,
+    };
+
+    use super::*;
+
+    const ROCK: MusicFolderId = MusicFolderId::new(1);
+    const JAZZ: MusicFolderId = MusicFolderId::new(2);
+
+    fn library() -> Library {
+        let mut library = Library::new();
+        library.insert_folder(MusicFolder::new(ROCK, "Rock"));
+        library.insert_folder(MusicFolder::new(JAZZ, "Jazz"));
+        library.insert_artist(Artist::new("ar-1", "radiohead"));
+        library.insert_artist(Artist::new("ar-2", "2Pac"));
+        library.insert_artist(Artist::new("ar-3", "Miles Davis"));
+        library.insert_artist(Artist::new("ar-4", "Muse"));
+        library.insert_album(Album {
+            year: Some(1997),
+            genre: Some("Rock".into()),
+            cover_art: Some("al-1".into()),
+            ..Album::new("al-1", "OK Computer", "ar-1", ROCK)
+        });
+        library.insert_album(Album {
+            year: Some(1995),
+            ..Album::new("al-2", "The Bends", "ar-1", ROCK)
+        });
+        library.insert_album(Album {
+            year: Some(1959),
+            genre: Some("Jazz".into()),
+            ..Album::new("al-3", "Kind of Blue", "ar-3", JAZZ)
+        });
+        library.insert_album(Album::new("al-4", "Greatest Hits", "ar-2", ROCK));
+        library.insert_song(Song {
+            track: Some(2),
+            duration: Some(Seconds::new(100)),
+            ..Song::new("so-1", "Paranoid Android", "al-1")
+        });
+        library.insert_song(Song {
+            track: Some(1),
+            duration: Some(Seconds::new(20)),
+            ..Song::new("so-2", "Airbag", "al-1")
+        });
+        library.insert_song(Song {
+            genre: Some("Alternative".into()),
+            ..Song::new("so-3", "Planet Telex", "al-2")
+        });
+        library.insert_song(Song::new("so-4", "So What", "al-3"));
+        library.insert_song(Song::new("so-5", "Changes", "al-4"));
+        library.insert_video(Video::new("vi-1", "Live", ROCK));
+        library
+    }
+
+    fn names(songs: &Songs) -> Vec<&str> {
+        songs.song.iter().map(|song| song.title.as_str()).collect()
+    }
+
+    #[test]
+    fn artists_are_indexed_by_first_letter() {
+        let library = library();
+        let artists = library.get_artists(&GetArtists {
+            music_folder_id: None,
+        });
+        let index = artists
+            .index
+            .iter()
+            .map(|index| {
+                let names = index.artist.iter().map(|a| a.name.as_str()).collect();
+                (index.name.as_str(), names)
+            })
+            .collect::<Vec<(_, Vec<_>)>>();
+        assert_eq!(
+            vec![
+                ("M", vec!["Miles Davis", "Muse"]),
+                ("R", vec!["radiohead"]),
+                ("#", vec!["2Pac"]),
+            ],
+            index
+        );
+        assert_eq!(2, artists.index[1].artist[0].album_count);
+        assert_eq!(index::DEFAULT_IGNORED_ARTICLES, artists.ignored_articles);
+
+        let jazz = library.get_artists(&GetArtists {
+            music_folder_id: Some(JAZZ),
+        });
+        assert_eq!(1, jazz.index.len());
+        assert_eq!("Miles Davis", jazz.index[0].artist[0].name);
+
+        let indexes = library.get_indexes(&GetIndexes {
+            music_folder_id: Some(ROCK),
+            if_modified_since: None,
+        });
+        assert_eq!(library.last_modified(), indexes.last_modified);
+        assert_eq!(2, indexes.index.len());
+        assert_eq!(DirectoryId::new("ar-1"), indexes.index[0].artist[0].id);
+
+        let unchanged = library.get_indexes(&GetIndexes {
+            music_folder_id: None,
+            if_modified_since: Some(library.last_modified()),
+        });
+        assert!(unchanged.index.is_empty());
+    }
+
+    #[test]
+    fn directories_and_albums() {
+        let library = library();
+        let artist = library
+            .get_music_directory(&GetMusicDirectory { id: "ar-1".into() })
+            .unwrap();
+        let albums = artist
+            .child
+            .iter()
+            .map(|c| c.title.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["The Bends", "OK Computer"], albums);
+        assert!(artist.child.iter().all(|child| child.is_dir));
+
+        let album = library
+            .get_music_directory(&GetMusicDirectory { id: "al-1".into() })
+            .unwrap();
+        assert_eq!(Some(DirectoryId::new("ar-1")), album.parent);
+        assert_eq!("Airbag", album.child[0].title);
+        assert!(library
+            .get_music_directory(&GetMusicDirectory { id: "so-1".into() })
+            .is_none());
+
+        let album = library.get_album(&GetAlbum { id: "al-1".into() }).unwrap();
+        assert_eq!(2, album.album.song_count);
+        assert_eq!(120, album.album.duration);
+        assert_eq!(Some("radiohead".to_string()), album.album.artist);
+
+        let song = library.get_song(&GetSong { id: "so-2".into() }).unwrap();
+        assert_eq!(Some(1997), song.year);
+        assert_eq!(Some("Rock".to_string()), song.genre);
+        assert_eq!(Some(CoverArtId::new("al-1")), song.cover_art);
+        assert_eq!(Some(ArtistId::new("ar-1")), song.artist_id);
+
+        let videos = library.get_videos();
+        assert_eq!(Some(true), videos.video[0].is_video);
+        assert_eq!(Some(MediaType::Video), videos.video[0].media_type);
+    }
+
+    #[test]
+    fn genres_and_songs_by_genre() {
+        let library = library();
+        let genres = library
+            .get_genres()
+            .genre
+            .into_iter()
+            .map(|genre| (genre.name, genre.song_count, genre.album_count))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("Alternative".to_string(), 1, 1),
+                ("Jazz".to_string(), 1, 1),
+                ("Rock".to_string(), 2, 1),
+            ],
+            genres
+        );
+
+        let rock = GetSongsByGenre::builder("Rock").build();
+        assert_eq!(
+            vec!["Paranoid Android", "Airbag"],
+            names(&library.get_songs_by_genre(&rock))
+        );
+        let paged = GetSongsByGenre::builder("Rock").offset(1).count(5).build();
+        assert_eq!(vec!["Airbag"], names(&library.get_songs_by_genre(&paged)));
+        let jazz = GetSongsByGenre::builder("Rock")
+            .music_folder_id(JAZZ)
+            .build();
+        assert!(library.get_songs_by_genre(&jazz).song.is_empty());
+    }
+
+    #[test]
+    fn random_songs_are_reproducible() {
+        let library = library();
+        let all = GetRandomSongs::builder().size(10).build();
+        let first = library.get_random_songs(&all, &mut Rng::new(7));
+        assert_eq!(first, library.get_random_songs(&all, &mut Rng::new(7)));
+        let mut titles = names(&first);
+        titles.sort();
+        titles.dedup();
+        assert_eq!(5, titles.len());
+
+        let two = GetRandomSongs::builder().size(2).build();
+        assert_eq!(
+            2,
+            library.get_random_songs(&two, &mut Rng::new(1)).song.len()
+        );
+
+        let filtered = GetRandomSongs::builder()
+            .from_year(1990)
+            .to_year(1996)
+            .build();
+        assert_eq!(
+            vec!["Planet Telex"],
+            names(&library.get_random_songs(&filtered, &mut Rng::new(3)))
+        );
+        let genre = GetRandomSongs::builder()
+            .genre("Jazz")
+            .music_folder_id(ROCK)
+            .build();
+        assert!(library
+            .get_random_songs(&genre, &mut Rng::new(3))
+            .song
+            .is_empty());
+    }
+
+    #[test]
+    fn album_lists() {
+        let mut library = library();
+        library.insert_album(Album {
+            play_count: Some(3),
+            ..Album::new("al-5", "Amnesiac", "ar-1", JAZZ)
+        });
+        let rng = &mut Rng::new(0);
+        let by_name = GetAlbumList2::builder(ListType::AlphabeticalByName)
+            .music_folder_id(ROCK)
+            .build();
+        let names = library
+            .get_album_list2(&by_name, rng)
+            .unwrap()
+            .album
+            .into_iter()
+            .map(|album| album.name)
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["Greatest Hits", "OK Computer", "The Bends"], names);
+
+        let frequent = GetAlbumList::builder(ListType::Frequent).build();
+        let list = library.get_album_list(&frequent, rng).unwrap();
+        assert_eq!(1, list.album.len());
+        assert_eq!(Some(DirectoryId::new("ar-1")), list.album[0].parent);
+    }
+
+    #[test]
+    fn search_uses_album_folders() {
+        let library = library();
+        let result = library.search3(&Search3::builder("").music_folder_id(JAZZ).build());
+        assert_eq!(
+            vec![ArtistId::new("ar-3")],
+            result.artist.into_iter().map(|a| a.id).collect::<Vec<_>>()
+        );
+        assert_eq!("Kind of Blue", result.album[0].name);
+        assert_eq!("So What", result.song[0].title);
+
+        let result = library.search(&Search::builder().album("ok").build());
+        assert_eq!(2, result.total_hits);
+    }
+
+    #[test]
+    fn replacing_entries_updates_the_indexes() {
+        let mut library = library();
+        let songs = |library: &Library, query: &str| {
+            let result = library.search3(&Search3::builder(query).build());
+            result.song.into_iter().map(|s| s.id).collect::<Vec<_>>()
+        };
+        assert_eq!(vec![SongId::new("so-1")], songs(&library, "paranoid"));
+        // The search index and album lists are only built once.
+        assert!(std::ptr::eq(library.search_index(), library.search_index()));
+        assert!(std::ptr::eq(library.album_lists(), library.album_lists()));
+
+        let old = library.insert_song(Song::new("so-1", "Let Down", "al-2"));
+        assert_eq!("Paranoid Android", old.unwrap().title);
+        assert_eq!(5, library.songs().len());
+        assert_eq!(1, library.songs_of(&"al-1".into()).len());
+        assert_eq!(2, library.songs_of(&"al-2".into()).len());
+        assert!(songs(&library, "paranoid").is_empty());
+        assert_eq!(vec![SongId::new("so-1")], songs(&library, "let down bends"));
+        let by_name = GetAlbumList2::builder(ListType::AlphabeticalByName).build();
+        let list = library.get_album_list2(&by_name, &mut Rng::new(0)).unwrap();
+        let bends = list.album.iter().find(|album| album.name == "The Bends");
+        assert_eq!(2, bends.unwrap().song_count);
+
+        library.insert_album(Album::new("al-2", "The Bends", "ar-4", ROCK));
+        assert_eq!(1, library.albums_of(&"ar-1".into()).len());
+        assert_eq!("The Bends", library.albums_of(&"ar-4".into())[0].name);
+    }
+
+    #[tokio::test]
+    async fn service_answers_from_the_library() {
+        let service = LibraryService::with_rng(library(), Rng::new(0));
+        let request = |id: &str| request(GetAlbum { id: id.into() });
+
+        let response = service.get_album(request("al-3")).await.unwrap();
+        let response::ResponseBody::Album(album) = response.body else {
+            panic!("unexpected response {response:?}");
+        };
+        assert_eq!("Kind of Blue", album.album.name);
+
+        let err = service.get_album(request("missing")).await.unwrap_err();
+        assert_eq!(ErrorCode::DataNotFound, err.code);
+    }
+}
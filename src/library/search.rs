@@ -0,0 +1,392 @@
+//! An in-memory index answering `search`, `search2` and `search3`.
+//!
+//! The names of artists, albums and songs are split into words, compared case-insensitively and
+//! without diacritics. An entry matches a query when every word of the query is the start of one
+//! of its words, so `"ok comp"` matches the album "OK Computer". Artists are searched by name,
+//! albums by name and artist and songs by title, artist and album.
+//!
+//! An empty query, or one without any words like `""` or `*`, matches everything. Clients such
+//! as DSub rely on this to download the whole library with `search3`.
+//!
+//! Results are returned in insertion order.
+//!
+//! # Example
+//! ```rust
+//! use subsonic_types::{
+//!     library::search::SearchIndex,
+//!     request::search::Search3,
+//!     response::{AlbumID3, ArtistID3},
+//! };
+//!
+//! let mut index = SearchIndex::new();
+//! index.insert_artist(ArtistID3 { id: "1".into(), name: "Björk".into(), ..Default::default() }, None);
+//! index.insert_album(AlbumID3::builder("2", "Homogenic", 10, 2640).artist("Björk").build(), None);
+//!
+//! let result = index.search3(&Search3::builder("bjo").build());
+//! assert_eq!(1, result.artist.len());
+//! assert_eq!("Homogenic", result.album[0].name);
+//! ```
+
+use time::OffsetDateTime;
+
+use crate::{
+    common::{Milliseconds, MusicFolderId},
+    request::search::{Search, Search2, Search3},
+    response::{AlbumID3, ArtistID3, Child, SearchResult, SearchResult2, SearchResult3},
+};
+
+use super::text::tokens;
+
+#[derive(Debug, Clone)]
+struct Entry<T> {
+    item: T,
+    tokens: Vec<String>,
+    music_folder_ids: Vec<MusicFolderId>,
+}
+
+impl<T> Entry<T> {
+    fn new<'a>(
+        item: T,
+        text: impl IntoIterator<Item = &'a str>,
+        music_folder_ids: impl IntoIterator<Item = MusicFolderId>,
+    ) -> Self {
+        Self {
+            item,
+            tokens: text.into_iter().flat_map(tokens).collect(),
+            music_folder_ids: music_folder_ids.into_iter().collect(),
+        }
+    }
+
+    fn in_folder(&self, folder: Option<MusicFolderId>) -> bool {
+        folder.is_none_or(|folder| self.music_folder_ids.contains(&folder))
+    }
+}
+
+/// The words of a query.
+struct Query(Vec<String>);
+
+impl Query {
+    fn parse(query: &str) -> Self {
+        Self(tokens(query))
+    }
+
+    fn matches(&self, words: &[String]) -> bool {
+        self.0
+            .iter()
+            .all(|prefix| words.iter().any(|word| word.starts_with(prefix.as_str())))
+    }
+}
+
+/// A search index over artists, albums and songs.
+/// See the [module documentation](self) for the matching rules.
+#[derive(Debug, Default, Clone)]
+pub struct SearchIndex {
+    artists: Vec<Entry<ArtistID3>>,
+    albums: Vec<Entry<AlbumID3>>,
+    songs: Vec<Entry<Child>>,
+}
+
+impl SearchIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an artist found in the given music folders.
+    /// Entries without a music folder are left out of searches for a `musicFolderId`.
+    pub fn insert_artist(
+        &mut self,
+        artist: ArtistID3,
+        music_folder_ids: impl IntoIterator<Item = MusicFolderId>,
+    ) {
+        let entry = Entry::new(artist.clone(), [artist.name.as_str()], music_folder_ids);
+        self.artists.push(entry);
+    }
+
+    pub fn insert_album(
+        &mut self,
+        album: AlbumID3,
+        music_folder_ids: impl IntoIterator<Item = MusicFolderId>,
+    ) {
+        let text = [Some(album.name.as_str()), album.artist.as_deref()];
+        let entry = Entry::new(album.clone(), text.into_iter().flatten(), music_folder_ids);
+        self.albums.push(entry);
+    }
+
+    pub fn insert_song(
+        &mut self,
+        song: Child,
+        music_folder_ids: impl IntoIterator<Item = MusicFolderId>,
+    ) {
+        let text = [
+            Some(song.title.as_str()),
+            song.artist.as_deref(),
+            song.album.as_deref(),
+        ];
+        let entry = Entry::new(song.clone(), text.into_iter().flatten(), music_folder_ids);
+        self.songs.push(entry);
+    }
+
+    /// Answers the deprecated `search`, which only returns songs.
+    /// Every given field has to match, `any` matches the title, artist or album. Songs without a
+    /// creation date are left out when `newerThan` is given.
+    pub fn search(&self, request: &Search) -> SearchResult {
+        let field = |value: &Option<String>| value.as_deref().map(Query::parse);
+        let (artist, album, title) = (
+            field(&request.artist),
+            field(&request.album),
+            field(&request.title),
+        );
+        let any = field(&request.any);
+        let matches_field = |query: &Option<Query>, value: Option<&str>| {
+            query
+                .as_ref()
+                .is_none_or(|query| query.matches(&tokens(value.unwrap_or_default())))
+        };
+
+        let matches = self
+            .songs
+            .iter()
+            .filter(|entry| {
+                let song = &entry.item;
+                matches_field(&artist, song.artist.as_deref())
+                    && matches_field(&album, song.album.as_deref())
+                    && matches_field(&title, Some(&song.title))
+                    && any.as_ref().is_none_or(|any| any.matches(&entry.tokens))
+                    && request
+                        .newer_than
+                        .is_none_or(|newer_than| is_newer(song, newer_than))
+            })
+            .map(|entry| &entry.item)
+            .collect::<Vec<_>>();
+
+        let offset = request.offset.unwrap_or(0);
+        let count = request.count.unwrap_or(20);
+        SearchResult {
+            offset,
+            total_hits: matches.len() as u32,
+            matches: matches
+                .into_iter()
+                .skip(offset as usize)
+                .take(count as usize)
+                .cloned()
+                .collect(),
+        }
+    }
+
+    pub fn search2(&self, request: &Search2) -> SearchResult2 {
+        self.find(
+            &request.query,
+            request.music_folder_id,
+            [
+                (request.artist_offset, request.artist_count),
+                (request.album_offset, request.album_count),
+                (request.song_offset, request.song_count),
+            ],
+        )
+        .into()
+    }
+
+    pub fn search3(&self, request: &Search3) -> SearchResult3 {
+        self.find(
+            &request.query,
+            request.music_folder_id,
+            [
+                (request.artist_offset, request.artist_count),
+                (request.album_offset, request.album_count),
+                (request.song_offset, request.song_count),
+            ],
+        )
+    }
+
+    /// Searches with the offset and count of artists, albums and songs.
+    fn find(
+        &self,
+        query: &str,
+        folder: Option<MusicFolderId>,
+        [artists, albums, songs]: [(Option<u32>, Option<u32>); 3],
+    ) -> SearchResult3 {
+        let query = Query::parse(query);
+        SearchResult3 {
+            artist: select(&self.artists, &query, folder, artists),
+            album: select(&self.albums, &query, folder, albums),
+            song: select(&self.songs, &query, folder, songs),
+        }
+    }
+}
+
+/// The page of the matching entries, 20 by default.
+fn select<T: Clone>(
+    entries: &[Entry<T>],
+    query: &Query,
+    folder: Option<MusicFolderId>,
+    (offset, count): (Option<u32>, Option<u32>),
+) -> Vec<T> {
+    entries
+        .iter()
+        .filter(|entry| entry.in_folder(folder) && query.matches(&entry.tokens))
+        .skip(offset.unwrap_or(0) as usize)
+        .take(count.unwrap_or(20) as usize)
+        .map(|entry| entry.item.clone())
+        .collect()
+}
+
+fn is_newer(song: &Child, newer_than: Milliseconds) -> bool {
+    song.created.clone().is_some_and(|created| {
+        let created = OffsetDateTime::from(created).unix_timestamp_nanos() / 1_000_000;
+        created >= newer_than.to_duration().as_millis() as i128
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::{DateTime, DirectoryId},
+        test_support::{self, album, artist},
+    };
+
+    use super::*;
+
+    const ROCK: MusicFolderId = MusicFolderId::new(1);
+    const JAZZ: MusicFolderId = MusicFolderId::new(2);
+
+    fn song(id: &str, title: &str, artist: &str, album: &str, created: i64) -> Child {
+        Child {
+            created: Some(DateTime::from(
+                OffsetDateTime::from_unix_timestamp(created).unwrap(),
+            )),
+            ..test_support::song(id, artist, album, title)
+        }
+    }
+
+    fn index() -> SearchIndex {
+        let mut index = SearchIndex::new();
+        index.insert_artist(artist("ar-1", "Björk"), [ROCK]);
+        index.insert_artist(artist("ar-2", "Miles Davis"), [JAZZ]);
+        index.insert_artist(artist("ar-3", "Mötley Crüe"), [ROCK, JAZZ]);
+        index.insert_album(album("al-1", "Björk", "Homogenic"), Some(ROCK));
+        index.insert_album(album("al-2", "Miles Davis", "Kind of Blue"), Some(JAZZ));
+        index.insert_album(album("al-3", "Radiohead", "OK Computer"), None);
+        index.insert_song(song("so-1", "Jóga", "Björk", "Homogenic", 100), Some(ROCK));
+        index.insert_song(
+            song("so-2", "So What", "Miles Davis", "Kind of Blue", 200),
+            Some(JAZZ),
+        );
+        index.insert_song(
+            song("so-3", "Blue in Green", "Miles Davis", "Kind of Blue", 300),
+            Some(JAZZ),
+        );
+        index
+    }
+
+    fn ids(result: &SearchResult3) -> (Vec<&str>, Vec<&str>, Vec<&str>) {
+        (
+            result.artist.iter().map(|a| a.id.as_str()).collect(),
+            result.album.iter().map(|a| a.id.as_str()).collect(),
+            result.song.iter().map(|s| s.id.as_str()).collect(),
+        )
+    }
+
+    #[test]
+    fn prefix_matching_ignores_case_and_diacritics() {
+        let index = index();
+        let search = |query: &str| index.search3(&Search3::builder(query).build());
+
+        assert_eq!(
+            (vec!["ar-1"], vec!["al-1"], vec!["so-1"]),
+            ids(&search("BJÖ"))
+        );
+        assert_eq!(
+            (vec!["ar-1"], vec!["al-1"], vec!["so-1"]),
+            ids(&search("bjork"))
+        );
+        assert_eq!((vec!["ar-3"], vec![], vec![]), ids(&search("motley crue")));
+        assert_eq!((vec![], vec!["al-3"], vec![]), ids(&search("ok comp")));
+        assert_eq!((vec![], vec![], vec!["so-1"]), ids(&search("joga")));
+        assert_eq!((vec![], vec![], vec!["so-3"]), ids(&search("blue gr")));
+        assert_eq!(
+            (vec![], vec!["al-2"], vec!["so-2", "so-3"]),
+            ids(&search("blue"))
+        );
+        assert_eq!((vec![], vec![], vec![]), ids(&search("ork")));
+        assert_eq!((vec![], vec![], vec![]), ids(&search("ok zebra")));
+    }
+
+    #[test]
+    fn empty_queries_return_everything() {
+        let index = index();
+        for query in ["", "\"\"", "*", " "] {
+            let result = index.search3(&Search3::builder(query).build());
+            assert_eq!(
+                (3, 3, 3),
+                (result.artist.len(), result.album.len(), result.song.len())
+            );
+        }
+
+        let paged = Search3::builder("")
+            .artist_offset(1)
+            .artist_count(1)
+            .album_count(0)
+            .song_offset(2)
+            .build();
+        assert_eq!(
+            (vec!["ar-2"], vec![], vec!["so-3"]),
+            ids(&index.search3(&paged))
+        );
+
+        let jazz = Search3::builder("").music_folder_id(JAZZ).build();
+        assert_eq!(
+            (vec!["ar-2", "ar-3"], vec!["al-2"], vec!["so-2", "so-3"]),
+            ids(&index.search3(&jazz))
+        );
+    }
+
+    #[test]
+    fn search2_uses_directories() {
+        let result = index().search2(&Search2::builder("homogenic").build());
+        assert_eq!(1, result.album.len());
+        assert!(result.album[0].is_dir);
+        assert_eq!("so-1", result.song[0].id.as_str());
+
+        let result = index().search2(&Search2::builder("bjork").build());
+        assert_eq!(DirectoryId::new("ar-1"), result.artist[0].id);
+    }
+
+    #[test]
+    fn legacy_search_fields() {
+        let index = index();
+        let titles = |request: Search| {
+            let result = index.search(&request);
+            let titles = result
+                .matches
+                .iter()
+                .map(|song| song.title.clone())
+                .collect::<Vec<_>>();
+            (result.total_hits, titles)
+        };
+
+        assert_eq!(
+            (2, vec!["So What".to_string(), "Blue in Green".to_string()]),
+            titles(Search::builder().artist("miles").build())
+        );
+        assert_eq!(
+            (1, vec!["Blue in Green".to_string()]),
+            titles(Search::builder().artist("miles").title("blue").build())
+        );
+        assert_eq!(
+            (0, vec![]),
+            titles(Search::builder().artist("kind").build())
+        );
+        assert_eq!(
+            (2, vec!["Blue in Green".to_string()]),
+            titles(Search::builder().any("kind").offset(1).build())
+        );
+        assert_eq!(
+            (2, vec!["So What".to_string(), "Blue in Green".to_string()]),
+            titles(
+                Search::builder()
+                    .newer_than(Milliseconds::new(200_000))
+                    .build()
+            )
+        );
+    }
+}
@@ -0,0 +1,65 @@
+//! Text normalization used to compare names.
+
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// Lowercases `text` and removes diacritics, e.g. `"Björk"` becomes `"bjork"`.
+/// Letters are decomposed and stripped of their combining marks, letters that do not decompose
+/// into a base letter, like `ß` or `æ`, are spelled out.
+pub(crate) fn fold(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.chars().flat_map(char::to_lowercase).nfd() {
+        match spell_out(c) {
+            Some(replacement) => folded.push_str(replacement),
+            None if is_combining_mark(c) => {}
+            None => folded.push(c),
+        }
+    }
+    folded
+}
+
+/// Splits folded `text` into its alphanumeric words.
+pub(crate) fn tokens(text: &str) -> Vec<String> {
+    fold(text)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Letters that have no decomposition into a base letter and a combining mark.
+fn spell_out(c: char) -> Option<&'static str> {
+    let spelled = match c {
+        'æ' => "ae",
+        'ð' | 'đ' => "d",
+        'ħ' => "h",
+        'ı' => "i",
+        'ł' => "l",
+        'ø' => "o",
+        'œ' => "oe",
+        'ß' => "ss",
+        'þ' => "th",
+        'ŧ' => "t",
+        _ => return None,
+    };
+    Some(spelled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folding() {
+        assert_eq!("bjork", fold("Björk"));
+        assert_eq!("motorhead", fold("MOTÖRHEAD"));
+        assert_eq!("strasse", fold("Straße"));
+        assert_eq!("sigur ros", fold("Sigur Ro\u{301}s"));
+        assert_eq!("dvorak", fold("Dvořák"));
+        assert_eq!("tiesto", fold("Tiësto"));
+        assert_eq!("anh", fold("Ánh"));
+        assert_eq!("lodz", fold("Łódź"));
+        assert_eq!("aether", fold("Æther"));
+        assert_eq!(vec!["the", "beatles", "1"], tokens("The Beatles - 1!"));
+        assert!(tokens(" - * ").is_empty());
+    }
+}
@@ -0,0 +1,789 @@
+//! JSON Schema and OpenAPI documents generated from the request and response types.
+//!
+//! Requires the `openapi` feature.
+//! The schemas describe the json format of a given API version: fields are only included
+//! in the versions they exist in and fields that are `Option` or `Vec` are not required,
+//! since servers omit empty lists.
+//!
+//! # Example
+//! ```rust
+//! use subsonic_types::{common::Version, openapi, response::Child};
+//!
+//! // `Child::album_id` was added in 1.8.0, `Child::bookmark_position` in 1.10.1.
+//! let schema = openapi::schema_for::<Child>(Version::V1_8_0);
+//! assert!(schema["properties"].get("albumId").is_some());
+//! assert!(schema["properties"].get("bookmarkPosition").is_none());
+//!
+//! let document = openapi::document(Version::LATEST);
+//! let search3 = &document["paths"]["/rest/search3"]["get"];
+//! assert_eq!("search3", search3["operationId"]);
+//! ```
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    capabilities::{FieldInfo, SubsonicFields},
+    common::{
+        AlbumId, ArtistId, AudioBitrate, AverageRating, CoverArtId, DateTime, DirectoryId, Id,
+        InternetRadioStationId, MediaType, Milliseconds, MusicFolderId, PlaylistId,
+        PodcastChannelId, PodcastEpisodeId, Seconds, ShareId, SongId, UserRating, Version,
+        VideoBitrate, VideoSize,
+    },
+    request::{
+        annotation, bookmark, browsing, chat,
+        jukebox::{self, JukeboxAction},
+        lists::{self, ListType},
+        playlists, podcast, radio, retrieval, scan, search, sharing, system, user, Authentication,
+        Request, SubsonicRequest,
+    },
+    response::{
+        AlbumInfo, AlbumList, AlbumList2, AlbumWithSongsID3, ArtistInfo, ArtistInfo2,
+        ArtistWithAlbumsID3, ArtistsID3, Bookmarks, ChatMessages, Child, Directory, Error,
+        ErrorCode, Genres, Indexes, InternetRadioStations, JukeboxPlaylist, JukeboxStatus, License,
+        Lyrics, MusicFolders, NewestPodcasts, NowPlaying, PlayQueue, PlaylistWithSongs, Playlists,
+        PodcastStatus, Podcasts, Response, ResponseBody, ResponseStatus, ScanStatus, SearchResult,
+        SearchResult2, SearchResult3, Shares, SimilarSongs, SimilarSongs2, Songs, Starred,
+        Starred2, TopSongs, User, Users, VideoInfo, Videos,
+    },
+};
+
+/// A type with a JSON Schema.
+/// This is implemented by `#[derive(SubsonicType)]` for response types.
+pub trait JsonSchema {
+    /// The name of the schema in the definitions, `None` if the schema is always inlined.
+    fn schema_name() -> Option<String> {
+        None
+    }
+
+    /// Can a field of this type be omitted from its object?
+    fn is_optional() -> bool {
+        false
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Value;
+}
+
+/// The query parameters of a request.
+/// This is implemented by `#[derive(ToQuery)]`.
+pub trait QueryParameters {
+    fn query_parameters(generator: &mut SchemaGenerator, parameters: &mut Vec<Value>);
+}
+
+/// Generates schemas for a given version and collects the definitions of named schemas.
+#[derive(Debug, Clone)]
+pub struct SchemaGenerator {
+    version: Version,
+    ref_prefix: &'static str,
+    definitions: Map<String, Value>,
+}
+
+impl SchemaGenerator {
+    /// A generator whose references point to `#/$defs/`.
+    pub fn new(version: Version) -> Self {
+        Self {
+            version,
+            ref_prefix: "#/$defs/",
+            definitions: Default::default(),
+        }
+    }
+
+    /// A generator whose references point to `#/components/schemas/`.
+    pub fn openapi(version: Version) -> Self {
+        Self {
+            ref_prefix: "#/components/schemas/",
+            ..Self::new(version)
+        }
+    }
+
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// The schema of `T`, a reference if `T` has a name.
+    pub fn subschema_for<T: JsonSchema + ?Sized>(&mut self) -> Value {
+        let name = match T::schema_name() {
+            Some(name) => name,
+            None => return T::json_schema(self),
+        };
+        if !self.definitions.contains_key(&name) {
+            // inserted first so recursive types terminate
+            self.definitions.insert(name.clone(), Value::Null);
+            let schema = T::json_schema(self);
+            self.definitions.insert(name.clone(), schema);
+        }
+        json!({ "$ref": format!("{}{}", self.ref_prefix, name) })
+    }
+
+    pub fn definitions(&self) -> &Map<String, Value> {
+        &self.definitions
+    }
+
+    pub fn into_definitions(self) -> Map<String, Value> {
+        self.definitions
+    }
+}
+
+/// Builds the schema of an object property by property.
+/// Used by the implementations generated by `#[derive(SubsonicType)]`.
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub struct ObjectSchema {
+    properties: Map<String, Value>,
+    required: Vec<Value>,
+}
+
+impl ObjectSchema {
+    /// Adds a field if it is present in the version of the generator.
+    pub fn property<T: JsonSchema>(&mut self, generator: &mut SchemaGenerator, field: &FieldInfo) {
+        let version = generator.version();
+        if !field.is_present(version) {
+            return;
+        }
+        let mut schema = generator.subschema_for::<T>();
+        if field.is_deprecated(version) {
+            schema["deprecated"] = Value::Bool(true);
+        }
+        if !T::is_optional() {
+            self.required.push(field.key.into());
+        }
+        self.properties.insert(field.key.to_string(), schema);
+    }
+
+    /// Adds the properties of a flattened type.
+    pub fn flatten<T: JsonSchema>(&mut self, generator: &mut SchemaGenerator) {
+        let mut schema = T::json_schema(generator);
+        if let Some(Value::Object(properties)) = schema.get_mut("properties").map(Value::take) {
+            self.properties.extend(properties);
+        }
+        if let Some(Value::Array(required)) = schema.get_mut("required").map(Value::take) {
+            self.required.extend(required);
+        }
+    }
+
+    pub fn into_schema(self) -> Value {
+        let mut schema = json!({
+            "type": "object",
+            "properties": self.properties,
+        });
+        if !self.required.is_empty() {
+            schema["required"] = Value::Array(self.required);
+        }
+        schema
+    }
+}
+
+/// Adds the query parameter `name` of type `T`.
+/// Used by the implementations generated by `#[derive(ToQuery)]`.
+#[doc(hidden)]
+pub fn query_parameter<T: JsonSchema>(
+    generator: &mut SchemaGenerator,
+    name: &str,
+    parameters: &mut Vec<Value>,
+) {
+    parameters.push(json!({
+        "name": name,
+        "in": "query",
+        "required": !T::is_optional(),
+        "schema": generator.subschema_for::<T>(),
+    }));
+}
+
+/// The JSON Schema of `T` in the given version, with the definitions it references.
+pub fn schema_for<T: JsonSchema>(version: Version) -> Value {
+    let mut generator = SchemaGenerator::new(version);
+    let mut schema = T::json_schema(&mut generator);
+    schema["$schema"] = "https://json-schema.org/draft/2020-12/schema".into();
+    if let Some(name) = T::schema_name() {
+        schema["title"] = name.into();
+    }
+    let definitions = generator.into_definitions();
+    if !definitions.is_empty() {
+        schema["$defs"] = Value::Object(definitions);
+    }
+    schema
+}
+
+/// The OpenAPI 3.1 document of the endpoints available in the given version.
+///
+/// Every endpoint is documented as a `GET` with its parameters in the query string.
+/// Responses are described in the json format, binary endpoints list the content type
+/// of the media they return.
+pub fn document(version: Version) -> Value {
+    let mut generator = SchemaGenerator::openapi(version);
+    let mut paths = Map::new();
+    for operation in OPERATIONS.iter().filter(|o| o.since <= version) {
+        let name = operation.path.trim_start_matches("/rest/");
+        paths.insert(
+            operation.path.to_string(),
+            json!({
+                "get": {
+                    "operationId": name,
+                    "externalDocs": {
+                        "url": format!("http://www.subsonic.org/pages/api.jsp#{}", name),
+                    },
+                    "parameters": (operation.parameters)(&mut generator),
+                    "responses": {
+                        "200": operation.content.response(&mut generator),
+                    },
+                },
+            }),
+        );
+    }
+    generator.subschema_for::<Response>();
+
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "Subsonic API",
+            "version": version.to_string(),
+        },
+        "paths": paths,
+        "components": {
+            "schemas": generator.into_definitions(),
+        },
+    })
+}
+
+type Subschema = fn(&mut SchemaGenerator) -> Value;
+
+struct Operation {
+    path: &'static str,
+    since: Version,
+    parameters: fn(&mut SchemaGenerator) -> Vec<Value>,
+    content: Content,
+}
+
+enum Content {
+    /// A `subsonic-response` with one of the given bodies.
+    Body(&'static [(&'static str, Subschema)]),
+    /// Media of the given content type, or a failed `subsonic-response`.
+    Media(&'static str),
+}
+
+impl Content {
+    fn response(&self, generator: &mut SchemaGenerator) -> Value {
+        match self {
+            Content::Body(bodies) => {
+                let bodies = bodies
+                    .iter()
+                    .map(|(key, schema)| (key.to_string(), schema(generator)))
+                    .collect();
+                json!({
+                    "description": "A subsonic-response, failed responses contain an error.",
+                    "content": { "application/json": { "schema": envelope(generator, bodies) } },
+                })
+            }
+            Content::Media(content_type) => {
+                let error = envelope(generator, Map::new());
+                json!({
+                    "description": "The requested media, or a failed subsonic-response.",
+                    "content": {
+                        *content_type: { "schema": { "type": "string", "format": "binary" } },
+                        "application/json": { "schema": error },
+                    },
+                })
+            }
+        }
+    }
+}
+
+/// The schema of `{"subsonic-response": {...}}` with the given optional bodies and the error.
+fn envelope(generator: &mut SchemaGenerator, mut bodies: Map<String, Value>) -> Value {
+    let mut object = ObjectSchema::default();
+    for key in ["status", "version"] {
+        let field = Response::find_field(key).expect("missing response field");
+        match key {
+            "status" => object.property::<ResponseStatus>(generator, field),
+            _ => object.property::<Version>(generator, field),
+        }
+    }
+    let mut response = object.into_schema();
+    bodies.insert("error".to_string(), generator.subschema_for::<Error>());
+    if let Some(Value::Object(properties)) = response.get_mut("properties") {
+        properties.extend(bodies);
+    }
+    json!({
+        "type": "object",
+        "properties": { "subsonic-response": response },
+        "required": ["subsonic-response"],
+    })
+}
+
+fn parameters<R: SubsonicRequest + QueryParameters>(generator: &mut SchemaGenerator) -> Vec<Value> {
+    let mut parameters = Vec::new();
+    Request::<R>::query_parameters(generator, &mut parameters);
+    parameters
+}
+
+macro_rules! body {
+    ($($key:literal => $ty:ty),* $(,)?) => {
+        Content::Body(&[$(($key, SchemaGenerator::subschema_for::<$ty> as Subschema)),*])
+    };
+}
+
+macro_rules! operations {
+    ($($request:ty => $content:expr),* $(,)?) => {
+        const OPERATIONS: &[Operation] = &[$(
+            Operation {
+                path: <$request as SubsonicRequest>::PATH,
+                since: <$request as SubsonicRequest>::SINCE,
+                parameters: parameters::<$request>,
+                content: $content,
+            }
+        ),*];
+    };
+}
+
+operations!(
+    system::Ping => body!(),
+    system::GetLicense => body!("license" => License),
+    browsing::GetMusicFolders => body!("musicFolders" => MusicFolders),
+    browsing::GetIndexes => body!("indexes" => Indexes),
+    browsing::GetMusicDirectory => body!("directory" => Directory),
+    browsing::GetGenres => body!("genres" => Genres),
+    browsing::GetArtists => body!("artists" => ArtistsID3),
+    browsing::GetArtist => body!("artist" => ArtistWithAlbumsID3),
+    browsing::GetAlbum => body!("album" => AlbumWithSongsID3),
+    browsing::GetSong => body!("song" => Child),
+    browsing::GetVideos => body!("videos" => Videos),
+    browsing::GetVideoInfo => body!("videoInfo" => VideoInfo),
+    browsing::GetArtistInfo => body!("artistInfo" => ArtistInfo),
+    browsing::GetArtistInfo2 => body!("artistInfo2" => ArtistInfo2),
+    browsing::GetAlbumInfo => body!("albumInfo" => AlbumInfo),
+    browsing::GetAlbumInfo2 => body!("albumInfo" => AlbumInfo),
+    browsing::GetSimilarSongs => body!("similarSongs" => SimilarSongs),
+    browsing::GetSimilarSongs2 => body!("similarSongs2" => SimilarSongs2),
+    browsing::GetTopSongs => body!("topSongs" => TopSongs),
+    lists::GetAlbumList => body!("albumList" => AlbumList),
+    lists::GetAlbumList2 => body!("albumList2" => AlbumList2),
+    lists::GetRandomSongs => body!("randomSongs" => Songs),
+    lists::GetSongsByGenre => body!("songsByGenre" => Songs),
+    lists::GetNowPlaying => body!("nowPlaying" => NowPlaying),
+    lists::GetStarred => body!("starred" => Starred),
+    lists::GetStarred2 => body!("starred2" => Starred2),
+    search::Search => body!("searchResult" => SearchResult),
+    search::Search2 => body!("searchResult2" => SearchResult2),
+    search::Search3 => body!("searchResult3" => SearchResult3),
+    playlists::GetPlaylists => body!("playlists" => Playlists),
+    playlists::GetPlaylist => body!("playlist" => PlaylistWithSongs),
+    playlists::CreatePlaylist => body!("playlist" => PlaylistWithSongs),
+    playlists::UpdatePlaylist => body!(),
+    playlists::DeletePlaylist => body!(),
+    retrieval::Stream => Content::Media("audio/*"),
+    retrieval::Download => Content::Media("application/octet-stream"),
+    retrieval::Hls => Content::Media("application/vnd.apple.mpegurl"),
+    retrieval::GetCaptions => Content::Media("text/vtt"),
+    retrieval::GetCoverArt => Content::Media("image/*"),
+    retrieval::GetLyrics => body!("lyrics" => Lyrics),
+    retrieval::GetAvatar => Content::Media("image/*"),
+    annotation::Star => body!(),
+    annotation::Unstar => body!(),
+    annotation::SetRating => body!(),
+    annotation::Scrobble => body!(),
+    sharing::GetShares => body!("shares" => Shares),
+    sharing::CreateShare => body!("shares" => Shares),
+    sharing::UpdateShare => body!(),
+    sharing::DeleteShare => body!(),
+    podcast::GetPodcasts => body!("podcasts" => Podcasts),
+    podcast::GetNewestPodcasts => body!("newestPodcasts" => NewestPodcasts),
+    podcast::RefreshPodcasts => body!(),
+    podcast::CreatePodcastChannel => body!(),
+    podcast::DeletePodcastChannel => body!(),
+    podcast::DeletePodcastEpisode => body!(),
+    podcast::DownloadPodcastEpisode => body!(),
+    jukebox::JukeboxControl => body!(
+        "jukeboxStatus" => JukeboxStatus,
+        "jukeboxPlaylist" => JukeboxPlaylist,
+    ),
+    radio::GetInternetRadioStations => body!("internetRadioStations" => InternetRadioStations),
+    radio::CreateInternetRadioStation => body!(),
+    radio::UpdateInternetRadioStation => body!(),
+    radio::DeleteInternetRadioStation => body!(),
+    chat::GetChatMessages => body!("chatMessages" => ChatMessages),
+    chat::AddChatMessage => body!(),
+    user::GetUser => body!("user" => User),
+    user::GetUsers => body!("users" => Users),
+    user::CreateUser => body!(),
+    user::UpdateUser => body!(),
+    user::DeleteUser => body!(),
+    user::ChangePassword => body!(),
+    bookmark::GetBookmarks => body!("bookmarks" => Bookmarks),
+    bookmark::CreateBookmark => body!(),
+    bookmark::DeleteBookmark => body!(),
+    bookmark::GetPlayQueue => body!("playQueue" => PlayQueue),
+    bookmark::SavePlayQueue => body!(),
+    scan::GetScanStatus => body!("scanStatus" => ScanStatus),
+    scan::StartScan => body!("scanStatus" => ScanStatus),
+);
+
+impl QueryParameters for Authentication {
+    fn query_parameters(generator: &mut SchemaGenerator, parameters: &mut Vec<Value>) {
+        // either the password or the token and salt are required
+        query_parameter::<Option<String>>(generator, "p", parameters);
+        query_parameter::<Option<String>>(generator, "t", parameters);
+        query_parameter::<Option<String>>(generator, "s", parameters);
+    }
+}
+
+impl JsonSchema for ResponseBody {
+    /// Every body available in the generator's version, at most one is present in a response.
+    fn json_schema(generator: &mut SchemaGenerator) -> Value {
+        let version = generator.version();
+        let mut properties = Map::new();
+        for operation in OPERATIONS.iter().filter(|o| o.since <= version) {
+            if let Content::Body(bodies) = operation.content {
+                for (key, schema) in bodies {
+                    if !properties.contains_key(*key) {
+                        properties.insert(key.to_string(), schema(generator));
+                    }
+                }
+            }
+        }
+        properties.insert("error".to_string(), generator.subschema_for::<Error>());
+        json!({ "type": "object", "properties": properties })
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for Option<T> {
+    fn is_optional() -> bool {
+        true
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Value {
+        generator.subschema_for::<T>()
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for Vec<T> {
+    fn is_optional() -> bool {
+        true
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Value {
+        json!({ "type": "array", "items": generator.subschema_for::<T>() })
+    }
+}
+
+macro_rules! impl_json_schema {
+    ($($t:ty => $schema:tt),* $(,)?) => {
+        $(
+            impl JsonSchema for $t {
+                fn json_schema(_: &mut SchemaGenerator) -> Value {
+                    json!($schema)
+                }
+            }
+        )*
+    };
+}
+
+impl_json_schema!(
+    bool => { "type": "boolean" },
+    u32 => { "type": "integer", "format": "int32", "minimum": 0 },
+    u64 => { "type": "integer", "format": "int64", "minimum": 0 },
+    f32 => { "type": "number", "format": "float" },
+    String => { "type": "string" },
+    Id => { "type": "string" },
+    SongId => { "type": "string" },
+    DirectoryId => { "type": "string" },
+    AlbumId => { "type": "string" },
+    ArtistId => { "type": "string" },
+    PlaylistId => { "type": "string" },
+    CoverArtId => { "type": "string" },
+    PodcastChannelId => { "type": "string" },
+    PodcastEpisodeId => { "type": "string" },
+    ShareId => { "type": "string" },
+    InternetRadioStationId => { "type": "string" },
+    MusicFolderId => { "type": "integer", "format": "int32", "minimum": 0 },
+    // the timezone is usually omitted, which `date-time` does not allow
+    DateTime => { "type": "string", "pattern": r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}" },
+    Milliseconds => { "type": "integer", "format": "int64", "minimum": 0 },
+    Seconds => { "type": "integer", "format": "int64", "minimum": 0 },
+    UserRating => { "type": "integer", "minimum": 1, "maximum": 5 },
+    AverageRating => { "type": "number", "minimum": 1.0, "maximum": 5.0 },
+    AudioBitrate => { "type": "integer", "minimum": 0 },
+    VideoSize => { "type": "string", "pattern": "^\\d+x\\d+$" },
+    VideoBitrate => { "type": "string", "pattern": "^\\d+(@\\d+x\\d+)?$" },
+    ErrorCode => { "type": "integer" },
+);
+
+impl JsonSchema for Version {
+    fn schema_name() -> Option<String> {
+        Some("Version".to_string())
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Value {
+        json!({ "type": "string", "pattern": "^\\d+\\.\\d+\\.\\d+$" })
+    }
+}
+
+/// Implements `JsonSchema` for an enum serialized as a string, `$value` converts a variant
+/// to its serialized form.
+macro_rules! impl_json_schema_for_enum {
+    ($($t:ident($value:expr) => [$($variant:expr),* $(,)?]),* $(,)?) => {
+        $(
+            impl JsonSchema for $t {
+                fn schema_name() -> Option<String> {
+                    Some(stringify!($t).to_string())
+                }
+
+                fn json_schema(_: &mut SchemaGenerator) -> Value {
+                    let values = [$($variant),*].iter().map($value).collect::<Vec<Value>>();
+                    json!({ "type": "string", "enum": values })
+                }
+            }
+        )*
+    };
+}
+
+fn serialized<T: serde::Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).expect("failed to serialize enum")
+}
+
+impl_json_schema_for_enum!(
+    ResponseStatus(serialized) => [ResponseStatus::Ok, ResponseStatus::Failed],
+    MediaType(serialized) => [MediaType::Music, MediaType::Podcast, MediaType::AudioBook, MediaType::Video],
+    PodcastStatus(serialized) => [
+        PodcastStatus::New,
+        PodcastStatus::Downloading,
+        PodcastStatus::Completed,
+        PodcastStatus::Deleted,
+        PodcastStatus::Skipped,
+        PodcastStatus::Error,
+    ],
+    ListType(|t: &ListType| Value::from(t.as_str())) => [
+        ListType::Random,
+        ListType::Newest,
+        ListType::Highest,
+        ListType::Frequent,
+        ListType::Recent,
+        ListType::AlphabeticalByName,
+        ListType::AlphabeticalByArtist,
+        ListType::Starred,
+        ListType::ByYear,
+        ListType::ByGenre,
+    ],
+    JukeboxAction(|a: &JukeboxAction| Value::from(a.as_str())) => [
+        JukeboxAction::Get,
+        JukeboxAction::Status,
+        JukeboxAction::Set,
+        JukeboxAction::Start,
+        JukeboxAction::Stop,
+        JukeboxAction::Skip,
+        JukeboxAction::Add,
+        JukeboxAction::Clear,
+        JukeboxAction::Remove,
+        JukeboxAction::Shuffle,
+        JukeboxAction::SetGain,
+    ],
+);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        response::AlbumID3,
+        test_support::{sample_bodies, Sample},
+    };
+
+    use super::*;
+
+    fn compile(schema: &Value) -> jsonschema::JSONSchema {
+        jsonschema::JSONSchema::options()
+            .with_draft(jsonschema::Draft::Draft202012)
+            .compile(schema)
+            .unwrap()
+    }
+
+    fn assert_valid(validator: &jsonschema::JSONSchema, instance: &Value) {
+        if let Err(errors) = validator.validate(instance) {
+            let errors = errors.map(|e| e.to_string()).collect::<Vec<_>>();
+            panic!("{}\n{}", instance, errors.join("\n"));
+        }
+    }
+
+    /// Checks that every key of `instance` is a property of `schema`.
+    fn assert_declared(instance: &Value, schema: &Value, definitions: &Map<String, Value>) {
+        if let Some(Value::String(reference)) = schema.get("$ref") {
+            let name = reference.rsplit('/').next().unwrap();
+            return assert_declared(instance, &definitions[name], definitions);
+        }
+        match instance {
+            Value::Object(object) => {
+                for (key, value) in object {
+                    let property = schema["properties"]
+                        .get(key)
+                        .unwrap_or_else(|| panic!("undeclared property `{}` in {}", key, schema));
+                    assert_declared(value, property, definitions);
+                }
+            }
+            Value::Array(items) => items
+                .iter()
+                .for_each(|item| assert_declared(item, &schema["items"], definitions)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn response_schema() {
+        for &version in Version::ALL {
+            let schema = schema_for::<Response>(version);
+            let validator = compile(&schema);
+            let definitions = schema["$defs"].as_object().unwrap();
+            for body in sample_bodies().into_iter().filter(|b| b.since() <= version) {
+                let response = Response::ok(version, body);
+                let json = response.to_json_versioned(version).unwrap();
+                let json = serde_json::from_str::<Value>(&json).unwrap();
+                let instance = &json["subsonic-response"];
+                assert_valid(&validator, instance);
+                assert_declared(instance, &schema, definitions);
+            }
+        }
+    }
+
+    #[test]
+    fn schema_versions() {
+        let schema = schema_for::<AlbumID3>(Version::V1_8_0);
+        assert_eq!("AlbumID3", schema["title"]);
+        assert_eq!(
+            json!(["id", "name", "songCount", "duration"]),
+            schema["required"]
+        );
+        assert!(schema["properties"].get("playCount").is_none());
+        assert!(schema["properties"].get("year").is_none());
+
+        let schema = schema_for::<AlbumID3>(Version::V1_14_0);
+        assert!(schema["properties"].get("playCount").is_some());
+        assert_eq!(
+            json!({ "type": "integer", "format": "int32", "minimum": 0 }),
+            schema["properties"]["year"]
+        );
+
+        // flattened fields are merged and renamed fields use their key
+        let schema = schema_for::<Child>(Version::LATEST);
+        assert_eq!(
+            json!({ "$ref": "#/$defs/MediaType" }),
+            schema["properties"]["type"]
+        );
+        assert_eq!(
+            json!(["music", "podcast", "audiobook", "video"]),
+            schema["$defs"]["MediaType"]["enum"]
+        );
+        let schema = schema_for::<crate::response::PodcastEpisode>(Version::LATEST);
+        assert!(schema["properties"].get("isDir").is_some());
+        assert!(schema["properties"].get("channelId").is_some());
+
+        // enumerations allow the values of the XSD, in any order
+        let xsd = crate::xsd::Schema::subsonic();
+        for ty in ["MediaType", "PodcastStatus"] {
+            let mut values = schema["$defs"][ty]["enum"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|v| v.as_str().unwrap())
+                .collect::<Vec<_>>();
+            let mut expected = xsd.enumeration(ty).to_vec();
+            values.sort_unstable();
+            expected.sort_unstable();
+            assert_eq!(expected, values, "{}", ty);
+        }
+    }
+
+    #[test]
+    fn operations() {
+        // one operation for each service method
+        let mut paths = OPERATIONS.iter().map(|o| o.path).collect::<Vec<_>>();
+        let mut endpoints = crate::capabilities::ENDPOINTS
+            .iter()
+            .map(|e| e.path)
+            .collect::<Vec<_>>();
+        paths.sort_unstable();
+        endpoints.sort_unstable();
+        assert_eq!(endpoints, paths);
+
+        // a body can be returned since the first operation returning it
+        for body in sample_bodies() {
+            if matches!(body, ResponseBody::Empty | ResponseBody::Error(_)) {
+                continue;
+            }
+            let since = body.since();
+            let json = Response::ok(Version::LATEST, body).to_json().unwrap();
+            let json = serde_json::from_str::<Value>(&json).unwrap();
+            let key = json["subsonic-response"]
+                .as_object()
+                .unwrap()
+                .keys()
+                .find(|k| !matches!(k.as_str(), "status" | "version"))
+                .unwrap()
+                .clone();
+            let first = OPERATIONS
+                .iter()
+                .filter(|o| matches!(o.content, Content::Body(bodies) if bodies.iter().any(|(k, _)| *k == key)))
+                .map(|o| o.since)
+                .min();
+            assert_eq!(Some(since), first, "{}", key);
+        }
+    }
+
+    #[test]
+    fn openapi_document() {
+        let document = document(Version::V1_4_0);
+        let paths = document["paths"].as_object().unwrap();
+        assert!(paths.contains_key("/rest/search2"));
+        assert!(!paths.contains_key("/rest/search3"));
+
+        let document = super::document(Version::LATEST);
+        let paths = document["paths"].as_object().unwrap();
+        assert_eq!(crate::capabilities::ENDPOINTS.len(), paths.len());
+
+        let search3 = &paths["/rest/search3"]["get"];
+        let parameters = search3["parameters"].as_array().unwrap();
+        let parameter = |name: &str| {
+            parameters
+                .iter()
+                .find(|p| p["name"] == name)
+                .unwrap_or_else(|| panic!("missing parameter {}", name))
+        };
+        for name in ["u", "p", "t", "s", "v", "c", "f", "query", "songCount"] {
+            parameter(name);
+        }
+        assert_eq!(json!(true), parameter("query")["required"]);
+        assert_eq!(json!(false), parameter("songCount")["required"]);
+        let parameters = paths["/rest/getAlbumList2"]["get"]["parameters"]
+            .as_array()
+            .unwrap();
+        let list_type = parameters.iter().find(|p| p["name"] == "type").unwrap();
+        assert_eq!(
+            json!({ "$ref": "#/components/schemas/ListType" }),
+            list_type["schema"]
+        );
+        assert_eq!(
+            json!({ "type": "string", "format": "binary" }),
+            paths["/rest/stream"]["get"]["responses"]["200"]["content"]["audio/*"]["schema"]
+        );
+
+        // the responses validate against the schema of their operation
+        let components = document["components"].clone();
+        for (path, body) in [
+            (
+                "/rest/search3",
+                ResponseBody::SearchResult3(Sample::sample()),
+            ),
+            (
+                "/rest/getAlbumList2",
+                ResponseBody::AlbumList2(Sample::sample()),
+            ),
+            ("/rest/ping", ResponseBody::Empty),
+        ] {
+            let mut schema = paths[path]["get"]["responses"]["200"]["content"]["application/json"]
+                ["schema"]
+                .clone();
+            schema["components"] = components.clone();
+            let validator = compile(&schema);
+            let response = Response::ok(Version::LATEST, body);
+            let json = serde_json::from_str(&response.to_json().unwrap()).unwrap();
+            assert_valid(&validator, &json);
+            let error = Response::failed(Version::LATEST, Error::new(ErrorCode::Generic));
+            let json = serde_json::from_str(&error.to_json().unwrap()).unwrap();
+            assert_valid(&validator, &json);
+        }
+    }
+}
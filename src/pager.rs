@@ -0,0 +1,681 @@
+//! Pagination for endpoints that use an offset and a size.
+//!
+//! # Example
+//! Iterating over every album of a server using [`GetAlbumList2`], the fetch function
+//! would usually send the request to a server.
+//! ```rust
+//! use subsonic_types::{
+//!     common::Version,
+//!     pager::Pager,
+//!     request::lists::{GetAlbumList2, ListType},
+//!     response::{AlbumID3, AlbumList2, Response, ResponseBody},
+//! };
+//!
+//! let albums = (0..25)
+//!     .map(|i| AlbumID3 {
+//!         id: i.to_string().into(),
+//!         name: format!("Album {}", i),
+//!         ..Default::default()
+//!     })
+//!     .collect::<Vec<_>>();
+//!
+//! let request = GetAlbumList2::builder(ListType::AlphabeticalByName)
+//!     .size(10)
+//!     .build();
+//! let pager = Pager::new(request, |request: GetAlbumList2| {
+//!     let offset = request.offset.unwrap_or(0) as usize;
+//!     let size = request.size.unwrap_or(10) as usize;
+//!     let album = albums.iter().skip(offset).take(size).cloned().collect();
+//!     Ok::<_, std::convert::Infallible>(Response::ok(
+//!         Version::LATEST,
+//!         ResponseBody::AlbumList2(AlbumList2 { album }),
+//!     ))
+//! });
+//! let fetched = pager.collect::<Result<Vec<_>, _>>().unwrap();
+//! assert_eq!(albums, fetched);
+//! ```
+
+use std::{
+    collections::VecDeque,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{
+    request::{
+        lists::{GetAlbumList, GetAlbumList2, GetSongsByGenre},
+        search::{Search, Search2, Search3},
+        SubsonicRequest,
+    },
+    response::{AlbumID3, Artist, ArtistID3, Child, Response, ResponseBody},
+};
+
+/// A request that can be paginated using an offset and a size.
+pub trait Paginate {
+    /// The request sent to the server.
+    type Request: SubsonicRequest;
+    /// The items of each page.
+    type Item;
+
+    /// The size used by the server when the request does not specify one.
+    const DEFAULT_SIZE: u32;
+
+    /// The offset of the first page.
+    fn offset(&self) -> u32;
+
+    /// The size of each page, if specified.
+    fn size(&self) -> Option<u32>;
+
+    /// Builds the request for the page starting at `offset`.
+    fn page(&self, offset: u32) -> Self::Request;
+
+    /// Extracts the items from a response body.
+    /// Returns `None` if the body does not belong to this request.
+    fn items(body: ResponseBody) -> Option<Vec<Self::Item>>;
+}
+
+/// Error returned by [`Pager`] and [`AsyncPager`].
+#[derive(Debug)]
+pub enum PagerError<E> {
+    /// The fetch function failed.
+    Fetch(E),
+    /// The server responded with an error.
+    Response(crate::response::Error),
+    /// The server responded with a body that does not belong to the request.
+    UnexpectedBody,
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for PagerError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PagerError::Fetch(err) => write!(f, "failed to fetch page: {}", err),
+            PagerError::Response(err) => write!(f, "server error: {}", err),
+            PagerError::UnexpectedBody => write!(f, "unexpected response body"),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for PagerError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PagerError::Fetch(err) => Some(err),
+            PagerError::Response(err) => Some(err),
+            PagerError::UnexpectedBody => None,
+        }
+    }
+}
+
+/// State shared by [`Pager`] and [`AsyncPager`].
+struct PagerState<P: Paginate> {
+    paginate: P,
+    offset: u32,
+    items: VecDeque<P::Item>,
+    /// Length of the longest page received so far.
+    page_len: usize,
+    done: bool,
+}
+
+impl<P: Paginate> PagerState<P> {
+    fn new(paginate: P) -> Self {
+        Self {
+            offset: paginate.offset(),
+            paginate,
+            items: VecDeque::new(),
+            page_len: 0,
+            done: false,
+        }
+    }
+
+    /// The request for the next page, if another page is needed.
+    fn next_request(&self) -> Option<P::Request> {
+        if self.done || !self.items.is_empty() {
+            None
+        } else {
+            Some(self.paginate.page(self.offset))
+        }
+    }
+
+    fn push<E>(&mut self, response: Result<Response, E>) -> Result<(), PagerError<E>> {
+        let body = match response {
+            Ok(response) => response.body,
+            Err(err) => {
+                self.done = true;
+                return Err(PagerError::Fetch(err));
+            }
+        };
+        let items = match body {
+            ResponseBody::Error(err) => Err(PagerError::Response(err)),
+            body => P::items(body).ok_or(PagerError::UnexpectedBody),
+        };
+        let items = match items {
+            Ok(items) => items,
+            Err(err) => {
+                self.done = true;
+                return Err(err);
+            }
+        };
+        // servers may cap the page size below the requested one, so only a
+        // page shorter than a previous one marks the end
+        if items.is_empty() || items.len() < self.page_len {
+            self.done = true;
+        }
+        self.page_len = self.page_len.max(items.len());
+        self.offset = self.offset.saturating_add(items.len() as u32);
+        self.items.extend(items);
+        Ok(())
+    }
+}
+
+/// Iterator over every item of a paginated request.
+///
+/// Pages are requested using the fetch function until an empty page or a page
+/// with less items than a previous page is returned.
+/// If an error is returned the iteration stops after yielding it.
+pub struct Pager<P: Paginate, F> {
+    state: PagerState<P>,
+    fetch: F,
+}
+
+impl<P, F, E> Pager<P, F>
+where
+    P: Paginate,
+    F: FnMut(P::Request) -> Result<Response, E>,
+{
+    pub fn new(paginate: P, fetch: F) -> Self {
+        Self {
+            state: PagerState::new(paginate),
+            fetch,
+        }
+    }
+}
+
+impl<P, F, E> Iterator for Pager<P, F>
+where
+    P: Paginate,
+    F: FnMut(P::Request) -> Result<Response, E>,
+{
+    type Item = Result<P::Item, PagerError<E>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(request) = self.state.next_request() {
+            if let Err(err) = self.state.push((self.fetch)(request)) {
+                return Some(Err(err));
+            }
+        }
+        self.state.items.pop_front().map(Ok)
+    }
+}
+
+/// Asynchronous version of [`Pager`].
+///
+/// Items can be retrieved using [`AsyncPager::next`] or by polling the pager as a [`futures_core::Stream`].
+pub struct AsyncPager<P: Paginate, F, Fut> {
+    state: PagerState<P>,
+    fetch: F,
+    pending: Option<Pin<Box<Fut>>>,
+}
+
+impl<P, F, Fut, E> AsyncPager<P, F, Fut>
+where
+    P: Paginate,
+    F: FnMut(P::Request) -> Fut,
+    Fut: Future<Output = Result<Response, E>>,
+{
+    pub fn new(paginate: P, fetch: F) -> Self {
+        Self {
+            state: PagerState::new(paginate),
+            fetch,
+            pending: None,
+        }
+    }
+
+    /// Returns the next item, fetching a new page if needed.
+    pub async fn next(&mut self) -> Option<Result<P::Item, PagerError<E>>> {
+        std::future::poll_fn(|cx| self.poll_item(cx)).await
+    }
+
+    fn poll_item(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<P::Item, PagerError<E>>>> {
+        if self.pending.is_none() {
+            if let Some(request) = self.state.next_request() {
+                self.pending = Some(Box::pin((self.fetch)(request)));
+            }
+        }
+        if let Some(pending) = self.pending.as_mut() {
+            let response = match pending.as_mut().poll(cx) {
+                Poll::Ready(response) => response,
+                Poll::Pending => return Poll::Pending,
+            };
+            self.pending = None;
+            if let Err(err) = self.state.push(response) {
+                return Poll::Ready(Some(Err(err)));
+            }
+        }
+        Poll::Ready(self.state.items.pop_front().map(Ok))
+    }
+}
+
+impl<P, F, Fut, E> futures_core::Stream for AsyncPager<P, F, Fut>
+where
+    P: Paginate + Unpin,
+    P::Item: Unpin,
+    F: FnMut(P::Request) -> Fut + Unpin,
+    Fut: Future<Output = Result<Response, E>>,
+{
+    type Item = Result<P::Item, PagerError<E>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_item(cx)
+    }
+}
+
+impl Paginate for GetAlbumList {
+    type Request = Self;
+    type Item = Child;
+
+    const DEFAULT_SIZE: u32 = 10;
+
+    fn offset(&self) -> u32 {
+        self.offset.unwrap_or(0)
+    }
+
+    fn size(&self) -> Option<u32> {
+        self.size
+    }
+
+    fn page(&self, offset: u32) -> Self::Request {
+        Self {
+            offset: Some(offset),
+            ..self.clone()
+        }
+    }
+
+    fn items(body: ResponseBody) -> Option<Vec<Self::Item>> {
+        match body {
+            ResponseBody::AlbumList(list) => Some(list.album),
+            _ => None,
+        }
+    }
+}
+
+impl Paginate for GetAlbumList2 {
+    type Request = Self;
+    type Item = AlbumID3;
+
+    const DEFAULT_SIZE: u32 = 10;
+
+    fn offset(&self) -> u32 {
+        self.offset.unwrap_or(0)
+    }
+
+    fn size(&self) -> Option<u32> {
+        self.size
+    }
+
+    fn page(&self, offset: u32) -> Self::Request {
+        Self {
+            offset: Some(offset),
+            ..self.clone()
+        }
+    }
+
+    fn items(body: ResponseBody) -> Option<Vec<Self::Item>> {
+        match body {
+            ResponseBody::AlbumList2(list) => Some(list.album),
+            _ => None,
+        }
+    }
+}
+
+impl Paginate for GetSongsByGenre {
+    type Request = Self;
+    type Item = Child;
+
+    const DEFAULT_SIZE: u32 = 10;
+
+    fn offset(&self) -> u32 {
+        self.offset.unwrap_or(0)
+    }
+
+    fn size(&self) -> Option<u32> {
+        self.count
+    }
+
+    fn page(&self, offset: u32) -> Self::Request {
+        Self {
+            offset: Some(offset),
+            ..self.clone()
+        }
+    }
+
+    fn items(body: ResponseBody) -> Option<Vec<Self::Item>> {
+        match body {
+            ResponseBody::SongsByGenre(songs) => Some(songs.song),
+            _ => None,
+        }
+    }
+}
+
+impl Paginate for Search {
+    type Request = Self;
+    type Item = Child;
+
+    const DEFAULT_SIZE: u32 = 20;
+
+    fn offset(&self) -> u32 {
+        self.offset.unwrap_or(0)
+    }
+
+    fn size(&self) -> Option<u32> {
+        self.count
+    }
+
+    fn page(&self, offset: u32) -> Self::Request {
+        Self {
+            offset: Some(offset),
+            ..self.clone()
+        }
+    }
+
+    fn items(body: ResponseBody) -> Option<Vec<Self::Item>> {
+        match body {
+            ResponseBody::SearchResult(result) => Some(result.matches),
+            _ => None,
+        }
+    }
+}
+
+/// Paginates the artists of a [`Search2`] or [`Search3`] request.
+/// The album and song counts are set to 0 so only artists are fetched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchArtists<R>(pub R);
+
+/// Paginates the albums of a [`Search2`] or [`Search3`] request.
+/// The artist and song counts are set to 0 so only albums are fetched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchAlbums<R>(pub R);
+
+/// Paginates the songs of a [`Search2`] or [`Search3`] request.
+/// The artist and album counts are set to 0 so only songs are fetched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchSongs<R>(pub R);
+
+macro_rules! impl_paginate_for_search {
+    ($request:ident, $body:ident, $artist:ty, $album:ty) => {
+        impl Paginate for SearchArtists<$request> {
+            type Request = $request;
+            type Item = $artist;
+
+            const DEFAULT_SIZE: u32 = 20;
+
+            fn offset(&self) -> u32 {
+                self.0.artist_offset.unwrap_or(0)
+            }
+
+            fn size(&self) -> Option<u32> {
+                self.0.artist_count
+            }
+
+            fn page(&self, offset: u32) -> Self::Request {
+                $request {
+                    artist_offset: Some(offset),
+                    album_count: Some(0),
+                    album_offset: None,
+                    song_count: Some(0),
+                    song_offset: None,
+                    ..self.0.clone()
+                }
+            }
+
+            fn items(body: ResponseBody) -> Option<Vec<Self::Item>> {
+                match body {
+                    ResponseBody::$body(result) => Some(result.artist),
+                    _ => None,
+                }
+            }
+        }
+
+        impl Paginate for SearchAlbums<$request> {
+            type Request = $request;
+            type Item = $album;
+
+            const DEFAULT_SIZE: u32 = 20;
+
+            fn offset(&self) -> u32 {
+                self.0.album_offset.unwrap_or(0)
+            }
+
+            fn size(&self) -> Option<u32> {
+                self.0.album_count
+            }
+
+            fn page(&self, offset: u32) -> Self::Request {
+                $request {
+                    album_offset: Some(offset),
+                    artist_count: Some(0),
+                    artist_offset: None,
+                    song_count: Some(0),
+                    song_offset: None,
+                    ..self.0.clone()
+                }
+            }
+
+            fn items(body: ResponseBody) -> Option<Vec<Self::Item>> {
+                match body {
+                    ResponseBody::$body(result) => Some(result.album),
+                    _ => None,
+                }
+            }
+        }
+
+        impl Paginate for SearchSongs<$request> {
+            type Request = $request;
+            type Item = Child;
+
+            const DEFAULT_SIZE: u32 = 20;
+
+            fn offset(&self) -> u32 {
+                self.0.song_offset.unwrap_or(0)
+            }
+
+            fn size(&self) -> Option<u32> {
+                self.0.song_count
+            }
+
+            fn page(&self, offset: u32) -> Self::Request {
+                $request {
+                    song_offset: Some(offset),
+                    artist_count: Some(0),
+                    artist_offset: None,
+                    album_count: Some(0),
+                    album_offset: None,
+                    ..self.0.clone()
+                }
+            }
+
+            fn items(body: ResponseBody) -> Option<Vec<Self::Item>> {
+                match body {
+                    ResponseBody::$body(result) => Some(result.song),
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+impl_paginate_for_search!(Search2, SearchResult2, Artist, Child);
+impl_paginate_for_search!(Search3, SearchResult3, ArtistID3, AlbumID3);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::Version,
+        request::lists::ListType,
+        response::{Error, ErrorCode, SearchResult3, Songs},
+    };
+
+    use super::*;
+
+    fn song(i: usize) -> Child {
+        Child {
+            id: i.to_string().into(),
+            title: format!("Song {}", i),
+            ..Default::default()
+        }
+    }
+
+    fn songs_by_genre(count: Option<u32>) -> GetSongsByGenre {
+        GetSongsByGenre {
+            genre: "Rock".into(),
+            count,
+            offset: None,
+            music_folder_id: None,
+        }
+    }
+
+    #[test]
+    fn test_pager() {
+        let songs = (0..25).map(song).collect::<Vec<_>>();
+        let mut requests = Vec::new();
+        let pager = Pager::new(songs_by_genre(None), |request: GetSongsByGenre| {
+            requests.push(request.offset);
+            let offset = request.offset.unwrap_or(0) as usize;
+            let count = request.count.unwrap_or(10) as usize;
+            let song = songs.iter().skip(offset).take(count).cloned().collect();
+            Ok::<_, ()>(Response::ok(
+                Version::LATEST,
+                ResponseBody::SongsByGenre(Songs { song }),
+            ))
+        });
+        let fetched = pager.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(songs, fetched);
+        assert_eq!(requests, vec![Some(0), Some(10), Some(20)]);
+
+        // an exact multiple of the page size requires an extra empty page
+        let mut pages = 0;
+        let pager = Pager::new(songs_by_genre(Some(5)), |request: GetSongsByGenre| {
+            pages += 1;
+            let offset = request.offset.unwrap_or(0) as usize;
+            let song = songs.iter().skip(offset).take(5).cloned().collect();
+            Ok::<_, ()>(Response::ok(
+                Version::LATEST,
+                ResponseBody::SongsByGenre(Songs { song }),
+            ))
+        });
+        assert_eq!(pager.count(), 25);
+        assert_eq!(pages, 6);
+    }
+
+    #[test]
+    fn test_pager_capped_size() {
+        // the server returns at most 3 songs whatever count is requested
+        let songs = (0..8).map(song).collect::<Vec<_>>();
+        let mut requests = Vec::new();
+        let pager = Pager::new(songs_by_genre(Some(10)), |request: GetSongsByGenre| {
+            requests.push(request.offset);
+            let offset = request.offset.unwrap_or(0) as usize;
+            let song = songs.iter().skip(offset).take(3).cloned().collect();
+            Ok::<_, ()>(Response::ok(
+                Version::LATEST,
+                ResponseBody::SongsByGenre(Songs { song }),
+            ))
+        });
+        let fetched = pager.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(songs, fetched);
+        assert_eq!(requests, vec![Some(0), Some(3), Some(6)]);
+    }
+
+    #[test]
+    fn test_pager_error() {
+        let mut pager = Pager::new(songs_by_genre(None), |_| {
+            Ok::<_, ()>(Response::failed(
+                Version::LATEST,
+                Error::new(ErrorCode::Generic),
+            ))
+        });
+        assert!(matches!(pager.next(), Some(Err(PagerError::Response(_)))));
+        assert!(pager.next().is_none());
+
+        let mut pager = Pager::new(songs_by_genre(None), |_| {
+            Ok::<_, ()>(Response::ok(Version::LATEST, ResponseBody::Empty))
+        });
+        assert!(matches!(
+            pager.next(),
+            Some(Err(PagerError::UnexpectedBody))
+        ));
+        assert!(pager.next().is_none());
+
+        let mut pager = Pager::new(songs_by_genre(None), |_| Err("offline"));
+        assert!(matches!(
+            pager.next(),
+            Some(Err(PagerError::Fetch("offline")))
+        ));
+        assert!(pager.next().is_none());
+    }
+
+    #[test]
+    fn test_search_pager() {
+        let songs = (0..7).map(song).collect::<Vec<_>>();
+        let request = Search3 {
+            query: "".into(),
+            artist_count: Some(3),
+            artist_offset: None,
+            album_count: None,
+            album_offset: None,
+            song_count: Some(3),
+            song_offset: Some(1),
+            music_folder_id: None,
+        };
+        let pager = Pager::new(SearchSongs(request), |request: Search3| {
+            assert_eq!(request.artist_count, Some(0));
+            assert_eq!(request.album_count, Some(0));
+            let offset = request.song_offset.unwrap() as usize;
+            let count = request.song_count.unwrap() as usize;
+            let song = songs.iter().skip(offset).take(count).cloned().collect();
+            Ok::<_, ()>(Response::ok(
+                Version::LATEST,
+                ResponseBody::SearchResult3(SearchResult3 {
+                    song,
+                    ..Default::default()
+                }),
+            ))
+        });
+        let fetched = pager.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(&songs[1..], fetched.as_slice());
+    }
+
+    #[tokio::test]
+    async fn test_async_pager() {
+        let albums = (0..12)
+            .map(|i| AlbumID3 {
+                id: i.to_string().into(),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let request = GetAlbumList2 {
+            list_type: ListType::Newest,
+            size: Some(5),
+            offset: None,
+            from_year: None,
+            to_year: None,
+            genre: None,
+            music_folder_id: None,
+        };
+        let mut pager = AsyncPager::new(request, |request: GetAlbumList2| {
+            let offset = request.offset.unwrap_or(0) as usize;
+            let album = albums.iter().skip(offset).take(5).cloned().collect();
+            async move {
+                tokio::task::yield_now().await;
+                Ok::<_, ()>(Response::ok(
+                    Version::LATEST,
+                    ResponseBody::AlbumList2(crate::response::AlbumList2 { album }),
+                ))
+            }
+        });
+        let mut fetched = Vec::new();
+        while let Some(album) = pager.next().await {
+            fetched.push(album.unwrap());
+        }
+        assert_eq!(albums, fetched);
+    }
+}
@@ -151,14 +151,19 @@ pub struct QueryBuilderString {
 
 impl QueryBuilderString {
     /// <https://url.spec.whatwg.org/#query-percent-encode-set≥
+    /// plus the characters that would otherwise change the meaning of a key or value.
     const QUERY_PERCENT_ENCODE_SET: percent_encoding::AsciiSet = percent_encoding::CONTROLS
         .add(b' ')
         .add(b'"')
         .add(b'#')
         .add(b'<')
-        .add(b'>');
+        .add(b'>')
+        .add(b'%')
+        .add(b'&')
+        .add(b'+')
+        .add(b'=');
 
-    fn into_query(self) -> String {
+    pub(crate) fn into_query(self) -> String {
         self.query
     }
 }
@@ -251,11 +256,13 @@ mod basic {
         }
     }
 
-    pub fn parse_query(query: &str) -> impl Iterator<Item = Result<QueryPair>> + '_ {
+    pub fn parse_query(query: &str) -> impl Iterator<Item = Result<QueryPair<'_>>> + '_ {
         QueryIter::new(query)
     }
 }
 
+pub use basic::parse_query;
+
 pub fn from_query<T>(query: &str) -> Result<T>
 where
     T: FromQuery,
@@ -614,4 +621,16 @@ mod tests {
         let query = to_query(&test);
         assert_eq!(query, "field_d=3&field_e=4");
     }
+
+    #[test]
+    fn test_reserved_characters() {
+        let test = Nested {
+            field_d: 3,
+            field_e: Some("a&b=c+d%20e f#g".to_string()),
+        };
+        let query = to_query(&test);
+        assert_eq!(query, "field_d=3&field_e=a%26b%3Dc%2Bd%2520e%20f%23g");
+        let parsed: Nested = from_query(&query).unwrap();
+        assert_eq!(parsed.field_e, test.field_e);
+    }
 }
@@ -0,0 +1,516 @@
+//! Recording and replay of traffic, to test against the output of real servers offline.
+//!
+//! [`RecordingTransport`] wraps a transport and writes every exchange as a line of JSON:
+//! the path, the parameters with the credentials redacted and the raw response.
+//! [`ReplayService`] is a [`SubsonicService`] that serves those recordings, matching requests by
+//! their [canonical form](crate::cache::canonicalize). The recorded responses are parsed again on
+//! every call, so changes to the parsers are tested against the recorded bytes.
+//!
+//! # Example
+//! ```rust
+//! use subsonic_types::{
+//!     common::Version,
+//!     recording::{RecordingTransport, ReplayService},
+//!     request::{system::Ping, Authentication, Request},
+//!     response::Response,
+//!     service::{self, ServiceTransport, SubsonicService},
+//!     transport,
+//! };
+//!
+//! struct Server;
+//!
+//! #[async_trait::async_trait]
+//! impl SubsonicService for Server {
+//!     async fn ping(&self, request: Request<Ping>) -> service::Result<Response> {
+//!         Ok(Response::ok_empty(request.version))
+//!     }
+//! }
+//!
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! let request = Request {
+//!     username: "admin".into(),
+//!     authentication: Authentication::Password("secret".into()),
+//!     version: Version::LATEST,
+//!     client: "example".into(),
+//!     format: Some("json".into()),
+//!     body: Ping,
+//! };
+//!
+//! let recorder = RecordingTransport::new(ServiceTransport::new(Server), Vec::new());
+//! let recorded = transport::execute_async(&recorder, &request).await.unwrap();
+//! let (_, corpus) = recorder.into_inner();
+//! assert!(!String::from_utf8_lossy(&corpus).contains("secret"));
+//!
+//! let replay = ServiceTransport::new(ReplayService::from_reader(corpus.as_slice()).unwrap());
+//! let replayed = transport::execute_async(&replay, &request).await.unwrap();
+//! assert_eq!(recorded, replayed);
+//! # });
+//! ```
+
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    io::{self, BufRead, Write},
+    sync::Mutex,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    binary::Media,
+    cache::canonicalize,
+    common::Format,
+    hex,
+    request::{
+        annotation, bookmark, browsing, chat, jukebox, lists, playlists, podcast, radio, retrieval,
+        scan, search, sharing, system, user, Request, SubsonicRequest,
+    },
+    response::{Error, ErrorCode, Response},
+    service::{self, service_methods, ServiceOutput, SubsonicService},
+    transport::{AsyncTransport, Transport, TransportError, TransportRequest, TransportResponse},
+};
+
+/// Parameters that are replaced by [`redact`]: password, token, salt and the OpenSubsonic API key.
+const CREDENTIAL_KEYS: &[&str] = &["p", "t", "s", "apiKey"];
+
+/// Replaces the values of the credential parameters of an encoded query with `redacted`.
+/// ```rust
+/// assert_eq!(
+///     "u=admin&t=redacted&s=redacted&v=1.16.1",
+///     subsonic_types::recording::redact("u=admin&t=26719a1196d2a940705a59634eb18eab&s=c19b2d&v=1.16.1")
+/// );
+/// ```
+pub fn redact(query: &str) -> String {
+    let is_credential = |key: &str| {
+        let key = percent_encoding::percent_decode_str(key).decode_utf8_lossy();
+        CREDENTIAL_KEYS.contains(&key.as_ref())
+    };
+    query
+        .split('&')
+        .map(|segment| match segment.split_once('=') {
+            Some((key, _)) if is_credential(key) => format!("{}=redacted", key),
+            _ => segment.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// A request and the raw response of the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recording {
+    /// The path of the endpoint, for example `/rest/ping`.
+    pub path: String,
+    /// The encoded parameters, with the credentials redacted.
+    pub query: String,
+    pub response: TransportResponse,
+}
+
+/// A [`Recording`] as a line of JSON. Bodies that are not UTF-8 are hex encoded.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Line {
+    path: String,
+    query: String,
+    status: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content_range: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body_hex: Option<String>,
+}
+
+impl Recording {
+    /// Creates a recording, redacting the parameters of the request.
+    pub fn new(request: &TransportRequest, response: TransportResponse) -> Self {
+        Self {
+            path: request.path.clone(),
+            query: redact(request.parameters.encoded()),
+            response,
+        }
+    }
+
+    /// Writes the recording as a single line of JSON.
+    pub fn write<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        let (body, body_hex) = match std::str::from_utf8(&self.response.body) {
+            Ok(body) => (Some(body.to_string()), None),
+            Err(_) => (None, Some(hex::encode(&self.response.body))),
+        };
+        let line = Line {
+            path: self.path.clone(),
+            query: self.query.clone(),
+            status: self.response.status,
+            content_type: self.response.content_type.clone(),
+            content_range: self.response.content_range.clone(),
+            body,
+            body_hex,
+        };
+        serde_json::to_writer(&mut *writer, &line)?;
+        writer.write_all(b"\n")
+    }
+
+    /// Reads the recordings written by [`Recording::write`], one per line.
+    pub fn read_all<R: BufRead>(reader: R) -> io::Result<Vec<Self>> {
+        let mut recordings = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let line: Line = serde_json::from_str(&line)?;
+            let body = match (line.body, line.body_hex) {
+                (_, Some(hex)) => hex::decode(&hex).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "invalid hex body")
+                })?,
+                (body, None) => body.unwrap_or_default().into_bytes(),
+            };
+            recordings.push(Self {
+                path: line.path,
+                query: line.query,
+                response: TransportResponse {
+                    status: line.status,
+                    content_type: line.content_type,
+                    content_range: line.content_range,
+                    body,
+                },
+            });
+        }
+        Ok(recordings)
+    }
+}
+
+/// A transport that writes a [`Recording`] of every response of the wrapped transport.
+///
+/// Recording is best effort, failing to write a recording does not fail the request.
+#[derive(Debug)]
+pub struct RecordingTransport<T, W> {
+    transport: T,
+    writer: Mutex<W>,
+}
+
+impl<T, W: Write> RecordingTransport<T, W> {
+    pub fn new(transport: T, writer: W) -> Self {
+        Self {
+            transport,
+            writer: Mutex::new(writer),
+        }
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    pub fn into_inner(self) -> (T, W) {
+        let writer = self
+            .writer
+            .into_inner()
+            .unwrap_or_else(|err| err.into_inner());
+        (self.transport, writer)
+    }
+
+    fn record(&self, request: &TransportRequest, response: &TransportResponse) {
+        let recording = Recording::new(request, response.clone());
+        let mut writer = self.writer.lock().unwrap_or_else(|err| err.into_inner());
+        let _ = recording.write(&mut *writer).and_then(|_| writer.flush());
+    }
+}
+
+impl<T: Transport, W: Write> Transport for RecordingTransport<T, W> {
+    type Error = T::Error;
+
+    fn execute(&self, request: TransportRequest) -> Result<TransportResponse, Self::Error> {
+        let response = self.transport.execute(request.clone())?;
+        self.record(&request, &response);
+        Ok(response)
+    }
+}
+
+impl<T, W> AsyncTransport for RecordingTransport<T, W>
+where
+    T: AsyncTransport + Sync,
+    W: Write + Send,
+{
+    type Error = T::Error;
+
+    async fn execute(&self, request: TransportRequest) -> Result<TransportResponse, Self::Error> {
+        let response = self.transport.execute(request.clone()).await?;
+        self.record(&request, &response);
+        Ok(response)
+    }
+}
+
+/// A [`SubsonicService`] that serves recorded responses.
+///
+/// Requests that were recorded several times get the recordings in order, the last one is
+/// repeated once they run out. Requests without a recording fail with [`ErrorCode::Generic`].
+#[derive(Debug, Default)]
+pub struct ReplayService {
+    recordings: HashMap<String, Vec<TransportResponse>>,
+    served: Mutex<HashMap<String, usize>>,
+}
+
+impl ReplayService {
+    pub fn new(recordings: impl IntoIterator<Item = Recording>) -> Self {
+        let mut service = Self::default();
+        for recording in recordings {
+            service
+                .recordings
+                .entry(canonicalize(&recording.path, &recording.query))
+                .or_default()
+                .push(recording.response);
+        }
+        service
+    }
+
+    /// Reads the recordings with [`Recording::read_all`].
+    pub fn from_reader<R: BufRead>(reader: R) -> io::Result<Self> {
+        Ok(Self::new(Recording::read_all(reader)?))
+    }
+
+    fn next<R: SubsonicRequest>(&self, request: &Request<R>) -> service::Result<TransportResponse> {
+        let key = canonicalize(R::PATH, &SubsonicRequest::to_query(request));
+        let responses = self.recordings.get(&key).ok_or_else(|| {
+            Error::with_message(ErrorCode::Generic, format!("no recording for {}", key))
+        })?;
+        let mut served = self.served.lock().unwrap_or_else(|err| err.into_inner());
+        let count = served.entry(key).or_default();
+        let response = responses[(*count).min(responses.len() - 1)].clone();
+        *count += 1;
+        Ok(response)
+    }
+}
+
+fn invalid_recording(err: TransportError<Infallible>) -> Error {
+    Error::with_message(ErrorCode::Generic, format!("invalid recording: {}", err))
+}
+
+macro_rules! impl_replay_service {
+    ($($(#[$comment:meta])* ($name:ident $request:path => $output:ty)),*) => {
+        #[async_trait::async_trait]
+        impl SubsonicService for ReplayService {
+            $(
+                async fn $name(&self, request: Request<$request>) -> service::Result<$output> {
+                    let format = request
+                        .format
+                        .as_deref()
+                        .and_then(|format| format.parse().ok())
+                        .unwrap_or(Format::Xml);
+                    <$output>::from_transport_response::<Infallible>(format, self.next(&request)?)
+                        .map_err(invalid_recording)?
+                }
+            )*
+        }
+    };
+}
+
+service_methods!(impl_replay_service);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        binary::BinaryResponse,
+        common::Version,
+        request::Authentication,
+        response::{Genre, Genres, ResponseBody},
+        service::ServiceTransport,
+        test_support, transport,
+    };
+
+    use super::*;
+
+    #[test]
+    fn redacted_credentials() {
+        assert_eq!(
+            "u=admin&p=redacted&v=1.16.1&c=test",
+            redact("u=admin&p=enc%3A736563726574&v=1.16.1&c=test")
+        );
+        assert_eq!(
+            "apiKey=redacted&v=1.16.1&c=test",
+            redact("apiKey=d6f0b8a4&v=1.16.1&c=test")
+        );
+        assert_eq!("p&id=1", redact("p&id=1"));
+        assert_eq!("", redact(""));
+    }
+
+    #[test]
+    fn lines() {
+        let recordings = vec![
+            Recording {
+                path: "/rest/ping".into(),
+                query: "v=1.16.1".into(),
+                response: TransportResponse {
+                    status: 200,
+                    content_type: Some("text/xml".into()),
+                    content_range: None,
+                    body: br#"<subsonic-response status="ok" version="1.16.1"/>"#.to_vec(),
+                },
+            },
+            Recording {
+                path: "/rest/getCoverArt".into(),
+                query: "id=1".into(),
+                response: TransportResponse {
+                    status: 206,
+                    content_type: None,
+                    content_range: Some("bytes 0-2/10".into()),
+                    body: vec![0x89, 0xff, 0x00],
+                },
+            },
+        ];
+        let mut corpus = Vec::new();
+        for recording in &recordings {
+            recording.write(&mut corpus).unwrap();
+        }
+        let corpus = String::from_utf8(corpus).unwrap();
+        assert_eq!(2, corpus.lines().count());
+        assert!(corpus.contains(r#""bodyHex":"89ff00""#), "{}", corpus);
+        assert_eq!(recordings, Recording::read_all(corpus.as_bytes()).unwrap());
+
+        assert!(Recording::read_all("{".as_bytes()).is_err());
+        assert!(Recording::read_all(
+            r#"{"path":"/rest/ping","query":"","status":200,"bodyHex":"8"}"#.as_bytes()
+        )
+        .is_err());
+    }
+
+    struct Server;
+
+    #[async_trait::async_trait]
+    impl SubsonicService for Server {
+        async fn get_genres(
+            &self,
+            request: Request<browsing::GetGenres>,
+        ) -> service::Result<Response> {
+            let genre = Genre {
+                song_count: 1,
+                album_count: 1,
+                name: "Rock".into(),
+            };
+            Ok(Response::genres(
+                request.version,
+                Genres { genre: vec![genre] },
+            ))
+        }
+
+        async fn get_cover_art(
+            &self,
+            request: Request<retrieval::GetCoverArt>,
+        ) -> service::Result<Media> {
+            match request.body.id.as_ref() {
+                "1" => Ok(Media::new("image/png", vec![0x89, 0x50, 0x4e, 0x47])),
+                _ => Err(Error::new(ErrorCode::DataNotFound)),
+            }
+        }
+    }
+
+    fn request<R: SubsonicRequest>(format: Option<&str>, body: R) -> Request<R> {
+        Request {
+            authentication: Authentication::random_token("secret").unwrap(),
+            format: format.map(str::to_string),
+            ..test_support::request(body)
+        }
+    }
+
+    #[tokio::test]
+    async fn record_and_replay() {
+        let recorder = RecordingTransport::new(ServiceTransport::new(Server), Vec::new());
+        let genres = request(Some("json"), browsing::GetGenres);
+        let xml_genres = request(None, browsing::GetGenres);
+        let cover_art = request(None, retrieval::GetCoverArt::builder("1").build());
+        let missing_cover_art = request(None, retrieval::GetCoverArt::builder("2").build());
+
+        let recorded_genres = transport::execute_async(&recorder, &genres).await.unwrap();
+        transport::execute_async(&recorder, &xml_genres)
+            .await
+            .unwrap();
+        let recorded_cover_art = transport::execute_binary_async(&recorder, &cover_art)
+            .await
+            .unwrap();
+        transport::execute_binary_async(&recorder, &missing_cover_art)
+            .await
+            .unwrap();
+
+        let (_, corpus) = recorder.into_inner();
+        let recordings = Recording::read_all(corpus.as_slice()).unwrap();
+        assert_eq!(4, recordings.len());
+        for recording in &recordings {
+            assert!(recording.query.contains("t=redacted&s=redacted"));
+        }
+        assert_eq!(
+            Some("application/json"),
+            recordings[0].response.content_type.as_deref()
+        );
+        assert_eq!(
+            Some("text/xml"),
+            recordings[1].response.content_type.as_deref()
+        );
+
+        let replay = ServiceTransport::new(ReplayService::new(recordings));
+        // Other credentials, same request.
+        let mut genres = genres;
+        genres.authentication = Authentication::Password("other".into());
+        assert_eq!(
+            recorded_genres,
+            transport::execute_async(&replay, &genres).await.unwrap()
+        );
+        assert_eq!(
+            recorded_genres,
+            transport::execute_async(&replay, &xml_genres)
+                .await
+                .unwrap()
+        );
+        assert_eq!(
+            recorded_cover_art,
+            transport::execute_binary_async(&replay, &cover_art)
+                .await
+                .unwrap()
+        );
+        let missing = transport::execute_binary_async(&replay, &missing_cover_art)
+            .await
+            .unwrap();
+        assert!(matches!(
+            missing,
+            BinaryResponse::Error(Error {
+                code: ErrorCode::DataNotFound,
+                ..
+            })
+        ));
+
+        let response = transport::execute_async(&replay, &request(None, system::Ping))
+            .await
+            .unwrap();
+        assert!(matches!(
+            response.body,
+            ResponseBody::Error(Error {
+                code: ErrorCode::Generic,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn repeated_requests() {
+        let recording = |version: &str| Recording {
+            path: "/rest/ping.view".into(),
+            query: "u=admin&p=redacted&v=1.16.1&c=test".into(),
+            response: TransportResponse {
+                status: 200,
+                content_type: Some("text/xml".into()),
+                content_range: None,
+                body: format!(r#"<subsonic-response status="ok" version="{}"/>"#, version)
+                    .into_bytes(),
+            },
+        };
+        let replay = ReplayService::new(vec![recording("1.16.0"), recording("1.16.1")]);
+        let mut versions = Vec::new();
+        for _ in 0..3 {
+            let ping = request(None, system::Ping);
+            versions.push(replay.ping(ping).await.unwrap().version);
+        }
+        assert_eq!(
+            vec![Version::V1_16_0, Version::V1_16_1, Version::V1_16_1],
+            versions
+        );
+    }
+}
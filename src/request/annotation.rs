@@ -3,7 +3,7 @@ use subsonic_types_macro::{FromQuery, SubsonicRequest, ToQuery};
 
 #[allow(unused)]
 use crate::{
-    common::{Milliseconds, UserRating},
+    common::{AlbumId, ArtistId, Id, Milliseconds, SongId, UserRating},
     request::lists::GetNowPlaying,
 };
 
@@ -12,19 +12,20 @@ use crate::{
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#star>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.8.0", path = "star")]
+#[subsonic(since = "1.8.0", path = "star", mutating)]
+#[non_exhaustive]
 pub struct Star {
     /// The ID of the file (song) or folder (album/artist) to star.
     #[serde(default)]
-    pub id: Vec<String>,
+    pub id: Vec<Id>,
     /// The ID of an album to star.
     /// Use this rather than id if the client accesses the media collection according to ID3 tags rather than file structure.
     #[serde(default)]
-    pub album_id: Vec<String>,
+    pub album_id: Vec<AlbumId>,
     /// The ID of an artist to star.
     /// Use this rather than id if the client accesses the media collection according to ID3 tags rather than file structure.
     #[serde(default)]
-    pub artist_id: Vec<String>,
+    pub artist_id: Vec<ArtistId>,
 }
 
 /// Removes the star from a song, album or artist.
@@ -32,19 +33,20 @@ pub struct Star {
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#unstar>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.8.0", path = "unstar")]
+#[subsonic(since = "1.8.0", path = "unstar", mutating)]
+#[non_exhaustive]
 pub struct Unstar {
     /// The ID of the file (song) or folder (album/artist) to star.
     #[serde(default)]
-    pub id: Vec<String>,
+    pub id: Vec<Id>,
     /// The ID of an album to star.
     /// Use this rather than id if the client accesses the media collection according to ID3 tags rather than file structure.
     #[serde(default)]
-    pub album_id: Vec<String>,
+    pub album_id: Vec<AlbumId>,
     /// The ID of an artist to star.
     /// Use this rather than id if the client accesses the media collection according to ID3 tags rather than file structure.
     #[serde(default)]
-    pub artist_id: Vec<String>,
+    pub artist_id: Vec<ArtistId>,
 }
 
 /// Sets the rating for a music file.
@@ -52,10 +54,10 @@ pub struct Unstar {
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#setRating>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.6.0", path = "setRating")]
+#[subsonic(since = "1.6.0", path = "setRating", mutating)]
 pub struct SetRating {
     /// A string which uniquely identifies the file (song) or folder (album/artist) to rate.
-    pub id: String,
+    pub id: Id,
     /// The rating between 1 and 5 (inclusive), or 0 to remove the rating.
     pub rating: UserRating,
 }
@@ -66,16 +68,18 @@ pub struct SetRating {
 /// - "Scrobbles" the media files on last.fm if the user has configured his/her last.fm credentials on the Subsonic server (Settings > Personal).
 /// - Updates the play count and last played timestamp for the media files. (Since 1.11.0)
 /// - Makes the media files appear in the "Now playing" page in the web app, and appear in the list of songs returned by [`GetNowPlaying`] (Since 1.11.0)
+///
 /// Since 1.8.0 you may specify multiple id (and optionally time) parameters to scrobble multiple files.
 ///
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#scrobble>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.5.0", path = "scrobble")]
+#[subsonic(since = "1.5.0", path = "scrobble", mutating)]
+#[non_exhaustive]
 pub struct Scrobble {
     /// A string which uniquely identifies the file to scrobble.
     #[serde(default)]
-    pub id: Vec<String>,
+    pub id: Vec<SongId>,
     /// The time at which the song was listened to.
     #[serde(default)]
     pub time: Vec<Milliseconds>,
@@ -93,7 +97,7 @@ mod tests {
     #[test]
     fn test_star() {
         let request = Star {
-            id: vec!["1".to_string()],
+            id: vec!["1".into()],
             album_id: vec![],
             artist_id: vec![],
         };
@@ -102,7 +106,7 @@ mod tests {
 
         let request = Star {
             id: vec![],
-            album_id: vec!["1".to_string()],
+            album_id: vec!["1".into()],
             artist_id: vec![],
         };
         let encoded = test_request_encode(&request);
@@ -111,23 +115,23 @@ mod tests {
         let request = Star {
             id: vec![],
             album_id: vec![],
-            artist_id: vec!["1".to_string()],
+            artist_id: vec!["1".into()],
         };
         let encoded = test_request_encode(&request);
         assert_eq!(encoded, "artistId=1");
 
         let request = Star {
-            id: vec!["1".to_string()],
-            album_id: vec!["2".to_string()],
-            artist_id: vec!["3".to_string()],
+            id: vec!["1".into()],
+            album_id: vec!["2".into()],
+            artist_id: vec!["3".into()],
         };
         let encoded = test_request_encode(&request);
         assert_eq!(encoded, "id=1&albumId=2&artistId=3");
 
         let request = Star {
-            id: vec!["1".to_string(), "2".to_string()],
-            album_id: vec!["3".to_string(), "4".to_string()],
-            artist_id: vec!["5".to_string(), "6".to_string()],
+            id: vec!["1".into(), "2".into()],
+            album_id: vec!["3".into(), "4".into()],
+            artist_id: vec!["5".into(), "6".into()],
         };
         let encoded = test_request_encode(&request);
         assert_eq!(
@@ -139,7 +143,7 @@ mod tests {
     #[test]
     fn test_unstar() {
         let request = Unstar {
-            id: vec!["1".to_string()],
+            id: vec!["1".into()],
             album_id: vec![],
             artist_id: vec![],
         };
@@ -148,7 +152,7 @@ mod tests {
 
         let request = Unstar {
             id: vec![],
-            album_id: vec!["1".to_string()],
+            album_id: vec!["1".into()],
             artist_id: vec![],
         };
         let encoded = test_request_encode(&request);
@@ -157,23 +161,23 @@ mod tests {
         let request = Unstar {
             id: vec![],
             album_id: vec![],
-            artist_id: vec!["1".to_string()],
+            artist_id: vec!["1".into()],
         };
         let encoded = test_request_encode(&request);
         assert_eq!(encoded, "artistId=1");
 
         let request = Unstar {
-            id: vec!["1".to_string()],
-            album_id: vec!["2".to_string()],
-            artist_id: vec!["3".to_string()],
+            id: vec!["1".into()],
+            album_id: vec!["2".into()],
+            artist_id: vec!["3".into()],
         };
         let encoded = test_request_encode(&request);
         assert_eq!(encoded, "id=1&albumId=2&artistId=3");
 
         let request = Unstar {
-            id: vec!["1".to_string(), "2".to_string()],
-            album_id: vec!["3".to_string(), "4".to_string()],
-            artist_id: vec!["5".to_string(), "6".to_string()],
+            id: vec!["1".into(), "2".into()],
+            album_id: vec!["3".into(), "4".into()],
+            artist_id: vec!["5".into(), "6".into()],
         };
         let encoded = test_request_encode(&request);
         assert_eq!(
@@ -185,14 +189,14 @@ mod tests {
     #[test]
     fn test_set_rating() {
         let request = SetRating {
-            id: "1".to_string(),
+            id: "1".into(),
             rating: UserRating::new(5).unwrap(),
         };
         let encoded = test_request_encode(&request);
         assert_eq!(encoded, "id=1&rating=5");
 
         let request = SetRating {
-            id: "1".to_string(),
+            id: "1".into(),
             rating: UserRating::new(2).unwrap(),
         };
         let encoded = test_request_encode(&request);
@@ -205,7 +209,7 @@ mod tests {
     #[test]
     fn test_scrobble() {
         let request = Scrobble {
-            id: vec!["1".to_string()],
+            id: vec!["1".into()],
             time: vec![],
             submission: None,
         };
@@ -221,7 +225,7 @@ mod tests {
         assert_eq!(encoded, "time=1");
 
         let request = Scrobble {
-            id: vec!["1".to_string()],
+            id: vec!["1".into()],
             time: vec![Milliseconds::new(1)],
             submission: None,
         };
@@ -229,7 +233,7 @@ mod tests {
         assert_eq!(encoded, "id=1&time=1");
 
         let request = Scrobble {
-            id: vec!["1".to_string(), "2".to_string()],
+            id: vec!["1".into(), "2".into()],
             time: vec![Milliseconds::new(1), Milliseconds::new(2)],
             submission: None,
         };
@@ -237,7 +241,7 @@ mod tests {
         assert_eq!(encoded, "id=1&id=2&time=1&time=2");
 
         let request = Scrobble {
-            id: vec!["1".to_string(), "2".to_string()],
+            id: vec!["1".into(), "2".into()],
             time: vec![Milliseconds::new(1), Milliseconds::new(2)],
             submission: Some(true),
         };
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use subsonic_types_macro::{FromQuery, SubsonicRequest, ToQuery};
 
-use crate::common::Milliseconds;
+use crate::common::{Milliseconds, SongId};
 
 /// Returns all bookmarks for this user. A bookmark is a position within a certain media file.
 ///
@@ -16,11 +16,12 @@ pub struct GetBookmarks;
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#createBookmark>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.9.0", path = "createBookmark")]
+#[subsonic(since = "1.9.0", path = "createBookmark", mutating)]
+#[non_exhaustive]
 pub struct CreateBookmark {
     /// ID of the media file to bookmark.
     /// If a bookmark already exists for this file it will be overwritten.
-    pub id: String,
+    pub id: SongId,
     /// The position within the media file.
     pub position: Milliseconds,
     /// A user-defined comment.
@@ -32,11 +33,11 @@ pub struct CreateBookmark {
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#deleteBookmark>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.9.0", path = "deleteBookmark")]
+#[subsonic(since = "1.9.0", path = "deleteBookmark", mutating)]
 pub struct DeleteBookmark {
     /// ID of the media file for which to delete the bookmark.
     /// Other users' bookmarks are not affected.
-    pub id: String,
+    pub id: SongId,
 }
 
 /// Returns the state of the play queue for this user (as set by [`SavePlayQueue`]).
@@ -56,13 +57,14 @@ pub struct GetPlayQueue;
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#savePlayQueue>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.12.0", path = "savePlayQueue")]
+#[subsonic(since = "1.12.0", path = "savePlayQueue", mutating)]
+#[non_exhaustive]
 pub struct SavePlayQueue {
     /// ID of a song in the play queue.
     #[serde(default)]
-    pub id: Vec<String>,
+    pub id: Vec<SongId>,
     /// The ID of the current playing song.
-    pub current: Option<String>,
+    pub current: Option<SongId>,
     /// The position in milliseconds within the currently playing song.
     pub position: Option<Milliseconds>,
 }
@@ -82,7 +84,7 @@ mod tests {
     #[test]
     fn test_create_bookmark() {
         let request = super::CreateBookmark {
-            id: "1".to_string(),
+            id: "1".into(),
             position: Milliseconds::new(1000),
             comment: None,
         };
@@ -91,7 +93,7 @@ mod tests {
 
         // Test with comment
         let request = super::CreateBookmark {
-            id: "1".to_string(),
+            id: "1".into(),
             position: Milliseconds::new(1000),
             comment: Some("test".to_string()),
         };
@@ -102,7 +104,7 @@ mod tests {
     #[test]
     fn test_delete_bookmarks() {
         let request = super::DeleteBookmark {
-            id: "1".to_string(),
+            id: "1".into(),
         };
         let query = test_request_encode(&request);
         assert_eq!("id=1", query);
@@ -118,8 +120,8 @@ mod tests {
     #[test]
     fn test_save_play_queue() {
         let request = super::SavePlayQueue {
-            id: vec!["1".to_string(), "2".to_string()],
-            current: Some("1".to_string()),
+            id: vec!["1".into(), "2".into()],
+            current: Some("1".into()),
             position: Some(Milliseconds::new(1000)),
         };
         let query = test_request_encode(&request);
@@ -127,7 +129,7 @@ mod tests {
 
         // Test without current
         let request = super::SavePlayQueue {
-            id: vec!["1".to_string(), "2".to_string()],
+            id: vec!["1".into(), "2".into()],
             current: None,
             position: Some(Milliseconds::new(1000)),
         };
@@ -136,8 +138,8 @@ mod tests {
 
         // Test without position
         let request = super::SavePlayQueue {
-            id: vec!["1".to_string(), "2".to_string()],
-            current: Some("1".to_string()),
+            id: vec!["1".into(), "2".into()],
+            current: Some("1".into()),
             position: None,
         };
         let query = test_request_encode(&request);
@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
 use subsonic_types_macro::{FromQuery, SubsonicRequest, ToQuery};
 
-use crate::common::Milliseconds;
+use crate::common::{
+    AlbumId, ArtistId, DirectoryId, Id, Milliseconds, MusicFolderId, SongId,
+};
 
 /// Returns all configured top-level music folders. Takes no extra parameters.
 ///
@@ -17,10 +19,11 @@ pub struct GetMusicFolders;
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
 #[subsonic(since = "1.0.0", path = "getIndexes")]
+#[non_exhaustive]
 pub struct GetIndexes {
     /// If specified, only return artists in the music folder with the given ID.
     /// See [`GetMusicFolders`].
-    pub music_folder_id: Option<String>,
+    pub music_folder_id: Option<MusicFolderId>,
     /// If specified, only return a result if the artist collection has changed since the given time.
     pub if_modified_since: Option<Milliseconds>,
 }
@@ -33,7 +36,7 @@ pub struct GetIndexes {
 #[subsonic(since = "1.0.0", path = "getMusicDirectory")]
 pub struct GetMusicDirectory {
     /// A string which uniquely identifies the music folder. Obtained by calls to getIndexes or getMusicDirectory.
-    pub id: String,
+    pub id: DirectoryId,
 }
 
 /// Returns all genres.
@@ -50,10 +53,11 @@ pub struct GetGenres;
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
 #[subsonic(since = "1.8.0", path = "getArtists")]
+#[non_exhaustive]
 pub struct GetArtists {
     /// If specified, only return artists in the music folder with the given ID.
     /// See [`GetMusicFolders`].
-    pub music_folder_id: Option<u32>,
+    pub music_folder_id: Option<MusicFolderId>,
 }
 
 /// Returns details for an artist, including a list of albums.
@@ -64,7 +68,7 @@ pub struct GetArtists {
 #[subsonic(since = "1.8.0", path = "getArtist")]
 pub struct GetArtist {
     /// The artist ID.
-    pub id: String,
+    pub id: ArtistId,
 }
 
 /// Returns details for an album, including a list of songs.
@@ -75,7 +79,7 @@ pub struct GetArtist {
 #[subsonic(since = "1.8.0", path = "getAlbum")]
 pub struct GetAlbum {
     /// The album ID.
-    pub id: String,
+    pub id: AlbumId,
 }
 
 /// Returns details for a song.
@@ -86,7 +90,7 @@ pub struct GetAlbum {
 #[subsonic(since = "1.8.0", path = "getSong")]
 pub struct GetSong {
     /// The ID of the song to retrieve.
-    pub id: String,
+    pub id: SongId,
 }
 
 /// Represents a request to retrieve all video files.
@@ -104,7 +108,8 @@ pub struct GetVideos;
 #[serde(rename_all = "camelCase")]
 #[subsonic(since = "1.14.0", path = "getVideoInfo")]
 pub struct GetVideoInfo {
-    pub id: String,
+    /// The video ID.
+    pub id: SongId,
 }
 
 /// Returns artist info with biography, image URLs and similar artists, using data from <http://last.fm>.
@@ -113,9 +118,10 @@ pub struct GetVideoInfo {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
 #[subsonic(since = "1.11.0", path = "getArtistInfo")]
+#[non_exhaustive]
 pub struct GetArtistInfo {
     /// The artist, album or song ID.
-    pub id: String,
+    pub id: Id,
     /// Max number of similar artists to return.
     pub count: Option<u32>,
     /// Whether to return artists that are not present in the media library.
@@ -133,9 +139,10 @@ impl GetArtistInfo {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
 #[subsonic(since = "1.11.0", path = "getArtistInfo2")]
+#[non_exhaustive]
 pub struct GetArtistInfo2 {
-    /// The artist, album or song ID.
-    pub id: String,
+    /// The artist ID.
+    pub id: ArtistId,
     /// Max number of similar artists to return.
     pub count: Option<u32>,
     /// Whether to return artists that are not present in the media library.
@@ -155,7 +162,7 @@ impl GetArtistInfo2 {
 #[subsonic(since = "1.14.0", path = "getAlbumInfo")]
 pub struct GetAlbumInfo {
     /// The album or song ID.
-    pub id: String,
+    pub id: Id,
 }
 
 /// Similar to [`GetAlbumInfo`], but organizes music according to ID3 tags.
@@ -165,8 +172,8 @@ pub struct GetAlbumInfo {
 #[serde(rename_all = "camelCase")]
 #[subsonic(since = "1.14.0", path = "getAlbumInfo2")]
 pub struct GetAlbumInfo2 {
-    /// The album or song ID.
-    pub id: String,
+    /// The album ID.
+    pub id: AlbumId,
 }
 
 /// Returns a random collection of songs from the given artist and similar artists, using data from last.fm. Typically used for artist radio features.
@@ -175,9 +182,10 @@ pub struct GetAlbumInfo2 {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
 #[subsonic(since = "1.11.0", path = "getSimilarSongs")]
+#[non_exhaustive]
 pub struct GetSimilarSongs {
     /// The artist, album or song ID.
-    pub id: String,
+    pub id: Id,
     /// Max number of songs to return.
     pub count: Option<u32>,
 }
@@ -192,9 +200,10 @@ impl GetSimilarSongs {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
 #[subsonic(since = "1.11.0", path = "getSimilarSongs2")]
+#[non_exhaustive]
 pub struct GetSimilarSongs2 {
-    /// The artist, album or song ID.
-    pub id: String,
+    /// The artist ID.
+    pub id: ArtistId,
     /// Max number of songs to return.
     pub count: Option<u32>,
 }
@@ -209,6 +218,7 @@ impl GetSimilarSongs2 {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
 #[subsonic(since = "1.13.0", path = "getTopSongs")]
+#[non_exhaustive]
 pub struct GetTopSongs {
     /// The artist name.
     pub id: String,
@@ -242,7 +252,7 @@ mod tests {
         assert_eq!(query, "");
 
         let request = GetIndexes {
-            music_folder_id: Some("123".to_string()),
+            music_folder_id: Some(MusicFolderId::new(123)),
             if_modified_since: Some(Milliseconds::new(123)),
         };
         let query = test_request_encode(&request);
@@ -252,7 +262,7 @@ mod tests {
     #[test]
     fn test_get_music_directory() {
         let request = GetMusicDirectory {
-            id: "123".to_string(),
+            id: "123".into(),
         };
         let query = test_request_encode(&request);
         assert_eq!(query, "id=123");
@@ -274,7 +284,7 @@ mod tests {
         assert_eq!(query, "");
 
         let request = GetArtists {
-            music_folder_id: Some(20),
+            music_folder_id: Some(MusicFolderId::new(20)),
         };
         let query = test_request_encode(&request);
         assert_eq!(query, "musicFolderId=20");
@@ -283,7 +293,7 @@ mod tests {
     #[test]
     fn test_get_artist() {
         let request = GetArtist {
-            id: "123".to_string(),
+            id: "123".into(),
         };
         let query = test_request_encode(&request);
         assert_eq!(query, "id=123");
@@ -292,7 +302,7 @@ mod tests {
     #[test]
     fn test_get_album() {
         let request = GetAlbum {
-            id: "123".to_string(),
+            id: "123".into(),
         };
         let query = test_request_encode(&request);
         assert_eq!(query, "id=123");
@@ -301,7 +311,7 @@ mod tests {
     #[test]
     fn test_get_song() {
         let request = GetSong {
-            id: "123".to_string(),
+            id: "123".into(),
         };
         let query = test_request_encode(&request);
         assert_eq!(query, "id=123");
@@ -317,7 +327,7 @@ mod tests {
     #[test]
     fn test_get_video_info() {
         let request = GetVideoInfo {
-            id: "123".to_string(),
+            id: "123".into(),
         };
         let query = test_request_encode(&request);
         assert_eq!(query, "id=123");
@@ -326,7 +336,7 @@ mod tests {
     #[test]
     fn test_get_artist_info() {
         let request = GetArtistInfo {
-            id: "123".to_string(),
+            id: "123".into(),
             count: None,
             include_not_present: None,
         };
@@ -334,7 +344,7 @@ mod tests {
         assert_eq!(query, "id=123");
 
         let request = GetArtistInfo {
-            id: "123".to_string(),
+            id: "123".into(),
             count: Some(123),
             include_not_present: Some(true),
         };
@@ -345,7 +355,7 @@ mod tests {
     #[test]
     fn test_get_artist_info_2() {
         let request = GetArtistInfo2 {
-            id: "123".to_string(),
+            id: "123".into(),
             count: None,
             include_not_present: None,
         };
@@ -353,7 +363,7 @@ mod tests {
         assert_eq!(query, "id=123");
 
         let request = GetArtistInfo2 {
-            id: "123".to_string(),
+            id: "123".into(),
             count: Some(123),
             include_not_present: Some(true),
         };
@@ -364,7 +374,7 @@ mod tests {
     #[test]
     fn test_get_album_info_2() {
         let request = GetAlbumInfo2 {
-            id: "123".to_string(),
+            id: "123".into(),
         };
         let query = test_request_encode(&request);
         assert_eq!(query, "id=123");
@@ -373,14 +383,14 @@ mod tests {
     #[test]
     fn test_get_similar_songs() {
         let request = GetSimilarSongs {
-            id: "123".to_string(),
+            id: "123".into(),
             count: None,
         };
         let query = test_request_encode(&request);
         assert_eq!(query, "id=123");
 
         let request = GetSimilarSongs {
-            id: "123".to_string(),
+            id: "123".into(),
             count: Some(123),
         };
         let query = test_request_encode(&request);
@@ -9,6 +9,7 @@ use crate::common::Milliseconds;
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
 #[subsonic(since = "1.2.0", path = "getChatMessages")]
+#[non_exhaustive]
 pub struct GetChatMessages {
     /// Only return messages newer than this time.
     pub since: Option<Milliseconds>,
@@ -19,7 +20,7 @@ pub struct GetChatMessages {
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#addChatMessage>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.2.0", path = "addChatMessage")]
+#[subsonic(since = "1.2.0", path = "addChatMessage", mutating)]
 pub struct AddChatMessage {
     /// The chat message.
     pub message: String,
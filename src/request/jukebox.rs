@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use subsonic_types_macro::{FromQuery, SubsonicRequest, ToQuery};
 
-use crate::{impl_from_query_value_for_parse, impl_to_query_value_for_display};
+use crate::common::SongId;
 
 #[derive(Debug)]
 pub struct InvalidJukeboxAction;
@@ -97,7 +97,8 @@ impl<'de> serde::Deserialize<'de> for JukeboxAction {
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#jukeboxControl>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.2.0", path = "jukeboxControl")]
+#[subsonic(since = "1.2.0", path = "jukeboxControl", mutating)]
+#[non_exhaustive]
 pub struct JukeboxControl {
     /// The operation to perform.
     pub action: JukeboxAction,
@@ -112,7 +113,7 @@ pub struct JukeboxControl {
     /// ID of song to add to the jukebox playlist.
     /// [`JukeboxAction::Set`] is similar to a [`JukeboxAction::Clear`] followed by a [`JukeboxAction::Add`], but will not change the currently playing track.)
     #[serde(default)]
-    pub id: Vec<String>,
+    pub id: Vec<SongId>,
     /// Used by [`JukeboxAction::SetGain`] to control the playback volume.
     /// A float value between 0.0 and 1.0.
     pub gain: Option<f32>,
@@ -149,7 +150,7 @@ mod tests {
             action: JukeboxAction::Set,
             index: Some(1),
             offset: Some(20),
-            id: vec!["1".to_string(), "2".to_string()],
+            id: vec!["1".into(), "2".into()],
             gain: None,
         };
         let query = test_request_encode(&request);
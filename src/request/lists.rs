@@ -1,9 +1,9 @@
 use serde::{Deserialize, Serialize};
 use subsonic_types_macro::{FromQuery, SubsonicRequest, ToQuery};
 
+use crate::common::MusicFolderId;
 #[allow(unused)]
 use crate::request::browsing::{GetGenres, GetMusicFolders};
-use crate::{impl_from_query_value_for_parse, impl_to_query_value_for_display};
 
 #[derive(Debug)]
 pub struct InvalidListType;
@@ -105,6 +105,7 @@ impl<'de> serde::Deserialize<'de> for ListType {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
 #[subsonic(since = "1.2.0", path = "getAlbumList")]
+#[non_exhaustive]
 pub struct GetAlbumList {
     /// See [`ListType`].
     #[serde(rename = "type")]
@@ -125,7 +126,7 @@ pub struct GetAlbumList {
     pub genre: Option<String>,
     /// Since 1.11.0
     /// Only return albums in the music folder with the given ID. See [`GetMusicFolders`].
-    pub music_folder_id: Option<String>,
+    pub music_folder_id: Option<MusicFolderId>,
 }
 
 /// Similar to [`GetAlbumList`], but organizes music according to ID3 tags.
@@ -134,6 +135,7 @@ pub struct GetAlbumList {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
 #[subsonic(since = "1.8.0", path = "getAlbumList2")]
+#[non_exhaustive]
 pub struct GetAlbumList2 {
     /// See [`ListType`].
     #[serde(rename = "type")]
@@ -154,7 +156,7 @@ pub struct GetAlbumList2 {
     pub genre: Option<String>,
     /// Since 1.11.0
     /// Only return albums in the music folder with the given ID. See [`GetMusicFolders`].
-    pub music_folder_id: Option<String>,
+    pub music_folder_id: Option<MusicFolderId>,
 }
 
 /// Returns random songs matching the given criteria.
@@ -163,6 +165,7 @@ pub struct GetAlbumList2 {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
 #[subsonic(since = "1.2.0", path = "getRandomSongs")]
+#[non_exhaustive]
 pub struct GetRandomSongs {
     /// The maximum number of songs to return. Max 500.
     pub size: Option<u32>,
@@ -173,7 +176,7 @@ pub struct GetRandomSongs {
     /// Only return songs published before or in this year.
     pub to_year: Option<u32>,
     /// Only return songs in the music folder with the given ID. See [`GetMusicFolders`].
-    pub music_folder_id: Option<String>,
+    pub music_folder_id: Option<MusicFolderId>,
 }
 
 /// Returns songs in a given genre.
@@ -182,6 +185,7 @@ pub struct GetRandomSongs {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
 #[subsonic(since = "1.9.0", path = "getSongsByGenre")]
+#[non_exhaustive]
 pub struct GetSongsByGenre {
     /// The genre, as returned by [`GetGenres`].
     pub genre: String,
@@ -191,7 +195,7 @@ pub struct GetSongsByGenre {
     pub offset: Option<u32>,
     /// Since 1.12.0
     /// Only return albums in the music folder with the given ID. See [`GetMusicFolders`].
-    pub music_folder_id: Option<String>,
+    pub music_folder_id: Option<MusicFolderId>,
 }
 
 /// Returns what is currently being played by all users. Takes no extra parameters.
@@ -208,10 +212,11 @@ pub struct GetNowPlaying;
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
 #[subsonic(since = "1.8.0", path = "getStarred")]
+#[non_exhaustive]
 pub struct GetStarred {
     /// Since 1.12.0
     /// Only return albums in the music folder with the given ID. See [`GetMusicFolders`].
-    pub music_folder_id: Option<String>,
+    pub music_folder_id: Option<MusicFolderId>,
 }
 
 /// Returns starred songs, albums and artists.
@@ -220,10 +225,11 @@ pub struct GetStarred {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
 #[subsonic(since = "1.12.0", path = "getStarred2")]
+#[non_exhaustive]
 pub struct GetStarred2 {
     /// Since 1.12.0
     /// Only return albums in the music folder with the given ID. See [`GetMusicFolders`].
-    pub music_folder_id: Option<String>,
+    pub music_folder_id: Option<MusicFolderId>,
 }
 
 #[cfg(test)]
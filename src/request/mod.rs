@@ -54,6 +54,7 @@ use subsonic_types_macro::{FromQuery, ToQuery};
 
 use crate::{
     common::Version,
+    hex,
     query::{self, FromQuery, QueryAccumulator, QueryPair, QueryValueParseError, ToQuery},
 };
 
@@ -110,11 +111,10 @@ pub mod scan;
 ///     assert_eq!("/rest/getArtistInfo", subsonic_types::request::browsing::GetArtistInfo::PATH);
 ///     assert_eq!(subsonic_types::common::Version::V1_11_0, subsonic_types::request::browsing::GetArtistInfo::SINCE);
 ///
-///     let request = subsonic_types::request::browsing::GetArtistInfo {
-///         id: "123".into(),
-///         count: Some(10),
-///         include_not_present: Some(true),
-///     };
+///     let request = subsonic_types::request::browsing::GetArtistInfo::builder("123")
+///         .count(10)
+///         .include_not_present(true)
+///         .build();
 ///     let query = request.to_query();
 ///     assert_eq!("id=123&count=10&includeNotPresent", query);
 ///     
@@ -128,9 +128,14 @@ pub trait SubsonicRequest:
     + std::fmt::Debug
     + std::cmp::PartialEq
     + std::clone::Clone
+    + crate::visit::Visit
+    + crate::visit::VisitMut
 {
     const PATH: &'static str;
     const SINCE: Version;
+    /// Whether the request modifies the server, e.g. `star` or `createPlaylist`.
+    /// Set with `#[subsonic(mutating)]`.
+    const MUTATING: bool = false;
 
     fn to_query(&self) -> String {
         query::to_query(self)
@@ -147,6 +152,51 @@ pub enum Authentication {
     Token { token: String, salt: String },
 }
 
+impl Authentication {
+    /// Token authentication, available since 1.13.0.
+    /// The token is computed as `md5(password + salt)`.
+    /// ```rust
+    /// use subsonic_types::request::Authentication;
+    ///
+    /// let auth = Authentication::token("sesame", "c19b2d");
+    /// assert_eq!(
+    ///     Authentication::Token {
+    ///         token: "26719a1196d2a940705a59634eb18eab".into(),
+    ///         salt: "c19b2d".into(),
+    ///     },
+    ///     auth
+    /// );
+    /// ```
+    pub fn token(password: &str, salt: impl Into<String>) -> Self {
+        let salt = salt.into();
+        let token = format!("{:x}", md5::compute(format!("{}{}", password, salt)));
+        Self::Token { token, salt }
+    }
+
+    /// Token authentication with a freshly generated random salt.
+    /// Fails if the operating system can't provide random bytes.
+    pub fn random_token(password: &str) -> Result<Self, getrandom::Error> {
+        let mut bytes = [0u8; 8];
+        getrandom::getrandom(&mut bytes)?;
+        Ok(Self::token(password, hex::encode(&bytes)))
+    }
+
+    /// Checks if this authentication matches the given password.
+    /// Passwords prefixed with `enc:` are hex decoded first.
+    pub fn verify(&self, password: &str) -> bool {
+        match self {
+            Authentication::Password(p) => match p.strip_prefix("enc:") {
+                Some(hex) => hex::decode(hex).as_deref() == Some(password.as_bytes()),
+                None => p == password,
+            },
+            Authentication::Token { token, salt } => {
+                format!("{:x}", md5::compute(format!("{}{}", password, salt)))
+                    .eq_ignore_ascii_case(token)
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, ToQuery, FromQuery)]
 pub struct Request<R: SubsonicRequest> {
     #[query(rename = "u")]
@@ -163,6 +213,49 @@ pub struct Request<R: SubsonicRequest> {
     pub body: R,
 }
 
+impl<R: SubsonicRequest> Request<R> {
+    /// Creates a request with the same credentials, version, client and format but a different body.
+    pub fn with_body<B: SubsonicRequest>(&self, body: B) -> Request<B> {
+        Request {
+            username: self.username.clone(),
+            authentication: self.authentication.clone(),
+            version: self.version,
+            client: self.client.clone(),
+            format: self.format.clone(),
+            body,
+        }
+    }
+
+    /// Builds the full url for this request, `base_url` is the address of the server.
+    /// ```rust
+    /// use subsonic_types::{
+    ///     common::Version,
+    ///     request::{system::Ping, Authentication, Request},
+    /// };
+    ///
+    /// let request = Request {
+    ///     username: "admin".into(),
+    ///     authentication: Authentication::Password("admin".into()),
+    ///     version: Version::LATEST,
+    ///     client: "example".into(),
+    ///     format: None,
+    ///     body: Ping,
+    /// };
+    /// assert_eq!(
+    ///     "http://localhost:3000/rest/ping?u=admin&p=admin&v=1.16.1&c=example",
+    ///     request.to_url("http://localhost:3000")
+    /// );
+    /// ```
+    pub fn to_url(&self, base_url: &str) -> String {
+        format!(
+            "{}{}?{}",
+            base_url.trim_end_matches('/'),
+            R::PATH,
+            SubsonicRequest::to_query(self)
+        )
+    }
+}
+
 impl<R> SubsonicRequest for Request<R>
 where
     R: SubsonicRequest,
@@ -170,6 +263,8 @@ where
     const PATH: &'static str = R::PATH;
 
     const SINCE: Version = R::SINCE;
+
+    const MUTATING: bool = R::MUTATING;
 }
 
 impl ToQuery for Authentication {
@@ -262,6 +357,23 @@ mod tests {
         query
     }
 
+    #[test]
+    fn test_authentication() {
+        let auth = Authentication::token("sesame", "c19b2d");
+        assert!(auth.verify("sesame"));
+        assert!(!auth.verify("sesame2"));
+
+        let a = Authentication::random_token("sesame").unwrap();
+        let b = Authentication::random_token("sesame").unwrap();
+        assert_ne!(a, b);
+        assert!(a.verify("sesame"));
+        assert!(b.verify("sesame"));
+
+        assert!(Authentication::Password("sesame".into()).verify("sesame"));
+        assert!(Authentication::Password("enc:736573616d65".into()).verify("sesame"));
+        assert!(!Authentication::Password("enc:7365".into()).verify("sesame"));
+    }
+
     #[test]
     fn test_ping_request() {
         let req = Request {
@@ -275,4 +387,41 @@ mod tests {
         let query = test_request_encode(&req);
         assert_eq!(query, "u=user&p=password&v=1.16.1&c=test");
     }
+
+    #[test]
+    fn test_builders() {
+        let req = search::Search3::builder("query").song_count(50).build();
+        assert_eq!(test_request_encode(&req), "query=query&songCount=50");
+
+        let req = user::CreateUser::builder("bob", "sesame", "bob@example.com")
+            .admin_role(true)
+            .music_folder_id(1)
+            .music_folder_id(2)
+            .build();
+        assert_eq!(
+            test_request_encode(&req),
+            "username=bob&password=sesame&email=bob@example.com&adminRole&musicFolderId=1&musicFolderId=2"
+        );
+
+        let req = user::UpdateUser::builder("bob").email("bob@example.org").build();
+        assert_eq!(req.password, None);
+        assert_eq!(req.email.as_deref(), Some("bob@example.org"));
+
+        let req = lists::GetAlbumList2::builder(lists::ListType::ByYear)
+            .from_year(1990)
+            .to_year(2000)
+            .build();
+        assert_eq!(test_request_encode(&req), "type=byYear&fromYear=1990&toYear=2000");
+
+        let req = retrieval::Stream::builder("song")
+            .max_bit_rate(128)
+            .format("mp3")
+            .build();
+        assert_eq!(req, {
+            let mut stream = retrieval::Stream::builder("song").build();
+            stream.max_bit_rate = Some(128);
+            stream.format = Some("mp3".into());
+            stream
+        });
+    }
 }
@@ -1,12 +1,15 @@
 use serde::{Deserialize, Serialize};
 use subsonic_types_macro::{FromQuery, SubsonicRequest, ToQuery};
 
+use crate::common::{PlaylistId, SongId};
+
 /// Returns all playlists a user is allowed to play.
 ///
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#getPlaylists>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
 #[subsonic(since = "1.0.0", path = "getPlaylists")]
+#[non_exhaustive]
 pub struct GetPlaylists {
     /// Since 1.8.0
     /// If specified, return playlists for this user rather than for the authenticated user. The authenticated user must have admin role if this parameter is used.
@@ -21,7 +24,7 @@ pub struct GetPlaylists {
 #[subsonic(since = "1.0.0", path = "getPlaylist")]
 pub struct GetPlaylist {
     /// ID of the playlist to return, as obtained by [`GetPlaylists`].
-    pub id: String,
+    pub id: PlaylistId,
 }
 
 /// Creates (or updates) a playlist.
@@ -29,17 +32,18 @@ pub struct GetPlaylist {
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#createPlaylist>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.2.0", path = "createPlaylist")]
+#[subsonic(since = "1.2.0", path = "createPlaylist", mutating)]
+#[non_exhaustive]
 pub struct CreatePlaylist {
     /// The playlist ID.
     /// Required if updating.
-    pub paylist_id: Option<String>,
+    pub paylist_id: Option<PlaylistId>,
     /// The human-readable name of the playlist.
     /// Required if creating.
     pub name: Option<String>,
     /// The list of song IDs to include in the playlist.
     #[serde(default)]
-    pub song_id: Vec<String>,
+    pub song_id: Vec<SongId>,
 }
 
 /// Updates a playlist. Only the owner of a playlist is allowed to update it.
@@ -47,10 +51,11 @@ pub struct CreatePlaylist {
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#updatePlaylist>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.8.0", path = "updatePlaylist")]
+#[subsonic(since = "1.8.0", path = "updatePlaylist", mutating)]
+#[non_exhaustive]
 pub struct UpdatePlaylist {
     /// The playlist ID.
-    pub playlist_id: String,
+    pub playlist_id: PlaylistId,
     /// The human-readable name of the playlist.
     pub name: Option<String>,
     /// The playlist comment.
@@ -59,7 +64,7 @@ pub struct UpdatePlaylist {
     pub public: Option<bool>,
     /// Add this song with this ID to the playlist.
     #[serde(default)]
-    pub song_id_to_add: Vec<String>,
+    pub song_id_to_add: Vec<SongId>,
     /// Remove the song at this position in the playlist.
     #[serde(default)]
     pub song_index_to_remove: Vec<u32>,
@@ -70,8 +75,8 @@ pub struct UpdatePlaylist {
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#deletePlaylist>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.2.0", path = "deletePlaylist")]
+#[subsonic(since = "1.2.0", path = "deletePlaylist", mutating)]
 pub struct DeletePlaylist {
     /// ID of the playlist to delete, as obtained by [`GetPlaylists`].
-    pub id: String,
+    pub id: PlaylistId,
 }
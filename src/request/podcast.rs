@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use subsonic_types_macro::{FromQuery, SubsonicRequest, ToQuery};
 
+use crate::common::{PodcastChannelId, PodcastEpisodeId};
+
 /// Returns all Podcast channels the server subscribes to, and (optionally) their episodes.
 /// This method can also be used to return details for only one channel - refer to the id parameter.
 /// A typical use case for this method would be to first retrieve all channels without episodes, and then retrieve all episodes for the single channel the user selects.
@@ -9,13 +11,14 @@ use subsonic_types_macro::{FromQuery, SubsonicRequest, ToQuery};
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
 #[subsonic(since = "1.6.0", path = "getPodcasts")]
+#[non_exhaustive]
 pub struct GetPodcasts {
     /// Since 1.9.0
     /// Whether to include Podcast episodes in the returned result.
     pub include_episodes: Option<bool>,
     /// Since 1.9.0
     /// If specified, only return the Podcast channel with this ID.
-    pub id: Option<String>,
+    pub id: Option<PodcastChannelId>,
 }
 
 /// Returns the most recently published Podcast episodes.
@@ -24,6 +27,7 @@ pub struct GetPodcasts {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
 #[subsonic(since = "1.13.0", path = "getNewestPodcasts")]
+#[non_exhaustive]
 pub struct GetNewestPodcasts {
     /// The maximum number of episodes to return.
     pub count: Option<u32>,
@@ -35,7 +39,7 @@ pub struct GetNewestPodcasts {
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#refreshPodcasts>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.9.0", path = "refreshPodcasts")]
+#[subsonic(since = "1.9.0", path = "refreshPodcasts", mutating)]
 pub struct RefreshPodcasts;
 
 /// Adds a new Podcast channel.
@@ -44,7 +48,7 @@ pub struct RefreshPodcasts;
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#createPodcastChannel>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.9.0", path = "createPodcastChannel")]
+#[subsonic(since = "1.9.0", path = "createPodcastChannel", mutating)]
 pub struct CreatePodcastChannel {
     /// The URL of the Podcast to add.
     pub url: String,
@@ -56,10 +60,10 @@ pub struct CreatePodcastChannel {
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#deletePodcastChannel>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.9.0", path = "deletePodcastChannel")]
+#[subsonic(since = "1.9.0", path = "deletePodcastChannel", mutating)]
 pub struct DeletePodcastChannel {
     /// The ID of the Podcast channel to delete.
-    pub id: String,
+    pub id: PodcastChannelId,
 }
 
 /// Deletes a Podcast episode.
@@ -68,10 +72,10 @@ pub struct DeletePodcastChannel {
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#deletePodcastEpisode>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.9.0", path = "deletePodcastEpisode")]
+#[subsonic(since = "1.9.0", path = "deletePodcastEpisode", mutating)]
 pub struct DeletePodcastEpisode {
     /// The ID of the Podcast episode to delete.
-    pub id: String,
+    pub id: PodcastEpisodeId,
 }
 
 /// Request the server to start downloading a given Podcast episode.
@@ -80,8 +84,8 @@ pub struct DeletePodcastEpisode {
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#downloadPodcastEpisode>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.9.0", path = "downloadPodcastEpisode")]
+#[subsonic(since = "1.9.0", path = "downloadPodcastEpisode", mutating)]
 pub struct DownloadPodcastEpisode {
     /// The ID of the Podcast episode to download.
-    pub id: String,
+    pub id: PodcastEpisodeId,
 }
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use subsonic_types_macro::{FromQuery, SubsonicRequest, ToQuery};
 
+use crate::common::InternetRadioStationId;
+
 ///Returns all internet radio stations. Takes no extra parameters.
 ///
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#getInternetRadioStations>
@@ -15,7 +17,8 @@ pub struct GetInternetRadioStations;
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#createInternetRadioStation>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.16.0", path = "createInternetRadioStation")]
+#[subsonic(since = "1.16.0", path = "createInternetRadioStation", mutating)]
+#[non_exhaustive]
 pub struct CreateInternetRadioStation {
     /// The stream URL for the station.
     pub stream_url: String,
@@ -31,10 +34,11 @@ pub struct CreateInternetRadioStation {
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#updateInternetRadioStation>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.16.0", path = "updateInternetRadioStation")]
+#[subsonic(since = "1.16.0", path = "updateInternetRadioStation", mutating)]
+#[non_exhaustive]
 pub struct UpdateInternetRadioStation {
     /// The ID for the station.
-    pub id: String,
+    pub id: InternetRadioStationId,
     /// The stream URL for the station.
     pub stream_url: String,
     /// The user-defined name for the station.
@@ -49,8 +53,8 @@ pub struct UpdateInternetRadioStation {
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#deleteInternetRadioStation>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.16.0", path = "deleteInternetRadioStation")]
+#[subsonic(since = "1.16.0", path = "deleteInternetRadioStation", mutating)]
 pub struct DeleteInternetRadioStation {
     /// The ID for the station.
-    pub id: String,
+    pub id: InternetRadioStationId,
 }
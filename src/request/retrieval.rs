@@ -1,19 +1,18 @@
 use serde::{Deserialize, Serialize};
 use subsonic_types_macro::{FromQuery, SubsonicRequest, ToQuery};
 
-use crate::common::{VideoBitrate, VideoSize};
+use crate::common::{CoverArtId, Id, Seconds, SongId, Version, VideoBitrate, VideoSize};
 #[allow(unused)]
-use crate::{
-    common::Seconds,
-    request::browsing::{GetMusicDirectory, GetVideoInfo},
-};
+use crate::request::browsing::{GetMusicDirectory, GetVideoInfo};
+use crate::request::{Authentication, Request, SubsonicRequest};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
 #[subsonic(since = "1.0.0", path = "stream")]
+#[non_exhaustive]
 pub struct Stream {
     /// A string which uniquely identifies the file to stream. Obtained by calls to [`GetMusicDirectory`].
-    pub id: String,
+    pub id: SongId,
     /// Since 1.2.0
     /// If specified, the server will attempt to limit the bitrate to this value, in kilobits per second. If set to zero, no limit is imposed.
     pub max_bit_rate: Option<u32>,
@@ -48,7 +47,7 @@ pub struct Stream {
 pub struct Download {
     /// A string which uniquely identifies the file to download.
     /// Obtained by calls to [`GetMusicDirectory`].
-    pub id: String,
+    pub id: Id,
 }
 
 /// Creates an HLS (HTTP Live Streaming) playlist used for streaming video or audio.
@@ -60,9 +59,10 @@ pub struct Download {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
 #[subsonic(since = "1.8.0", path = "hls")]
+#[non_exhaustive]
 pub struct Hls {
     /// A string which uniquely identifies the file to stream. Obtained by calls to [`GetMusicDirectory`].
-    pub id: String,
+    pub id: SongId,
     /// If specified, the server will attempt to limit the bitrate to this value, in kilobits per second.
     /// If this parameter is specified more than once, the server will create a variant playlist, suitable for adaptive bitrate streaming.
     /// The playlist will support streaming at all the specified bitrates.
@@ -79,9 +79,10 @@ pub struct Hls {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
 #[subsonic(since = "1.14.0", path = "getCaptions")]
+#[non_exhaustive]
 pub struct GetCaptions {
     /// The ID of the video.
-    pub id: String,
+    pub id: SongId,
     /// Preferred captions format ("srt" or "vtt").
     pub format: Option<String>,
 }
@@ -92,11 +93,55 @@ pub struct GetCaptions {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
 #[subsonic(since = "1.0.0", path = "getCoverArt")]
+#[non_exhaustive]
 pub struct GetCoverArt {
     /// The ID of a song, album or artist.
-    pub id: String,
+    pub id: CoverArtId,
     /// If specified, scale image to this size.
-    pub size: Option<String>,
+    pub size: Option<u32>,
+}
+
+impl<R: SubsonicRequest> Request<R> {
+    /// Builds an authenticated `getCoverArt` url using the credentials, version and client of this request.
+    /// The `f` parameter is not included since the response is an image.
+    /// Useful for `<img>` tags and caches.
+    ///
+    /// # Example
+    /// ```rust
+    /// use subsonic_types::{
+    ///     common::Version,
+    ///     request::{system::Ping, Authentication, Request},
+    /// };
+    ///
+    /// let template = Request {
+    ///     username: "admin".into(),
+    ///     authentication: Authentication::Token {
+    ///         token: "26719a1196d2a940705a59634eb18eab".into(),
+    ///         salt: "c19b2d".into(),
+    ///     },
+    ///     version: Version::LATEST,
+    ///     client: "example".into(),
+    ///     format: Some("json".into()),
+    ///     body: Ping,
+    /// };
+    /// assert_eq!(
+    ///     "http://localhost:4533/rest/getCoverArt?u=admin&t=26719a1196d2a940705a59634eb18eab&s=c19b2d&v=1.16.1&c=example&id=al-123_0&size=300",
+    ///     template.cover_art_url("http://localhost:4533/", "al-123_0", Some(300)),
+    /// );
+    /// ```
+    pub fn cover_art_url(
+        &self,
+        base_url: &str,
+        id: impl Into<CoverArtId>,
+        size: Option<u32>,
+    ) -> String {
+        let mut request = self.with_body(GetCoverArt {
+            id: id.into(),
+            size,
+        });
+        request.format = None;
+        request.to_url(base_url)
+    }
 }
 
 /// Searches for and returns lyrics for a given song.
@@ -105,6 +150,7 @@ pub struct GetCoverArt {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
 #[subsonic(since = "1.2.0", path = "getLyrics")]
+#[non_exhaustive]
 pub struct GetLyrics {
     /// The artist name.
     pub artist: Option<String>,
@@ -122,3 +168,201 @@ pub struct GetAvatar {
     /// The user in question.
     pub username: String,
 }
+
+/// Error returned by [`Request::signed_url`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignedUrlError {
+    /// The request uses token authentication.
+    /// Every url is signed with a fresh salt, which requires the password.
+    PasswordRequired,
+    /// No random salt could be generated.
+    Random(getrandom::Error),
+}
+
+impl std::fmt::Display for SignedUrlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignedUrlError::PasswordRequired => {
+                write!(f, "Signing a url requires password authentication")
+            }
+            SignedUrlError::Random(err) => write!(f, "Failed to generate a salt: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for SignedUrlError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SignedUrlError::PasswordRequired => None,
+            SignedUrlError::Random(err) => Some(err),
+        }
+    }
+}
+
+/// Options for [`Request::signed_url`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StreamOptions {
+    /// Limit the bitrate to this value, in kilobits per second.
+    pub max_bit_rate: Option<u32>,
+    /// The preferred target format, e.g. "mp3" or "raw".
+    pub format: Option<String>,
+    /// Start streaming at the given offset into the media.
+    pub time_offset: Option<Seconds>,
+}
+
+/// Requests whose response is media data that can be handed to an external player.
+pub trait MediaRequest: SubsonicRequest {
+    /// Applies the options supported by this request, the others are ignored.
+    fn apply_options(&mut self, options: &StreamOptions);
+}
+
+impl MediaRequest for Stream {
+    fn apply_options(&mut self, options: &StreamOptions) {
+        if options.max_bit_rate.is_some() {
+            self.max_bit_rate = options.max_bit_rate;
+        }
+        if options.format.is_some() {
+            self.format = options.format.clone();
+        }
+        if options.time_offset.is_some() {
+            self.time_offset = options.time_offset;
+        }
+    }
+}
+
+impl MediaRequest for Download {
+    /// Downloads are never transcoded so all options are ignored.
+    fn apply_options(&mut self, _options: &StreamOptions) {}
+}
+
+impl MediaRequest for Hls {
+    fn apply_options(&mut self, options: &StreamOptions) {
+        if let Some(max_bit_rate) = options.max_bit_rate {
+            self.bit_rate = Some(VideoBitrate::without_size(max_bit_rate));
+        }
+    }
+}
+
+impl<R: MediaRequest> Request<R> {
+    /// Builds a complete url that can be handed to players that can't set headers, like mpv or an `<audio>` tag.
+    ///
+    /// If the version supports token authentication (1.13.0) a fresh salt and token are generated
+    /// for every url so the password is never embedded, older versions embed the password.
+    /// Requests using token authentication fail with [`SignedUrlError::PasswordRequired`] since
+    /// reusing their salt would make every url signed with it valid for as long as the password is.
+    /// The `f` parameter is omitted since the response is not json or xml.
+    ///
+    /// The returned url's query always parses back with [`SubsonicRequest::from_query`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use subsonic_types::{
+    ///     common::Version,
+    ///     request::{retrieval::{Stream, StreamOptions}, Authentication, Request, SubsonicRequest},
+    /// };
+    ///
+    /// let request = Request {
+    ///     username: "admin".into(),
+    ///     authentication: Authentication::Password("sesame".into()),
+    ///     version: Version::LATEST,
+    ///     client: "example".into(),
+    ///     format: Some("json".into()),
+    ///     body: Stream::builder("song 1").build(),
+    /// };
+    /// let options = StreamOptions {
+    ///     max_bit_rate: Some(128),
+    ///     ..Default::default()
+    /// };
+    /// let url = request.signed_url("http://localhost:4533", &options).unwrap();
+    /// assert!(!url.contains("sesame"));
+    ///
+    /// let (_, query) = url.split_once('?').unwrap();
+    /// let parsed = Request::<Stream>::from_query(query).unwrap();
+    /// assert!(parsed.authentication.verify("sesame"));
+    /// assert_eq!(parsed.body.id, "song 1");
+    /// assert_eq!(Some(128), parsed.body.max_bit_rate);
+    /// ```
+    pub fn signed_url(
+        &self,
+        base_url: &str,
+        options: &StreamOptions,
+    ) -> Result<String, SignedUrlError> {
+        let Authentication::Password(ref password) = self.authentication else {
+            return Err(SignedUrlError::PasswordRequired);
+        };
+        let mut request = self.clone();
+        request.body.apply_options(options);
+        request.format = None;
+        if self.version >= Version::V1_13_0 {
+            request.authentication =
+                Authentication::random_token(password).map_err(SignedUrlError::Random)?;
+        }
+        Ok(request.to_url(base_url))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request<R: SubsonicRequest>(version: Version, body: R) -> Request<R> {
+        Request {
+            authentication: Authentication::Password("sesame".into()),
+            version,
+            format: Some("json".into()),
+            ..crate::test_support::request(body)
+        }
+    }
+
+    fn parse<R: SubsonicRequest>(url: &str) -> Request<R> {
+        let (path, query) = url.split_once('?').unwrap();
+        assert!(path.ends_with(R::PATH), "{}", path);
+        Request::<R>::from_query(query).unwrap()
+    }
+
+    #[test]
+    fn test_signed_url() {
+        let id = "a&b=c+d%20e f#g?h/i";
+        let req = request(Version::LATEST, Stream::builder(id).build());
+        let options = StreamOptions {
+            max_bit_rate: Some(320),
+            format: Some("mp3".into()),
+            time_offset: Some(Seconds::new(30)),
+        };
+        let url1 = req.signed_url("http://localhost:4533/", &options).unwrap();
+        let url2 = req.signed_url("http://localhost:4533/", &options).unwrap();
+        assert_ne!(url1, url2);
+        assert!(!url1.contains("sesame"));
+        assert!(!url1.contains("f=json"));
+
+        let parsed = parse::<Stream>(&url1);
+        assert!(parsed.authentication.verify("sesame"));
+        assert_eq!(parsed.format, None);
+        assert_eq!(parsed.body.id, id);
+        assert_eq!(parsed.body.max_bit_rate, Some(320));
+        assert_eq!(parsed.body.format.as_deref(), Some("mp3"));
+        assert_eq!(parsed.body.time_offset, Some(Seconds::new(30)));
+
+        let parsed = parse::<Download>(
+            &request(Version::LATEST, Download { id: id.into() })
+                .signed_url("http://localhost", &options)
+                .unwrap(),
+        );
+        assert!(parsed.authentication.verify("sesame"));
+        assert_eq!(parsed.body.id, id);
+
+        // a token can't be refreshed without the password
+        let mut req = request(Version::LATEST, Download { id: id.into() });
+        req.authentication = Authentication::token("sesame", "abcdef");
+        assert_eq!(
+            Err(SignedUrlError::PasswordRequired),
+            req.signed_url("http://localhost", &options)
+        );
+
+        // token authentication is not available before 1.13.0
+        let req = request(Version::V1_12_0, Hls::builder(id).build());
+        let parsed = parse::<Hls>(&req.signed_url("http://localhost", &options).unwrap());
+        assert_eq!(parsed.authentication, req.authentication);
+        assert_eq!(parsed.body.bit_rate, Some(VideoBitrate::without_size(320)));
+    }
+}
@@ -14,5 +14,5 @@ pub struct GetScanStatus;
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#startScan>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.15.0", path = "startScan")]
+#[subsonic(since = "1.15.0", path = "startScan", mutating)]
 pub struct StartScan;
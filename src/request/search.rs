@@ -2,7 +2,10 @@ use serde::{Deserialize, Serialize};
 use subsonic_types_macro::{FromQuery, SubsonicRequest, ToQuery};
 
 #[allow(unused)]
-use crate::{common::Milliseconds, request::browsing::GetMusicFolders};
+use crate::{
+    common::{Milliseconds, MusicFolderId},
+    request::browsing::GetMusicFolders,
+};
 
 /// Returns a listing of files matching the given search criteria. Supports paging through the result.
 ///
@@ -10,6 +13,7 @@ use crate::{common::Milliseconds, request::browsing::GetMusicFolders};
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
 #[subsonic(since = "1.0.0", path = "search")]
+#[non_exhaustive]
 pub struct Search {
     /// Artist to search for.
     pub artist: Option<String>,
@@ -34,6 +38,7 @@ pub struct Search {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
 #[subsonic(since = "1.4.0", path = "search2")]
+#[non_exhaustive]
 pub struct Search2 {
     /// Search query.
     pub query: String,
@@ -51,7 +56,7 @@ pub struct Search2 {
     pub song_offset: Option<u32>,
     /// Since 1.12.0
     /// Only return results from the music folder with the given ID. See [`GetMusicFolders`].
-    pub music_folder_id: Option<String>,
+    pub music_folder_id: Option<MusicFolderId>,
 }
 
 /// Similar to [`Search2`], but organizes music according to ID3 tags.
@@ -60,6 +65,7 @@ pub struct Search2 {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
 #[subsonic(since = "1.8.0", path = "search3")]
+#[non_exhaustive]
 pub struct Search3 {
     /// Search query.
     pub query: String,
@@ -77,5 +83,5 @@ pub struct Search3 {
     pub song_offset: Option<u32>,
     /// Since 1.12.0
     /// Only return results from the music folder with the given ID. See [`GetMusicFolders`].
-    pub music_folder_id: Option<String>,
+    pub music_folder_id: Option<MusicFolderId>,
 }
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use subsonic_types_macro::{FromQuery, SubsonicRequest, ToQuery};
 
-use crate::common::Milliseconds;
+use crate::common::{Id, Milliseconds, ShareId};
 
 /// Returns information about shared media this user is allowed to manage. Takes no extra parameters.
 ///
@@ -18,11 +18,12 @@ pub struct GetShares;
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#createShare>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.6.0", path = "createShare")]
+#[subsonic(since = "1.6.0", path = "createShare", mutating)]
+#[non_exhaustive]
 pub struct CreateShare {
     /// ID of a song, album or video to share. Use one id parameter for each entry to share
     #[serde(default)]
-    pub id: Vec<String>,
+    pub id: Vec<Id>,
     /// A user-defined description that will be displayed to people visiting the shared media.
     pub description: Option<String>,
     /// The time at which the share expires.
@@ -34,10 +35,11 @@ pub struct CreateShare {
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#updateShare>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.6.0", path = "updateShare")]
+#[subsonic(since = "1.6.0", path = "updateShare", mutating)]
+#[non_exhaustive]
 pub struct UpdateShare {
     /// ID of the share to update.
-    pub id: String,
+    pub id: ShareId,
     /// A user-defined description that will be displayed to people visiting the shared media.
     pub description: Option<String>,
     /// The time at which the share expires. Set to zero to remove the expiration.
@@ -49,8 +51,8 @@ pub struct UpdateShare {
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#deleteShare>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.6.0", path = "deleteShare")]
+#[subsonic(since = "1.6.0", path = "deleteShare", mutating)]
 pub struct DeleteShare {
     /// ID of the share to delete.
-    pub id: String,
+    pub id: ShareId,
 }
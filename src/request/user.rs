@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use subsonic_types_macro::{FromQuery, SubsonicRequest, ToQuery};
 
-use crate::common::AudioBitrate;
+use crate::common::{AudioBitrate, MusicFolderId};
 
 /// Get details about a given user, including which authorization roles and folder access it has.
 /// Can be used to enable/disable certain features in the client, such as jukebox control.
@@ -30,7 +30,8 @@ pub struct GetUsers;
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#createUser>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.1.0", path = "createUser")]
+#[subsonic(since = "1.1.0", path = "createUser", mutating)]
+#[non_exhaustive]
 pub struct CreateUser {
     /// The name of the new user.
     pub username: String,
@@ -69,7 +70,7 @@ pub struct CreateUser {
     /// Since 1.12.0
     /// IDs of the music folders the user is allowed access to. Include the parameter once for each folder.
     #[serde(default)]
-    pub music_folder_id: Vec<String>,
+    pub music_folder_id: Vec<MusicFolderId>,
 }
 
 ///  Modifies an existing Subsonic user, using the following parameters.
@@ -77,7 +78,8 @@ pub struct CreateUser {
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#updateUser>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.10.1", path = "updateUser")]
+#[subsonic(since = "1.10.1", path = "updateUser", mutating)]
+#[non_exhaustive]
 pub struct UpdateUser {
     /// The name of the user.
     pub username: String,
@@ -113,7 +115,7 @@ pub struct UpdateUser {
     /// Since 1.12.0
     /// IDs of the music folders the user is allowed access to.
     #[serde(default)]
-    pub music_folder_id: Vec<String>,
+    pub music_folder_id: Vec<MusicFolderId>,
     /// Since 1.13.0
     /// he maximum bit rate (in Kbps) for the user.
     /// Audio streams of higher bit rates are automatically downsampled to this bit rate.
@@ -126,7 +128,7 @@ pub struct UpdateUser {
 /// For more information, see <http://www.subsonic.org/pages/api.jsp#deleteUser>
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.3.0", path = "deleteUser")]
+#[subsonic(since = "1.3.0", path = "deleteUser", mutating)]
 pub struct DeleteUser {
     /// The name of the user to delete.
     pub username: String,
@@ -136,7 +138,7 @@ pub struct DeleteUser {
 /// You can only change your own password unless you have admin privileges.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToQuery, FromQuery, SubsonicRequest)]
 #[serde(rename_all = "camelCase")]
-#[subsonic(since = "1.1.0", path = "changePassword")]
+#[subsonic(since = "1.1.0", path = "changePassword", mutating)]
 pub struct ChangePassword {
     /// The name of the user which should change its password.
     pub username: String,
@@ -20,6 +20,14 @@
 //! # }
 //! ```
 //!
+//! Building a response with the constructor of its body:
+//! ```
+//!     use subsonic_types::{common::Version, response::{Response, ResponseBody, Child}};
+//!     let song = Child::builder("1", "Song").artist("Artist").build();
+//!     let response = Response::song(Version::V1_16_1, song.clone());
+//!     assert_eq!(Response::ok(Version::V1_16_1, ResponseBody::Song(song)), response);
+//! ```
+//!
 //! Parsing a response:
 //! Deserialize a response from json
 //! ```
@@ -46,10 +54,14 @@ use serde::{Deserialize, Serialize};
 use subsonic_types_macro::SubsonicType;
 
 use crate::{
+    capabilities::{FieldInfo, SubsonicFields},
     common::{
-        AverageRating, DateTime, Format, MediaType, Milliseconds, Seconds, UserRating, Version,
+        AlbumId, ArtistId, AverageRating, CoverArtId, DateTime, DirectoryId, Format,
+        InternetRadioStationId, MediaType, Milliseconds, MusicFolderId, PlaylistId,
+        PodcastChannelId, PodcastEpisodeId, Seconds, ShareId, SongId, UserRating, Version,
     },
     deser::{SubsonicDeserialize, SubsonicSerialize, SubsonicSerializeWrapper},
+    visit::IdKind,
 };
 
 #[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, SubsonicType)]
@@ -95,6 +107,36 @@ impl Response {
         }
     }
 
+    /// Convert this response so it can be sent to a client speaking `target`.
+    ///
+    /// Bodies that were introduced after `target` are converted to their older equivalent
+    /// when one exists, e.g. [`SearchResult3`] becomes [`SearchResult2`] and [`AlbumList2`]
+    /// becomes [`AlbumList`]. Otherwise [`Incompatible`] is returned.
+    /// The version of the returned response is set to `target`.
+    ///
+    /// # Example
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     use subsonic_types::{common::Version, response::{Response, ResponseBody, AlbumList2, ScanStatus}};
+    ///     let response = Response::ok(Version::V1_16_1, ResponseBody::AlbumList2(AlbumList2::default()));
+    ///     let downgraded = response.downgrade(Version::V1_2_0)?;
+    ///     assert!(matches!(downgraded.body, ResponseBody::AlbumList(_)));
+    ///     assert_eq!(Version::V1_2_0, downgraded.version);
+    ///
+    ///     let response = Response::ok(Version::V1_16_1, ResponseBody::ScanStatus(ScanStatus::default()));
+    ///     assert!(response.downgrade(Version::V1_10_2).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn downgrade(self, target: Version) -> Result<Self, Incompatible> {
+        let body = self.body.downgrade(target)?;
+        Ok(Self {
+            status: self.status,
+            version: target,
+            body,
+        })
+    }
+
     /// Serialize a response to json
     /// ```
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -277,13 +319,60 @@ impl Response {
     /// Same as [`Response::to_xml`] but allows specifying the api version.
     /// See [`Response::from_json_versioned`] for an example.
     pub fn to_xml_versioned(&self, version: Version) -> Result<String, Error> {
+        self.to_xml_versioned_with(version, XmlOptions::default())
+    }
+
+    /// Same as [`Response::to_xml`] but allows specifying [`XmlOptions`].
+    ///
+    /// # Example
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     use subsonic_types::{common::Version, response::{Response, ResponseBody, XmlOptions}};
+    ///     let response = Response::ok(Version::V1_16_1, ResponseBody::Empty);
+    ///     assert_eq!(
+    ///         concat!(
+    ///             r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+    ///             r#"<subsonic-response xmlns="http://subsonic.org/restapi" status="ok" version="1.16.1"/>"#
+    ///         ),
+    ///         response.to_xml_with(XmlOptions::spec())?
+    ///     );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_xml_with(&self, options: XmlOptions) -> Result<String, Error> {
+        self.to_xml_versioned_with(self.version, options)
+    }
+
+    /// Same as [`Response::to_xml_versioned`] but allows specifying [`XmlOptions`].
+    pub fn to_xml_versioned_with(
+        &self,
+        version: Version,
+        options: XmlOptions,
+    ) -> Result<String, Error> {
         let mut response = String::new();
         let serializer =
             quick_xml::se::Serializer::with_root(&mut response, Some("subsonic-response"))
                 .map_err(Error::custom)?;
         <Self as SubsonicSerialize>::serialize(self, serializer, Format::Xml, version)
             .map_err(Error::custom)?;
-        Ok(response)
+
+        let mut output = String::with_capacity(response.len() + 128);
+        if options.prolog {
+            output.push_str(XML_PROLOG);
+        }
+        let mut response = response.as_str();
+        if options.namespace {
+            const ROOT: &str = "<subsonic-response";
+            if let Some(rest) = response.strip_prefix(ROOT) {
+                output.push_str(ROOT);
+                output.push_str(" xmlns=\"");
+                output.push_str(XML_NAMESPACE);
+                output.push('"');
+                response = rest;
+            }
+        }
+        escape_xml_control_chars(response, &mut output);
+        Ok(output)
     }
 
     /// Deserialize a response from xml
@@ -319,8 +408,298 @@ impl Response {
             .map_err(Error::custom)?;
         Ok(response)
     }
+
+    /// Parse a response without knowing its format.
+    ///
+    /// The payload is sniffed: xml starts with `<`, json with `{` and jsonp is a json object wrapped
+    /// in `callback(...)`. Byte order marks are handled and xml documents may use any encoding declared
+    /// in their prolog. The `content_type` is used to detect the charset and as a fallback for the format.
+    /// Returns the detected format, jsonp is reported as [`Format::Json`].
+    ///
+    /// # Example
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     use subsonic_types::{common::{Format, Version}, response::{Response, ResponseBody}};
+    ///     let expected = Response::ok(Version::V1_16_1, ResponseBody::Empty);
+    ///
+    ///     let xml = br#"<?xml version="1.0" encoding="UTF-8"?><subsonic-response status="ok" version="1.16.1"/>"#;
+    ///     assert_eq!((expected.clone(), Format::Xml), Response::from_bytes(xml, None)?);
+    ///
+    ///     let jsonp = br#"callback({"subsonic-response":{"status":"ok","version":"1.16.1"}});"#;
+    ///     assert_eq!((expected, Format::Json), Response::from_bytes(jsonp, Some("text/javascript"))?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_bytes(bytes: &[u8], content_type: Option<&str>) -> Result<(Self, Format), Error> {
+        Self::from_bytes_versioned(bytes, content_type, Version::LATEST)
+    }
+
+    /// Same as [`Response::from_bytes`] but allows specifying the api version.
+    pub fn from_bytes_versioned(
+        bytes: &[u8],
+        content_type: Option<&str>,
+        version: Version,
+    ) -> Result<(Self, Format), Error> {
+        let content = sniff::decode(bytes, content_type)?;
+        let content = content.trim_start();
+        if content.starts_with('<') {
+            let response = Self::from_xml_versioned(content, version)?;
+            return Ok((response, Format::Xml));
+        }
+        if content.starts_with('{') {
+            let response = Self::from_json_versioned(content, version)?;
+            return Ok((response, Format::Json));
+        }
+        if let Some(content) = sniff::strip_jsonp(content) {
+            let response = Self::from_json_versioned(content, version)?;
+            return Ok((response, Format::Json));
+        }
+        match content_type.map(|c| c.to_ascii_lowercase()) {
+            Some(c) if c.contains("json") => {
+                Ok((Self::from_json_versioned(content, version)?, Format::Json))
+            }
+            Some(c) if c.contains("xml") => {
+                Ok((Self::from_xml_versioned(content, version)?, Format::Xml))
+            }
+            _ => Err(Error::with_message(
+                ErrorCode::Generic,
+                "unable to detect the response format",
+            )),
+        }
+    }
+}
+
+/// Helpers for [`Response::from_bytes`].
+mod sniff {
+    use std::borrow::Cow;
+
+    use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+
+    use super::{Error, ErrorCode};
+
+    /// Decodes the payload into a string.
+    /// The encoding is taken from the byte order mark, the xml prolog or the content type, in that order.
+    pub fn decode<'a>(bytes: &'a [u8], content_type: Option<&str>) -> Result<Cow<'a, str>, Error> {
+        let (encoding, bytes) = match Encoding::for_bom(bytes) {
+            Some((encoding, bom_length)) => (encoding, &bytes[bom_length..]),
+            None => {
+                let encoding = match bytes {
+                    // utf-16 without a byte order mark
+                    [b'<', 0, ..] => UTF_16LE,
+                    [0, b'<', ..] => UTF_16BE,
+                    _ => prolog_encoding(bytes)
+                        .or_else(|| content_type.and_then(charset))
+                        .unwrap_or(UTF_8),
+                };
+                (encoding, bytes)
+            }
+        };
+        let (content, malformed) = encoding.decode_without_bom_handling(bytes);
+        if malformed {
+            return Err(Error::with_message(
+                ErrorCode::Generic,
+                format!("invalid {} content", encoding.name()),
+            ));
+        }
+        Ok(content)
+    }
+
+    /// The encoding declared in an xml prolog like `<?xml version="1.0" encoding="ISO-8859-1"?>`.
+    fn prolog_encoding(bytes: &[u8]) -> Option<&'static Encoding> {
+        let start = bytes.iter().position(|b| !b.is_ascii_whitespace())?;
+        let bytes = &bytes[start..];
+        if !bytes.starts_with(b"<?xml") {
+            return None;
+        }
+        let end = bytes.iter().position(|&b| b == b'>')?;
+        let prolog = std::str::from_utf8(&bytes[..end]).ok()?;
+        let (_, rest) = prolog.split_once("encoding")?;
+        let rest = rest.trim_start().strip_prefix('=')?.trim_start();
+        let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let (label, _) = rest[1..].split_once(quote)?;
+        Encoding::for_label(label.as_bytes())
+    }
+
+    /// The encoding in a content type like `text/xml; charset=ISO-8859-1`.
+    fn charset(content_type: &str) -> Option<&'static Encoding> {
+        content_type.split(';').skip(1).find_map(|param| {
+            let (key, value) = param.split_once('=')?;
+            if !key.trim().eq_ignore_ascii_case("charset") {
+                return None;
+            }
+            Encoding::for_label(value.trim().trim_matches('"').as_bytes())
+        })
+    }
+
+    /// Strips the `callback(...)` wrapper of a jsonp payload.
+    pub fn strip_jsonp(content: &str) -> Option<&str> {
+        let content = content.trim_start_matches("/**/").trim_start();
+        let (callback, rest) = content.split_once('(')?;
+        let callback = callback.trim_end();
+        let is_identifier = !callback.is_empty()
+            && callback
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$' || c == '.');
+        if !is_identifier {
+            return None;
+        }
+        let rest = rest.trim_end();
+        let rest = rest.strip_suffix(';').unwrap_or(rest).trim_end();
+        let json = rest.strip_suffix(')')?.trim();
+        json.starts_with('{').then_some(json)
+    }
+}
+
+/// The namespace of the Subsonic API xml schema.
+pub const XML_NAMESPACE: &str = "http://subsonic.org/restapi";
+
+const XML_PROLOG: &str = r#"<?xml version="1.0" encoding="UTF-8"?>"#;
+
+/// Options for [`Response::to_xml_with`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct XmlOptions {
+    /// Add `xmlns="http://subsonic.org/restapi"` to the root element.
+    pub namespace: bool,
+    /// Start the document with `<?xml version="1.0" encoding="UTF-8"?>`.
+    pub prolog: bool,
+}
+
+impl XmlOptions {
+    /// Options matching the documents returned by the reference Subsonic server.
+    pub const fn spec() -> Self {
+        Self {
+            namespace: true,
+            prolog: true,
+        }
+    }
 }
 
+/// Copies serialized xml into `output` escaping control characters.
+/// Tabs, newlines and carriage returns inside attribute values are written as character references
+/// so that parsers do not normalize them to spaces. Characters that are not allowed in xml 1.0 are
+/// replaced by U+FFFD.
+fn escape_xml_control_chars(xml: &str, output: &mut String) {
+    let mut in_tag = false;
+    let mut quote = None;
+    for c in xml.chars() {
+        match c {
+            '\t' | '\n' | '\r' if quote.is_some() => {
+                output.push_str(match c {
+                    '\t' => "&#9;",
+                    '\n' => "&#10;",
+                    _ => "&#13;",
+                });
+                continue;
+            }
+            '\t' | '\n' | '\r' => {}
+            '\u{0}'..='\u{1f}' | '\u{fffe}' | '\u{ffff}' => {
+                output.push('\u{fffd}');
+                continue;
+            }
+            '"' | '\'' if in_tag => match quote {
+                None => quote = Some(c),
+                Some(q) if q == c => quote = None,
+                Some(_) => {}
+            },
+            '<' if quote.is_none() => in_tag = true,
+            '>' if quote.is_none() => in_tag = false,
+            _ => {}
+        }
+        output.push(c);
+    }
+}
+
+/// Calls `$callback!` with the `(constructor Variant(Type) => Request)` list of every
+/// [`ResponseBody`] variant except `Empty` and `Error`, where `Request` is the request introducing
+/// the body. The request modules must be in scope where the list is used.
+///
+/// [`ResponseBody::since`] matches exhaustively on this list, so a missing variant does not compile.
+macro_rules! response_bodies {
+    ($callback:ident) => {
+        $callback!(
+            (music_folders MusicFolders(MusicFolders) => browsing::GetMusicFolders),
+            (indexes Indexes(Indexes) => browsing::GetIndexes),
+            (directory Directory(Directory) => browsing::GetMusicDirectory),
+            (genres Genres(Genres) => browsing::GetGenres),
+            (artists Artists(ArtistsID3) => browsing::GetArtists),
+            (artist Artist(ArtistWithAlbumsID3) => browsing::GetArtist),
+            (album Album(AlbumWithSongsID3) => browsing::GetAlbum),
+            (song Song(Child) => browsing::GetSong),
+            (videos Videos(Videos) => browsing::GetVideos),
+            (video_info VideoInfo(VideoInfo) => browsing::GetVideoInfo),
+            (now_playing NowPlaying(NowPlaying) => lists::GetNowPlaying),
+            (search_result SearchResult(SearchResult) => search::Search),
+            (search_result2 SearchResult2(SearchResult2) => search::Search2),
+            (search_result3 SearchResult3(SearchResult3) => search::Search3),
+            (playlists Playlists(Playlists) => playlists::GetPlaylists),
+            (playlist Playlist(PlaylistWithSongs) => playlists::GetPlaylist),
+            (jukebox_status JukeboxStatus(JukeboxStatus) => jukebox::JukeboxControl),
+            (jukebox_playlist JukeboxPlaylist(JukeboxPlaylist) => jukebox::JukeboxControl),
+            (license License(License) => system::GetLicense),
+            (users Users(Users) => user::GetUsers),
+            (user User(User) => user::GetUser),
+            (chat_messages ChatMessages(ChatMessages) => chat::GetChatMessages),
+            (album_list AlbumList(AlbumList) => lists::GetAlbumList),
+            (album_list2 AlbumList2(AlbumList2) => lists::GetAlbumList2),
+            (random_songs RandomSongs(Songs) => lists::GetRandomSongs),
+            (songs_by_genre SongsByGenre(Songs) => lists::GetSongsByGenre),
+            (lyrics Lyrics(Lyrics) => retrieval::GetLyrics),
+            (podcasts Podcasts(Podcasts) => podcast::GetPodcasts),
+            (newest_podcasts NewestPodcasts(NewestPodcasts) => podcast::GetNewestPodcasts),
+            (internet_radio_stations InternetRadioStations(InternetRadioStations) => radio::GetInternetRadioStations),
+            (bookmarks Bookmarks(Bookmarks) => bookmark::GetBookmarks),
+            (play_queue PlayQueue(PlayQueue) => bookmark::GetPlayQueue),
+            (shares Shares(Shares) => sharing::GetShares),
+            (starred Starred(Starred) => lists::GetStarred),
+            (starred2 Starred2(Starred2) => lists::GetStarred2),
+            (album_info AlbumInfo(AlbumInfo) => browsing::GetAlbumInfo),
+            (artist_info ArtistInfo(ArtistInfo) => browsing::GetArtistInfo),
+            (artist_info2 ArtistInfo2(ArtistInfo2) => browsing::GetArtistInfo2),
+            (similar_songs SimilarSongs(SimilarSongs) => browsing::GetSimilarSongs),
+            (similar_songs2 SimilarSongs2(SimilarSongs2) => browsing::GetSimilarSongs2),
+            (top_songs TopSongs(TopSongs) => browsing::GetTopSongs),
+            (scan_status ScanStatus(ScanStatus) => scan::GetScanStatus)
+        );
+    };
+}
+pub(crate) use response_bodies;
+
+macro_rules! impl_body_constructors {
+    ($(($name:ident $variant:ident($ty:ty) => $request:path)),*) => {
+        impl Response {
+            $(
+                #[doc = concat!("Creates a successful response with a [`ResponseBody::", stringify!($variant), "`] body.")]
+                pub fn $name(version: Version, $name: impl Into<$ty>) -> Self {
+                    Self::ok(version, ResponseBody::$variant($name.into()))
+                }
+            )*
+        }
+    };
+}
+
+response_bodies!(impl_body_constructors);
+
+macro_rules! impl_body_since {
+    ($(($name:ident $variant:ident($ty:ty) => $request:path)),*) => {
+        impl ResponseBody {
+            /// The version since this body can be returned by a server.
+            pub fn since(&self) -> Version {
+                use crate::request::{
+                    bookmark, browsing, chat, jukebox, lists, playlists, podcast, radio, retrieval,
+                    scan, search, sharing, system, user, SubsonicRequest,
+                };
+
+                match self {
+                    ResponseBody::Empty | ResponseBody::Error(_) => system::Ping::SINCE,
+                    $(ResponseBody::$variant(_) => <$request as SubsonicRequest>::SINCE,)*
+                }
+            }
+        }
+    };
+}
+
+response_bodies!(impl_body_since);
+
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, PartialEq)]
 pub enum ResponseBody {
@@ -370,6 +749,50 @@ pub enum ResponseBody {
     Error(Error),
 }
 
+impl ResponseBody {
+    /// Convert this body to one that exists in `target`.
+    /// See [`Response::downgrade`].
+    pub fn downgrade(self, target: Version) -> Result<Self, Incompatible> {
+        if target >= self.since() {
+            return Ok(self);
+        }
+
+        let body = match self {
+            ResponseBody::SearchResult3(v) => ResponseBody::SearchResult2(v.into()),
+            ResponseBody::AlbumList2(v) => ResponseBody::AlbumList(v.into()),
+            ResponseBody::Starred2(v) => ResponseBody::Starred(v.into()),
+            body => {
+                return Err(Incompatible {
+                    since: body.since(),
+                    target,
+                })
+            }
+        };
+        body.downgrade(target)
+    }
+}
+
+/// Error returned when a response body can not be represented in the target version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Incompatible {
+    /// The version since the body is available.
+    pub since: Version,
+    /// The version the body was being converted to.
+    pub target: Version,
+}
+
+impl std::fmt::Display for Incompatible {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "response body requires version {} but the target version is {}",
+            self.since, self.target
+        )
+    }
+}
+
+impl std::error::Error for Incompatible {}
+
 const _: () = {
     impl SubsonicSerialize for ResponseBody {
         fn serialize<S>(
@@ -432,7 +855,7 @@ const _: () = {
                 }
                 ResponseBody::SearchResult(v) => {
                     map.serialize_entry(
-                        "searchResult2",
+                        "searchResult",
                         &SubsonicSerializeWrapper(v, format, version),
                     )?;
                 }
@@ -901,6 +1324,10 @@ const _: () = {
     impl<'de> SubsonicDeserialize<'de> for ResponseBody {
         type Seed = ResponseBodySeed;
     }
+
+    impl SubsonicFields for ResponseBody {
+        const FIELDS: &'static [FieldInfo] = &[];
+    }
 };
 
 #[derive(Debug, Default, Clone, PartialEq, SubsonicType)]
@@ -923,7 +1350,7 @@ pub struct MusicFolders {
 #[derive(Debug, Default, Clone, PartialEq, SubsonicType)]
 pub struct MusicFolder {
     #[subsonic(attribute)]
-    pub id: u32,
+    pub id: MusicFolderId,
     #[subsonic(attribute)]
     pub name: Option<String>,
 }
@@ -951,7 +1378,7 @@ pub struct Index {
 #[derive(Debug, Default, Clone, PartialEq, SubsonicType)]
 pub struct Artist {
     #[subsonic(attribute)]
-    pub id: String,
+    pub id: DirectoryId,
     #[subsonic(attribute)]
     pub name: String,
     #[subsonic(attribute, since = "1.16.1")]
@@ -994,13 +1421,14 @@ pub struct IndexID3 {
 }
 
 #[derive(Debug, Default, Clone, PartialEq, SubsonicType)]
+#[subsonic(visit = "artist_id3")]
 pub struct ArtistID3 {
     #[subsonic(attribute)]
-    pub id: String,
+    pub id: ArtistId,
     #[subsonic(attribute)]
     pub name: String,
     #[subsonic(attribute)]
-    pub cover_art: Option<String>,
+    pub cover_art: Option<CoverArtId>,
     #[subsonic(attribute, since = "1.16.1")]
     pub artist_image_url: Option<String>,
     #[subsonic(attribute)]
@@ -1009,6 +1437,18 @@ pub struct ArtistID3 {
     pub starred: Option<DateTime>,
 }
 
+impl From<ArtistID3> for Artist {
+    fn from(value: ArtistID3) -> Self {
+        Self {
+            id: DirectoryId::from(String::from(value.id)),
+            name: value.name,
+            artist_image_url: value.artist_image_url,
+            starred: value.starred,
+            ..Default::default()
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq, SubsonicType)]
 pub struct ArtistWithAlbumsID3 {
     #[subsonic(flatten)]
@@ -1017,17 +1457,18 @@ pub struct ArtistWithAlbumsID3 {
 }
 
 #[derive(Debug, Default, Clone, PartialEq, SubsonicType)]
+#[subsonic(builder, visit = "album_id3")]
 pub struct AlbumID3 {
     #[subsonic(attribute)]
-    pub id: String,
+    pub id: AlbumId,
     #[subsonic(attribute)]
     pub name: String,
     #[subsonic(attribute)]
     pub artist: Option<String>,
     #[subsonic(attribute)]
-    pub artist_id: Option<String>,
+    pub artist_id: Option<ArtistId>,
     #[subsonic(attribute)]
-    pub cover_art: Option<String>,
+    pub cover_art: Option<CoverArtId>,
     #[subsonic(attribute)]
     pub song_count: u32,
     #[subsonic(attribute)]
@@ -1044,6 +1485,30 @@ pub struct AlbumID3 {
     pub genre: Option<String>,
 }
 
+/// Converts an album to the directory-based representation used by [`AlbumList`],
+/// [`SearchResult2`] and [`Starred`].
+impl From<AlbumID3> for Child {
+    fn from(value: AlbumID3) -> Self {
+        Self {
+            id: SongId::from(String::from(value.id)),
+            parent: value.artist_id.clone().map(|id| DirectoryId::from(String::from(id))),
+            is_dir: true,
+            title: value.name.clone(),
+            album: Some(value.name),
+            artist: value.artist,
+            year: value.year,
+            genre: value.genre,
+            cover_art: value.cover_art,
+            duration: Some(Seconds::new(value.duration as u64)),
+            play_count: value.play_count,
+            created: value.created,
+            starred: value.starred,
+            artist_id: value.artist_id,
+            ..Default::default()
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq, SubsonicType)]
 pub struct AlbumWithSongsID3 {
     #[subsonic(flatten)]
@@ -1059,7 +1524,7 @@ pub struct Videos {
 #[derive(Debug, Default, Clone, PartialEq, SubsonicType)]
 pub struct VideoInfo {
     #[subsonic(attribute)]
-    pub id: String,
+    pub id: SongId,
     pub captions: Vec<Captions>,
     pub audio_track: Vec<AudioTrack>,
     pub conversion: Vec<VideoConversion>,
@@ -1070,7 +1535,7 @@ pub struct Captions {
     #[subsonic(attribute)]
     pub id: String,
     #[subsonic(attribute)]
-    pub format: Option<String>,
+    pub name: Option<String>,
 }
 
 #[derive(Debug, Default, Clone, PartialEq, SubsonicType)]
@@ -1096,9 +1561,9 @@ pub struct VideoConversion {
 #[derive(Debug, Default, Clone, PartialEq, SubsonicType)]
 pub struct Directory {
     #[subsonic(attribute)]
-    pub id: String,
+    pub id: DirectoryId,
     #[subsonic(attribute)]
-    pub parent: Option<String>,
+    pub parent: Option<DirectoryId>,
     #[subsonic(attribute)]
     pub name: String,
     #[subsonic(attribute, since = "1.10.1")]
@@ -1113,11 +1578,13 @@ pub struct Directory {
 }
 
 #[derive(Debug, Default, Clone, PartialEq, SubsonicType)]
+#[subsonic(builder, visit = "child")]
 pub struct Child {
+    /// The ID of a song, or of a directory if `is_dir` is set.
+    #[subsonic(attribute, visit_id = "id_kind")]
+    pub id: SongId,
     #[subsonic(attribute)]
-    pub id: String,
-    #[subsonic(attribute)]
-    pub parent: Option<String>,
+    pub parent: Option<DirectoryId>,
     #[subsonic(attribute)]
     pub is_dir: bool,
     #[subsonic(attribute)]
@@ -1133,7 +1600,7 @@ pub struct Child {
     #[subsonic(attribute)]
     pub genre: Option<String>,
     #[subsonic(attribute)]
-    pub cover_art: Option<String>,
+    pub cover_art: Option<CoverArtId>,
     #[subsonic(attribute)]
     pub size: Option<u64>,
     #[subsonic(attribute)]
@@ -1165,9 +1632,9 @@ pub struct Child {
     #[subsonic(attribute, since = "1.8.0")]
     pub starred: Option<DateTime>,
     #[subsonic(attribute, since = "1.8.0")]
-    pub album_id: Option<String>,
+    pub album_id: Option<AlbumId>,
     #[subsonic(attribute, since = "1.8.0")]
-    pub artist_id: Option<String>,
+    pub artist_id: Option<ArtistId>,
     #[subsonic(rename = "type", attribute, since = "1.8.0")]
     pub media_type: Option<MediaType>,
     #[subsonic(attribute, since = "1.10.1")]
@@ -1178,6 +1645,25 @@ pub struct Child {
     pub original_height: Option<u32>,
 }
 
+impl Child {
+    /// The ID of this entry as a directory.
+    /// Useful when this entry is a directory (`is_dir`), e.g. an album returned by
+    /// [`AlbumList`] that can be passed to [`GetMusicDirectory`](crate::request::browsing::GetMusicDirectory).
+    pub fn directory_id(&self) -> DirectoryId {
+        DirectoryId::from(self.id.clone())
+    }
+
+    /// The kind of [`id`](Self::id) passed to visitors: [`IdKind::Directory`] if this entry is a
+    /// directory, [`IdKind::Song`] otherwise.
+    pub fn id_kind(&self) -> IdKind {
+        if self.is_dir {
+            IdKind::Directory
+        } else {
+            IdKind::Song
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq, SubsonicType)]
 pub struct NowPlaying {
     pub entry: Vec<NowPlayingEntry>,
@@ -1221,6 +1707,16 @@ pub struct SearchResult3 {
     pub song: Vec<Child>,
 }
 
+impl From<SearchResult3> for SearchResult2 {
+    fn from(value: SearchResult3) -> Self {
+        Self {
+            artist: value.artist.into_iter().map(Into::into).collect(),
+            album: value.album.into_iter().map(Into::into).collect(),
+            song: value.song,
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq, SubsonicType)]
 pub struct Playlists {
     pub playlist: Vec<Playlist>,
@@ -1229,7 +1725,7 @@ pub struct Playlists {
 #[derive(Debug, Default, Clone, PartialEq, SubsonicType)]
 pub struct Playlist {
     #[subsonic(attribute)]
-    pub id: String,
+    pub id: PlaylistId,
     #[subsonic(attribute)]
     pub name: String,
     #[subsonic(attribute, since = "1.8.0")]
@@ -1247,7 +1743,7 @@ pub struct Playlist {
     #[subsonic(attribute, since = "1.13.0")]
     pub changed: DateTime,
     #[subsonic(attribute, since = "1.11.0")]
-    pub cover_art: Option<String>,
+    pub cover_art: Option<CoverArtId>,
     #[subsonic(since = "1.8.0")]
     pub allowed_user: Vec<String>,
 }
@@ -1287,8 +1783,9 @@ pub struct ChatMessages {
 pub struct ChatMessage {
     #[subsonic(attribute)]
     pub username: String,
+    /// The time the message was sent, in milliseconds since the Unix epoch.
     #[subsonic(attribute)]
-    pub time: DateTime,
+    pub time: Milliseconds,
     #[subsonic(attribute)]
     pub message: String,
 }
@@ -1303,6 +1800,14 @@ pub struct AlbumList2 {
     pub album: Vec<AlbumID3>,
 }
 
+impl From<AlbumList2> for AlbumList {
+    fn from(value: AlbumList2) -> Self {
+        Self {
+            album: value.album.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq, SubsonicType)]
 pub struct Songs {
     pub song: Vec<Child>,
@@ -1324,7 +1829,7 @@ pub struct Podcasts {
 #[derive(Debug, Default, Clone, PartialEq, SubsonicType)]
 pub struct PodcastChannel {
     #[subsonic(attribute)]
-    pub id: String,
+    pub id: PodcastChannelId,
     #[subsonic(attribute)]
     pub url: String,
     #[subsonic(attribute)]
@@ -1332,7 +1837,7 @@ pub struct PodcastChannel {
     #[subsonic(attribute)]
     pub description: Option<String>,
     #[subsonic(attribute, since = "1.13.0")]
-    pub cover_art: Option<String>,
+    pub cover_art: Option<CoverArtId>,
     #[subsonic(attribute, since = "1.13.0")]
     pub original_image_url: Option<String>,
     #[subsonic(attribute)]
@@ -1348,14 +1853,15 @@ pub struct NewestPodcasts {
 }
 
 #[derive(Debug, Default, Clone, PartialEq, SubsonicType)]
+#[subsonic(builder)]
 pub struct PodcastEpisode {
     #[subsonic(flatten)]
     pub child: Child,
     /// Use this ID for streaming the podcast
     #[subsonic(attribute)]
-    pub stream_id: Option<String>,
+    pub stream_id: Option<SongId>,
     #[subsonic(attribute, since = "1.13.0")]
-    pub channel_id: String,
+    pub channel_id: PodcastChannelId,
     #[subsonic(attribute)]
     pub description: Option<String>,
     #[subsonic(attribute)]
@@ -1364,14 +1870,23 @@ pub struct PodcastEpisode {
     pub publish_date: Option<DateTime>,
 }
 
+impl PodcastEpisode {
+    /// The ID of this episode.
+    pub fn id(&self) -> PodcastEpisodeId {
+        PodcastEpisodeId::from(self.child.id.clone())
+    }
+}
+
 #[derive(
     Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, SubsonicType,
 )]
+#[serde(rename_all = "lowercase")]
 #[subsonic(serde)]
 pub enum PodcastStatus {
     New,
     Downloading,
     Completed,
+    Deleted,
     Skipped,
     #[default]
     Error,
@@ -1385,7 +1900,7 @@ pub struct InternetRadioStations {
 #[derive(Debug, Default, Clone, PartialEq, SubsonicType)]
 pub struct InternetRadioStation {
     #[subsonic(attribute)]
-    pub id: String,
+    pub id: InternetRadioStationId,
     #[subsonic(attribute)]
     pub name: String,
     #[subsonic(attribute)]
@@ -1418,7 +1933,7 @@ pub struct Bookmark {
 pub struct PlayQueue {
     /// ID of the currently playing song
     #[subsonic(attribute)]
-    pub current: Option<u64>, // TODO: u64?
+    pub current: Option<SongId>,
     /// Position of the currently playing track
     #[subsonic(attribute)]
     pub position: Option<Milliseconds>,
@@ -1440,7 +1955,7 @@ pub struct Shares {
 #[derive(Debug, Default, Clone, PartialEq, SubsonicType)]
 pub struct Share {
     #[subsonic(attribute)]
-    pub id: String,
+    pub id: ShareId,
     #[subsonic(attribute)]
     pub url: String,
     #[subsonic(attribute)]
@@ -1460,9 +1975,9 @@ pub struct Share {
 
 #[derive(Debug, Default, Clone, PartialEq, SubsonicType)]
 pub struct Starred {
-    pub song: Vec<Child>,
-    pub album: Vec<Child>,
     pub artist: Vec<Artist>,
+    pub album: Vec<Child>,
+    pub song: Vec<Child>,
 }
 
 #[derive(Debug, Default, Clone, PartialEq, SubsonicType)]
@@ -1516,9 +2031,19 @@ pub struct TopSongs {
 
 #[derive(Debug, Default, Clone, PartialEq, SubsonicType)]
 pub struct Starred2 {
-    pub song: Vec<Child>,
-    pub album: Vec<AlbumID3>,
     pub artist: Vec<ArtistID3>,
+    pub album: Vec<AlbumID3>,
+    pub song: Vec<Child>,
+}
+
+impl From<Starred2> for Starred {
+    fn from(value: Starred2) -> Self {
+        Self {
+            song: value.song,
+            album: value.album.into_iter().map(Into::into).collect(),
+            artist: value.artist.into_iter().map(Into::into).collect(),
+        }
+    }
 }
 
 #[derive(Debug, Default, Clone, PartialEq, SubsonicType)]
@@ -1535,6 +2060,7 @@ pub struct Users {
 }
 
 #[derive(Debug, Default, Clone, PartialEq, SubsonicType)]
+#[subsonic(builder)]
 pub struct User {
     #[subsonic(attribute)]
     pub username: String,
@@ -1715,6 +2241,279 @@ impl<'de> serde::Deserialize<'de> for ErrorCode {
 mod tests {
     use super::*;
 
+    use crate::request::{search, SubsonicRequest};
+
+    #[test]
+    fn xml_options() {
+        let response = Response::ok(
+            Version::V1_16_1,
+            ResponseBody::Song(Child {
+                id: "1".into(),
+                parent: Some("2".into()),
+                is_dir: false,
+                title: "tab\tnew\nline\r\u{1}\"quoted\" <a & b>".into(),
+                ..Default::default()
+            }),
+        );
+
+        let xml = response.to_xml().unwrap();
+        assert_eq!(
+            xml,
+            concat!(
+                r#"<subsonic-response status="ok" version="1.16.1">"#,
+                r#"<song id="1" parent="2" isDir="false" title="tab&#9;new&#10;line&#13;�&quot;quoted&quot; &lt;a &amp; b&gt;"/>"#,
+                r#"</subsonic-response>"#
+            )
+        );
+
+        let options = [
+            XmlOptions::default(),
+            XmlOptions {
+                namespace: true,
+                prolog: false,
+            },
+            XmlOptions {
+                namespace: false,
+                prolog: true,
+            },
+            XmlOptions::spec(),
+        ];
+        for options in options {
+            let xml = response.to_xml_with(options).unwrap();
+            assert_eq!(xml.starts_with("<?xml"), options.prolog);
+            assert_eq!(xml.contains(XML_NAMESPACE), options.namespace);
+            let mut expected = response.clone();
+            if let ResponseBody::Song(ref mut song) = expected.body {
+                song.title = "tab\tnew\nline\r\u{fffd}\"quoted\" <a & b>".into();
+            }
+            assert_eq!(Response::from_xml(&xml).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn from_xml_namespaced() {
+        let expected = Response::ok(
+            Version::V1_16_1,
+            ResponseBody::License(License {
+                valid: true,
+                ..Default::default()
+            }),
+        );
+        for xml in [
+            r#"<subsonic-response xmlns="http://subsonic.org/restapi" status="ok" version="1.16.1"><license valid="true"/></subsonic-response>"#,
+            r#"<?xml version="1.0" encoding="UTF-8"?><sub:subsonic-response xmlns:sub="http://subsonic.org/restapi" status="ok" version="1.16.1"><sub:license valid="true"/></sub:subsonic-response>"#,
+            r#"<subsonic-response xmlns="http://subsonic.org/restapi" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://subsonic.org/restapi subsonic-rest-api.xsd" status="ok" version="1.16.1"><license valid="true"/></subsonic-response>"#,
+        ] {
+            assert_eq!(Response::from_xml(xml).unwrap(), expected, "{}", xml);
+        }
+    }
+
+    #[test]
+    fn from_bytes() {
+        let expected = Response::ok(
+            Version::V1_16_1,
+            ResponseBody::Song(Child {
+                id: "1".into(),
+                title: "Café".into(),
+                ..Default::default()
+            }),
+        );
+        let parse = |bytes: &[u8], content_type: Option<&str>| {
+            Response::from_bytes(bytes, content_type).unwrap()
+        };
+
+        let xml = expected.to_xml_with(XmlOptions::spec()).unwrap();
+        assert_eq!(parse(xml.as_bytes(), None), (expected.clone(), Format::Xml));
+        let with_bom = [b"\xef\xbb\xbf".as_slice(), xml.as_bytes()].concat();
+        assert_eq!(parse(&with_bom, None), (expected.clone(), Format::Xml));
+        let utf16 = [0xff, 0xfe]
+            .into_iter()
+            .chain(xml.encode_utf16().flat_map(u16::to_le_bytes))
+            .collect::<Vec<_>>();
+        assert_eq!(parse(&utf16, None), (expected.clone(), Format::Xml));
+
+        let latin1 = [
+            b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?>\n".as_slice(),
+            b"<subsonic-response status=\"ok\" version=\"1.16.1\">",
+            b"<song id=\"1\" isDir=\"false\" title=\"Caf\xe9\"/>",
+            b"</subsonic-response>",
+        ]
+        .concat();
+        assert_eq!(parse(&latin1, None), (expected.clone(), Format::Xml));
+        let latin1 = &latin1[latin1.iter().position(|&b| b == b'\n').unwrap() + 1..];
+        assert!(Response::from_bytes(latin1, None).is_err());
+        assert_eq!(
+            parse(latin1, Some("text/xml; charset=iso-8859-1")),
+            (expected.clone(), Format::Xml)
+        );
+
+        let json = expected.to_json().unwrap();
+        assert_eq!(parse(json.as_bytes(), None), (expected.clone(), Format::Json));
+        let with_bom = [b"\xef\xbb\xbf  ".as_slice(), json.as_bytes()].concat();
+        assert_eq!(parse(&with_bom, None), (expected.clone(), Format::Json));
+        for jsonp in [
+            format!("callback({})", json),
+            format!("jQuery_123.cb ( {} );\n", json),
+            format!("/**/cb({});", json),
+        ] {
+            assert_eq!(parse(jsonp.as_bytes(), None), (expected.clone(), Format::Json));
+        }
+
+        assert!(Response::from_bytes(b"", None).is_err());
+        assert!(Response::from_bytes(b"callback(1)", None).is_err());
+        assert!(Response::from_bytes(b"ok", Some("text/plain")).is_err());
+    }
+
+    #[test]
+    fn downgrade() {
+        let album = AlbumID3 {
+            id: "al-1".into(),
+            name: "Album".into(),
+            artist: Some("Artist".into()),
+            artist_id: Some("ar-1".into()),
+            song_count: 10,
+            duration: 300,
+            ..Default::default()
+        };
+        let response = Response::ok(
+            Version::LATEST,
+            ResponseBody::SearchResult3(SearchResult3 {
+                artist: vec![ArtistID3 {
+                    id: "ar-1".into(),
+                    name: "Artist".into(),
+                    album_count: 1,
+                    ..Default::default()
+                }],
+                album: vec![album.clone()],
+                song: vec![],
+            }),
+        );
+
+        // Nothing to convert
+        let downgraded = response.clone().downgrade(Version::V1_8_0).unwrap();
+        assert_eq!(Version::V1_8_0, downgraded.version);
+        assert_eq!(response.body, downgraded.body);
+
+        let downgraded = response.clone().downgrade(Version::V1_4_0).unwrap();
+        let expected = ResponseBody::SearchResult2(SearchResult2 {
+            artist: vec![Artist {
+                id: "ar-1".into(),
+                name: "Artist".into(),
+                ..Default::default()
+            }],
+            album: vec![Child {
+                id: "al-1".into(),
+                parent: Some("ar-1".into()),
+                is_dir: true,
+                title: "Album".into(),
+                album: Some("Album".into()),
+                artist: Some("Artist".into()),
+                duration: Some(Seconds::new(300)),
+                artist_id: Some("ar-1".into()),
+                ..Default::default()
+            }],
+            song: vec![],
+        });
+        assert_eq!(expected, downgraded.body);
+
+        let err = response.downgrade(Version::V1_3_0).unwrap_err();
+        assert_eq!(search::Search2::SINCE, err.since);
+        assert_eq!(Version::V1_3_0, err.target);
+
+        let response = Response::ok(
+            Version::LATEST,
+            ResponseBody::AlbumList2(AlbumList2 {
+                album: vec![album],
+            }),
+        );
+        let downgraded = response.downgrade(Version::V1_2_0).unwrap();
+        assert!(
+            matches!(downgraded.body, ResponseBody::AlbumList(ref list) if list.album.len() == 1)
+        );
+
+        let response = Response::ok(Version::LATEST, ResponseBody::Starred2(Default::default()));
+        let downgraded = response.downgrade(Version::V1_8_0).unwrap();
+        assert_eq!(ResponseBody::Starred(Default::default()), downgraded.body);
+
+        let response = Response::ok(Version::LATEST, ResponseBody::ScanStatus(Default::default()));
+        assert!(response.downgrade(Version::V1_10_2).is_err());
+
+        let response = Response::failed(Version::LATEST, Error::new(ErrorCode::Generic));
+        assert!(response.downgrade(Version::V1_1_0).is_ok());
+    }
+
+    #[test]
+    fn builders() {
+        let album = AlbumID3::builder("al-1", "Album", 10, 300)
+            .artist("Artist")
+            .artist_id("ar-1")
+            .year(2001)
+            .build();
+        assert_eq!(
+            AlbumID3 {
+                id: "al-1".into(),
+                name: "Album".into(),
+                artist: Some("Artist".into()),
+                artist_id: Some("ar-1".into()),
+                song_count: 10,
+                duration: 300,
+                year: Some(2001),
+                ..Default::default()
+            },
+            album
+        );
+
+        let song = Child::builder("s-1", "Song")
+            .album_id("al-1")
+            .duration(Seconds::new(30))
+            .media_type(MediaType::Music)
+            .build();
+        assert!(!song.is_dir);
+        assert_eq!(Some(AlbumId::from("al-1")), song.album_id);
+
+        let response = Response::album(
+            Version::LATEST,
+            AlbumWithSongsID3 {
+                album: album.clone(),
+                song: vec![song.clone()],
+            },
+        );
+        assert_eq!(
+            Response::ok(
+                Version::LATEST,
+                ResponseBody::Album(AlbumWithSongsID3 {
+                    album,
+                    song: vec![song.clone()],
+                })
+            ),
+            response
+        );
+
+        let episode = PodcastEpisode::builder(
+            Child::builder("e-1", "Episode"),
+            "c-1",
+            PodcastStatus::Completed,
+        )
+        .description("Description")
+        .build();
+        assert_eq!("e-1", episode.id().as_ref());
+        assert_eq!(PodcastChannelId::from("c-1"), episode.channel_id);
+
+        let user = User::builder("admin")
+            .admin_role(true)
+            .stream_role(true)
+            .folder(1)
+            .folder(2)
+            .build();
+        assert!(user.admin_role && user.stream_role && !user.jukebox_role);
+        assert_eq!(vec![1, 2], user.folder);
+        let response = Response::user(Version::LATEST, User::builder("guest"));
+        assert!(matches!(response.body, ResponseBody::User(ref user) if user.username == "guest"));
+
+        let response = Response::random_songs(Version::LATEST, Songs { song: vec![song] });
+        assert!(matches!(response.body, ResponseBody::RandomSongs(_)));
+    }
+
     #[test]
     fn example_ping() {
         let xml = r#"
@@ -1774,15 +2573,15 @@ mod tests {
             ResponseBody::MusicFolders(MusicFolders {
                 music_folder: vec![
                     MusicFolder {
-                        id: 1,
+                        id: MusicFolderId::new(1),
                         name: Some("Music".into()),
                     },
                     MusicFolder {
-                        id: 2,
+                        id: MusicFolderId::new(2),
                         name: Some("Movies".into()),
                     },
                     MusicFolder {
-                        id: 3,
+                        id: MusicFolderId::new(3),
                         name: Some("Incoming".into()),
                     },
                 ],
@@ -1814,7 +2613,7 @@ mod tests {
 
         let value = Response::from_xml(xml).unwrap();
         let expected = Response::ok(
-            Version::new(1, 10, 1),
+            Version::V1_10_1,
             ResponseBody::Indexes(Indexes {
                 shortcut: vec![
                     Artist {
@@ -1921,7 +2720,7 @@ mod tests {
 
         let value = Response::from_xml(xml).unwrap();
         let expected = Response::ok(
-            Version::new(1, 10, 1),
+            Version::V1_10_1,
             ResponseBody::Directory(Directory {
                 id: "10".into(),
                 parent: Some("9".into()),
@@ -2106,7 +2905,7 @@ mod tests {
 
         let value = Response::from_xml(xml).unwrap();
         let expected = Response::ok(
-            Version::new(1, 10, 1),
+            Version::V1_10_1,
             ResponseBody::Artists(ArtistsID3 {
                 ignored_articles: "The El La Los Las Le Les".into(),
                 index: vec![
@@ -0,0 +1,479 @@
+//! Server side abstraction over the Subsonic API.
+//!
+//! Implement [`SubsonicService`] for the endpoints a server supports, every other endpoint
+//! responds with an error. [`dispatch`] routes an encoded request to the right method and
+//! [`ServiceTransport`] allows using a service as an [`AsyncTransport`], which is useful to test
+//! client code without a network.
+//!
+//! # Example
+//! ```rust
+//! use async_trait::async_trait;
+//! use subsonic_types::{
+//!     common::Version,
+//!     request::{system::Ping, Authentication, Request},
+//!     response::{Response, ResponseBody},
+//!     service::{self, ServiceTransport, SubsonicService},
+//!     transport,
+//! };
+//!
+//! struct Server;
+//!
+//! #[async_trait]
+//! impl SubsonicService for Server {
+//!     async fn ping(&self, request: Request<Ping>) -> service::Result<Response> {
+//!         Ok(Response::ok(request.version, ResponseBody::Empty))
+//!     }
+//! }
+//!
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! let request = Request {
+//!     username: "admin".into(),
+//!     authentication: Authentication::Password("admin".into()),
+//!     version: Version::LATEST,
+//!     client: "example".into(),
+//!     format: Some("json".into()),
+//!     body: Ping,
+//! };
+//! let transport = ServiceTransport::new(Server);
+//! let response = transport::execute_async(&transport, &request).await.unwrap();
+//! assert_eq!(Response::ok(Version::LATEST, ResponseBody::Empty), response);
+//! # });
+//! ```
+
+use std::convert::Infallible;
+
+use crate::{
+    binary::{BinaryResponse, Media},
+    common::{Format, Version},
+    request::{
+        annotation, bookmark, browsing, chat, jukebox, lists, playlists, podcast, radio, retrieval,
+        scan, search, sharing, system, user, Request, SubsonicRequest,
+    },
+    response::{Error, ErrorCode, Response, XmlOptions},
+    transport::{self, AsyncTransport, TransportError, TransportRequest, TransportResponse},
+};
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The error returned by the default implementation of every [`SubsonicService`] method.
+pub fn not_implemented() -> Error {
+    Error::with_message(ErrorCode::Generic, "not implemented")
+}
+
+macro_rules! declare_service {
+    ($($(#[$comment:meta])* ($name:ident $request:path => $output:ty)),*) => {
+        /// A Subsonic server.
+        /// Every method has a default implementation that returns [`not_implemented`].
+        #[async_trait::async_trait]
+        #[allow(unused_variables)]
+        pub trait SubsonicService: Send + Sync + 'static {
+            $(
+                async fn $name(&self, request: Request<$request>) -> Result<$output> {
+                    Err(not_implemented())
+                }
+            )*
+        }
+
+        /// Routes an encoded request to the matching [`SubsonicService`] method and encodes its response.
+        ///
+        /// `path` may or may not have the `.view` suffix, `query` contains the encoded parameters.
+        /// Unknown paths respond with status 404, errors are encoded as failed responses.
+        pub async fn dispatch<S>(service: &S, path: &str, query: &str) -> TransportResponse
+        where
+            S: SubsonicService + ?Sized,
+        {
+            let path = path.strip_suffix(".view").unwrap_or(path);
+            $(
+                if path == <$request as SubsonicRequest>::PATH {
+                    let request = match Request::<$request>::from_query(query) {
+                        Ok(request) => request,
+                        Err(err) => {
+                            return Response::failed(Version::LATEST, Error::from(err))
+                                .into_transport_response(query_format(query));
+                        }
+                    };
+                    let format = request_format(request.format.as_deref());
+                    let version = request.version;
+                    return match service.$name(request).await {
+                        Ok(output) => output.into_transport_response(format),
+                        Err(err) => Response::failed(version, err).into_transport_response(format),
+                    };
+                }
+            )*
+            TransportResponse {
+                status: 404,
+                content_type: None,
+                content_range: None,
+                body: Vec::new(),
+            }
+        }
+    };
+}
+
+/// Calls `$callback!` with the `(method Request => Output)` list of every [`SubsonicService`] method.
+/// The request modules, `Response` and `Media` must be in scope where it is called.
+macro_rules! service_methods {
+    ($callback:ident) => {
+        $callback!(
+            // Annotation
+            (star annotation::Star => Response),
+            (unstar annotation::Unstar => Response),
+            (set_rating annotation::SetRating => Response),
+            (scrobble annotation::Scrobble => Response),
+            // Bookmarks
+            (get_bookmarks bookmark::GetBookmarks => Response),
+            (create_bookmark bookmark::CreateBookmark => Response),
+            (delete_bookmark bookmark::DeleteBookmark => Response),
+            (get_play_queue bookmark::GetPlayQueue => Response),
+            (save_play_queue bookmark::SavePlayQueue => Response),
+            // Browsing
+            (get_music_folders browsing::GetMusicFolders => Response),
+            (get_indexes browsing::GetIndexes => Response),
+            (get_music_directory browsing::GetMusicDirectory => Response),
+            (get_genres browsing::GetGenres => Response),
+            (get_artists browsing::GetArtists => Response),
+            (get_artist browsing::GetArtist => Response),
+            (get_album browsing::GetAlbum => Response),
+            (get_song browsing::GetSong => Response),
+            (get_videos browsing::GetVideos => Response),
+            (get_video_info browsing::GetVideoInfo => Response),
+            (get_artist_info browsing::GetArtistInfo => Response),
+            (get_artist_info2 browsing::GetArtistInfo2 => Response),
+            (get_album_info browsing::GetAlbumInfo => Response),
+            (get_album_info2 browsing::GetAlbumInfo2 => Response),
+            (get_similar_songs browsing::GetSimilarSongs => Response),
+            (get_similar_songs2 browsing::GetSimilarSongs2 => Response),
+            (get_top_songs browsing::GetTopSongs => Response),
+            // Chat
+            (get_chat_messages chat::GetChatMessages => Response),
+            (add_chat_message chat::AddChatMessage => Response),
+            // Jukebox
+            (jukebox_control jukebox::JukeboxControl => Response),
+            // Lists
+            (get_album_list lists::GetAlbumList => Response),
+            (get_album_list2 lists::GetAlbumList2 => Response),
+            (get_random_songs lists::GetRandomSongs => Response),
+            (get_songs_by_genre lists::GetSongsByGenre => Response),
+            (get_now_playing lists::GetNowPlaying => Response),
+            (get_starred lists::GetStarred => Response),
+            (get_starred2 lists::GetStarred2 => Response),
+            // Playlists
+            (get_playlists playlists::GetPlaylists => Response),
+            (get_playlist playlists::GetPlaylist => Response),
+            (create_playlist playlists::CreatePlaylist => Response),
+            (update_playlist playlists::UpdatePlaylist => Response),
+            (delete_playlist playlists::DeletePlaylist => Response),
+            // Podcasts
+            (get_podcasts podcast::GetPodcasts => Response),
+            (get_newest_podcasts podcast::GetNewestPodcasts => Response),
+            (refresh_podcasts podcast::RefreshPodcasts => Response),
+            (create_podcast_channel podcast::CreatePodcastChannel => Response),
+            (delete_podcast_channel podcast::DeletePodcastChannel => Response),
+            (delete_podcast_episode podcast::DeletePodcastEpisode => Response),
+            (download_podcast_episode podcast::DownloadPodcastEpisode => Response),
+            // Radio
+            (get_internet_radio_stations radio::GetInternetRadioStations => Response),
+            (create_internet_radio_station radio::CreateInternetRadioStation => Response),
+            (update_internet_radio_station radio::UpdateInternetRadioStation => Response),
+            (delete_internet_radio_station radio::DeleteInternetRadioStation => Response),
+            // Retrieval
+            (stream retrieval::Stream => Media),
+            (download retrieval::Download => Media),
+            (hls retrieval::Hls => Media),
+            (get_captions retrieval::GetCaptions => Media),
+            (get_cover_art retrieval::GetCoverArt => Media),
+            (get_lyrics retrieval::GetLyrics => Response),
+            (get_avatar retrieval::GetAvatar => Media),
+            // Scan
+            (get_scan_status scan::GetScanStatus => Response),
+            (start_scan scan::StartScan => Response),
+            // Search
+            (search search::Search => Response),
+            (search2 search::Search2 => Response),
+            (search3 search::Search3 => Response),
+            // Sharing
+            (get_shares sharing::GetShares => Response),
+            (create_share sharing::CreateShare => Response),
+            (update_share sharing::UpdateShare => Response),
+            (delete_share sharing::DeleteShare => Response),
+            // System
+            (ping system::Ping => Response),
+            (get_license system::GetLicense => Response),
+            // User
+            (get_user user::GetUser => Response),
+            (get_users user::GetUsers => Response),
+            (create_user user::CreateUser => Response),
+            (update_user user::UpdateUser => Response),
+            (delete_user user::DeleteUser => Response),
+            (change_password user::ChangePassword => Response)
+        );
+    };
+}
+pub(crate) use service_methods;
+
+service_methods!(declare_service);
+
+/// The output of a [`SubsonicService`] method: a [`Response`] or [`Media`].
+pub(crate) trait ServiceOutput: Clone + Send + Sync + Sized + 'static {
+    /// The output as a response, `None` for media.
+    fn as_response(&self) -> Option<&Response>;
+    fn as_response_mut(&mut self) -> Option<&mut Response>;
+    fn into_transport_response(self, format: Format) -> TransportResponse;
+    /// Parses the output of a [`TransportResponse`]. Errors returned instead of media are
+    /// returned in the inner result.
+    fn from_transport_response<E>(
+        format: Format,
+        response: TransportResponse,
+    ) -> Result<Result<Self>, TransportError<E>>;
+}
+
+impl ServiceOutput for Response {
+    fn as_response(&self) -> Option<&Response> {
+        Some(self)
+    }
+
+    fn as_response_mut(&mut self) -> Option<&mut Response> {
+        Some(self)
+    }
+
+    fn into_transport_response(self, format: Format) -> TransportResponse {
+        let (content_type, body) = match format {
+            Format::Json => ("application/json", self.to_json()),
+            Format::Xml => ("text/xml", self.to_xml_with(XmlOptions::spec())),
+        };
+        match body {
+            Ok(body) => TransportResponse {
+                status: 200,
+                content_type: Some(content_type.to_string()),
+                content_range: None,
+                body: body.into_bytes(),
+            },
+            Err(err) => TransportResponse {
+                status: 500,
+                content_type: Some("text/plain".to_string()),
+                content_range: None,
+                body: err.to_string().into_bytes(),
+            },
+        }
+    }
+
+    fn from_transport_response<E>(
+        format: Format,
+        response: TransportResponse,
+    ) -> Result<Result<Self>, TransportError<E>> {
+        transport::parse_response(format, response).map(Ok)
+    }
+}
+
+impl ServiceOutput for Media {
+    fn as_response(&self) -> Option<&Response> {
+        None
+    }
+
+    fn as_response_mut(&mut self) -> Option<&mut Response> {
+        None
+    }
+
+    /// Partial media responds with status 206 and its `Content-Range`.
+    fn into_transport_response(self, _format: Format) -> TransportResponse {
+        TransportResponse {
+            status: if self.range.is_some() { 206 } else { 200 },
+            content_type: Some(self.content_type),
+            content_range: self.range.map(|range| range.to_string()),
+            body: self.data,
+        }
+    }
+
+    fn from_transport_response<E>(
+        _format: Format,
+        response: TransportResponse,
+    ) -> Result<Result<Self>, TransportError<E>> {
+        transport::parse_binary_response(response).map(BinaryResponse::into_result)
+    }
+}
+
+fn request_format(format: Option<&str>) -> Format {
+    format.and_then(|f| f.parse().ok()).unwrap_or(Format::Xml)
+}
+
+/// The format of a request that failed to parse.
+fn query_format(query: &str) -> Format {
+    let format = query.split('&').find_map(|pair| pair.strip_prefix("f="));
+    request_format(format)
+}
+
+/// An [`AsyncTransport`] that executes requests on an in-process [`SubsonicService`].
+#[derive(Debug, Clone)]
+pub struct ServiceTransport<S> {
+    service: S,
+}
+
+impl<S: SubsonicService> ServiceTransport<S> {
+    pub fn new(service: S) -> Self {
+        Self { service }
+    }
+
+    pub fn service(&self) -> &S {
+        &self.service
+    }
+
+    pub fn into_inner(self) -> S {
+        self.service
+    }
+}
+
+impl<S: SubsonicService> AsyncTransport for ServiceTransport<S> {
+    type Error = Infallible;
+
+    async fn execute(&self, request: TransportRequest) -> Result<TransportResponse, Self::Error> {
+        Ok(dispatch(&self.service, &request.path, request.parameters.encoded()).await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        binary::{BinaryResponse, ContentRange},
+        response::{License, ResponseBody, ResponseStatus},
+        test_support::request,
+        transport::{execute_async, execute_binary_async, TransportError},
+    };
+
+    use super::*;
+
+    struct Server;
+
+    #[async_trait::async_trait]
+    impl SubsonicService for Server {
+        async fn get_license(&self, request: Request<system::GetLicense>) -> Result<Response> {
+            Ok(Response::ok(
+                request.version,
+                ResponseBody::License(License {
+                    valid: true,
+                    ..Default::default()
+                }),
+            ))
+        }
+
+        async fn get_avatar(&self, request: Request<retrieval::GetAvatar>) -> Result<Media> {
+            Ok(Media::new("image/png", request.body.username.into_bytes()))
+        }
+
+        async fn get_cover_art(&self, _: Request<retrieval::GetCoverArt>) -> Result<Media> {
+            let range = ContentRange {
+                start: 2,
+                end: 3,
+                total: Some(4),
+            };
+            Ok(Media::new("image/png", vec![2, 3]).with_range(range))
+        }
+
+        async fn get_captions(&self, _: Request<retrieval::GetCaptions>) -> Result<Media> {
+            Ok(Media::new("text/vtt", CAPTIONS.as_bytes().to_vec()))
+        }
+    }
+
+    const CAPTIONS: &str = "WEBVTT\n\n00:00.000 --> 00:01.000\n<v Narrator>Hello\n";
+
+    #[tokio::test]
+    async fn test_service_transport() {
+        let transport = ServiceTransport::new(Server);
+        for format in [None, Some("json"), Some("xml")] {
+            let format = format.map(str::to_string);
+            let license = Request {
+                format: format.clone(),
+                ..request(system::GetLicense)
+            };
+            let response = execute_async(&transport, &license).await.unwrap();
+            assert_eq!(response.status, ResponseStatus::Ok);
+            assert!(matches!(response.body, ResponseBody::License(l) if l.valid));
+
+            let ping = Request {
+                format,
+                ..request(system::Ping)
+            };
+            let response = execute_async(&transport, &ping).await.unwrap();
+            assert_eq!(
+                response,
+                Response::failed(Version::LATEST, not_implemented())
+            );
+        }
+
+        let avatar = request(retrieval::GetAvatar {
+            username: "bob".into(),
+        });
+        let response = transport
+            .execute(TransportRequest::query(&avatar))
+            .await
+            .unwrap();
+        assert_eq!(response.body, b"bob");
+        assert_eq!(response.content_type.as_deref(), Some("image/png"));
+
+        let media = execute_binary_async(&transport, &avatar).await.unwrap();
+        assert_eq!(
+            media,
+            BinaryResponse::Media(Media::new("image/png", b"bob".to_vec()))
+        );
+
+        // partial media
+        let cover_art = request(retrieval::GetCoverArt::builder("1").build());
+        let response = transport
+            .execute(TransportRequest::query(&cover_art))
+            .await
+            .unwrap();
+        assert_eq!(response.status, 206);
+        assert_eq!(response.content_range.as_deref(), Some("bytes 2-3/4"));
+        let media = execute_binary_async(&transport, &cover_art).await.unwrap();
+        assert_eq!(
+            Some(ContentRange {
+                start: 2,
+                end: 3,
+                total: Some(4)
+            }),
+            media.into_result().unwrap().range
+        );
+
+        // captions are media, not a subsonic response
+        let captions = request(retrieval::GetCaptions::builder("1").format("vtt").build());
+        let response = transport
+            .execute(TransportRequest::query(&captions))
+            .await
+            .unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.content_type.as_deref(), Some("text/vtt"));
+        let media = execute_binary_async(&transport, &captions).await.unwrap();
+        assert_eq!(
+            media,
+            BinaryResponse::Media(Media::new("text/vtt", CAPTIONS.as_bytes().to_vec()))
+        );
+
+        let stream = Request {
+            format: Some("json".into()),
+            ..request(retrieval::Stream::builder("1").build())
+        };
+        let response = execute_binary_async(&transport, &stream).await.unwrap();
+        assert_eq!(response, BinaryResponse::Error(not_implemented()));
+    }
+
+    #[tokio::test]
+    async fn test_dispatch() {
+        let response = dispatch(
+            &Server,
+            "/rest/getLicense.view",
+            "u=a&p=b&v=1.16.1&c=c&f=json",
+        )
+        .await;
+        assert_eq!(response.status, 200);
+        assert_eq!(response.content_type.as_deref(), Some("application/json"));
+
+        let response = dispatch(&Server, "/rest/getLicense", "f=json").await;
+        assert_eq!(response.status, 200);
+        let response = crate::transport::parse_response::<()>(Format::Xml, response).unwrap();
+        assert_eq!(response.status, ResponseStatus::Failed);
+
+        let response = dispatch(&Server, "/rest/unknown", "").await;
+        assert_eq!(response.status, 404);
+        let err = crate::transport::parse_response::<()>(Format::Xml, response).unwrap_err();
+        assert!(matches!(err, TransportError::Status(404)));
+    }
+}
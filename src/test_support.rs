@@ -0,0 +1,225 @@
+//! Test fixtures shared by the tests of several modules.
+
+use crate::{
+    capabilities::SubsonicFields,
+    common::Version,
+    request::{Authentication, Request, SubsonicRequest},
+    response::{response_bodies, AlbumID3, ArtistID3, Child, ResponseBody},
+};
+
+/// Wraps `body` in a request of the latest version from the `admin` user.
+/// Tests that need other credentials, a version or a format override them with
+/// struct update syntax.
+pub(crate) fn request<R: SubsonicRequest>(body: R) -> Request<R> {
+    Request {
+        username: "admin".into(),
+        authentication: Authentication::Password("admin".into()),
+        version: Version::LATEST,
+        client: "test".into(),
+        format: None,
+        body,
+    }
+}
+
+pub(crate) fn artist(id: &str, name: &str) -> ArtistID3 {
+    ArtistID3 {
+        id: id.into(),
+        name: name.into(),
+        ..Default::default()
+    }
+}
+
+pub(crate) fn album(id: &str, artist: &str, name: &str) -> AlbumID3 {
+    AlbumID3 {
+        id: id.into(),
+        name: name.into(),
+        artist: Some(artist.into()),
+        ..Default::default()
+    }
+}
+
+pub(crate) fn song(id: &str, artist: &str, album: &str, title: &str) -> Child {
+    Child::builder(id, title)
+        .artist(artist)
+        .album(album)
+        .build()
+}
+
+/// A value used to fill versioned fields in generated tests.
+pub(crate) trait Sample {
+    fn sample() -> Self;
+}
+
+macro_rules! impl_sample_for_default {
+    ($($t:ty),*) => {
+        $(
+            impl Sample for $t {
+                fn sample() -> Self {
+                    Default::default()
+                }
+            }
+        )*
+    };
+}
+
+impl_sample_for_default!(
+    bool,
+    u32,
+    u64,
+    f32,
+    String,
+    crate::common::DateTime,
+    crate::common::Milliseconds,
+    crate::common::Seconds,
+    crate::common::MusicFolderId,
+    crate::common::SongId,
+    crate::common::AlbumId,
+    crate::common::ArtistId,
+    crate::common::DirectoryId,
+    crate::common::CoverArtId,
+    crate::common::PlaylistId,
+    crate::common::PodcastChannelId,
+    crate::common::InternetRadioStationId,
+    crate::common::ShareId
+);
+
+impl<T: Sample> Sample for Option<T> {
+    fn sample() -> Self {
+        Some(T::sample())
+    }
+}
+
+impl<T: Sample> Sample for Vec<T> {
+    fn sample() -> Self {
+        vec![T::sample()]
+    }
+}
+
+impl Sample for crate::common::UserRating {
+    fn sample() -> Self {
+        Self::new(3).unwrap()
+    }
+}
+
+impl Sample for crate::common::AverageRating {
+    fn sample() -> Self {
+        Self::new(3.5).unwrap()
+    }
+}
+
+impl Sample for crate::common::MediaType {
+    fn sample() -> Self {
+        Self::Music
+    }
+}
+
+impl Sample for Version {
+    fn sample() -> Self {
+        Version::LATEST
+    }
+}
+
+impl Sample for crate::response::ResponseStatus {
+    fn sample() -> Self {
+        Self::Ok
+    }
+}
+
+impl Sample for crate::response::ResponseBody {
+    fn sample() -> Self {
+        Self::Empty
+    }
+}
+
+impl Sample for crate::response::PodcastStatus {
+    fn sample() -> Self {
+        Self::Completed
+    }
+}
+
+impl Sample for crate::response::ErrorCode {
+    fn sample() -> Self {
+        Self::DataNotFound
+    }
+}
+
+macro_rules! sample_bodies {
+    ($(($name:ident $variant:ident($ty:ty) => $request:path)),*) => {
+        /// A value of every [`ResponseBody`] variant with all fields set.
+        pub(crate) fn sample_bodies() -> Vec<ResponseBody> {
+            vec![
+                ResponseBody::Empty,
+                $(ResponseBody::$variant(Sample::sample()),)*
+                ResponseBody::Error(Sample::sample()),
+            ]
+        }
+    };
+}
+
+response_bodies!(sample_bodies);
+
+/// Checks that the versioned fields of `value` are only serialized and accepted in the
+/// versions they exist in. Every versioned field of `value` is expected to be set.
+/// Used by the tests generated by `#[derive(SubsonicType)]`.
+pub(crate) fn assert_versioned_fields<T>(value: &T)
+where
+    T: SubsonicFields
+        + crate::deser::SubsonicSerialize
+        + for<'de> crate::deser::SubsonicDeserialize<'de>
+        + std::fmt::Debug
+        + PartialEq,
+{
+    use crate::{common::Format, deser::SubsonicSerializeWrapper};
+
+    let serialize = |value: &T, version: Version| {
+        serde_json::to_value(SubsonicSerializeWrapper(value, Format::Json, version)).unwrap()
+    };
+    // A document containing the fields of every version.
+    let mut full = serde_json::Map::new();
+    for &version in Version::ALL {
+        if let serde_json::Value::Object(object) = serialize(value, version) {
+            full.extend(object);
+        }
+    }
+    let full = serde_json::Value::Object(full);
+
+    for field in T::FIELDS {
+        for version in [field.since, field.until, field.deprecated]
+            .into_iter()
+            .flatten()
+        {
+            assert!(
+                Version::ALL.contains(&version),
+                "field `{}` uses version {} missing from `Version::ALL`",
+                field.name,
+                version
+            );
+        }
+    }
+
+    for &version in Version::ALL {
+        let serialized = serialize(value, version);
+        let object = serialized.as_object().unwrap();
+        for field in T::FIELDS
+            .iter()
+            .filter(|f| f.since.is_some() || f.until.is_some())
+        {
+            assert_eq!(
+                field.is_present(version),
+                object.contains_key(field.key),
+                "field `{}` serialized in version {}",
+                field.name,
+                version
+            );
+        }
+
+        let seed = <T as crate::deser::SubsonicDeserialize>::Seed::from((Format::Json, version));
+        let accepted = serde::de::DeserializeSeed::deserialize(seed, &full).unwrap();
+        assert_eq!(
+            serialized,
+            serialize(&accepted, version),
+            "deserializing in version {}",
+            version
+        );
+    }
+}
@@ -0,0 +1,304 @@
+//! Abstraction over the HTTP stack used to execute requests.
+//!
+//! A [`Transport`] (or [`AsyncTransport`]) receives the path, the encoded parameters and the
+//! requested format and returns the status, content type and bytes of the response.
+//! [`execute`] and [`execute_async`] combine a transport with a [`Request`] and parse the [`Response`].
+//!
+//! Adapters are provided for [`reqwest`](https://docs.rs/reqwest) behind the `reqwest` feature and for
+//! an in-process [`SubsonicService`](crate::service::SubsonicService) with [`ServiceTransport`](crate::service::ServiceTransport).
+
+use std::future::Future;
+
+use crate::{
+    binary::BinaryResponse,
+    common::Format,
+    request::{Request, SubsonicRequest},
+    response::{self, Response},
+};
+
+#[cfg(feature = "reqwest")]
+mod reqwest;
+#[cfg(feature = "reqwest")]
+pub use self::reqwest::{BlockingReqwestTransport, ReqwestTransport};
+
+/// How the parameters of a request are sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Parameters {
+    /// Parameters sent in the url's query string.
+    Query(String),
+    /// Parameters sent in an `application/x-www-form-urlencoded` body. Supported since 1.14.0.
+    Form(String),
+}
+
+impl Parameters {
+    /// The encoded parameters.
+    pub fn encoded(&self) -> &str {
+        match self {
+            Parameters::Query(query) => query,
+            Parameters::Form(form) => form,
+        }
+    }
+}
+
+/// A request ready to be sent by a transport.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransportRequest {
+    /// The path of the endpoint, for example `/rest/ping`.
+    pub path: String,
+    pub parameters: Parameters,
+    /// The format requested with the `f` parameter, xml if not specified.
+    pub format: Format,
+}
+
+impl TransportRequest {
+    /// Creates a request that sends its parameters in the query string.
+    pub fn query<R: SubsonicRequest>(request: &Request<R>) -> Self {
+        Self {
+            path: R::PATH.to_string(),
+            parameters: Parameters::Query(SubsonicRequest::to_query(request)),
+            format: request_format(request),
+        }
+    }
+
+    /// Creates a request that sends its parameters in a form body.
+    pub fn form<R: SubsonicRequest>(request: &Request<R>) -> Self {
+        Self {
+            path: R::PATH.to_string(),
+            parameters: Parameters::Form(SubsonicRequest::to_query(request)),
+            format: request_format(request),
+        }
+    }
+}
+
+/// A response received by a transport.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransportResponse {
+    /// The HTTP status code.
+    pub status: u16,
+    /// The value of the `Content-Type` header, if present.
+    pub content_type: Option<String>,
+    /// The value of the `Content-Range` header, if present.
+    pub content_range: Option<String>,
+    pub body: Vec<u8>,
+}
+
+impl TransportResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// A blocking transport.
+pub trait Transport {
+    type Error;
+
+    fn execute(&self, request: TransportRequest) -> Result<TransportResponse, Self::Error>;
+}
+
+/// An asynchronous transport.
+pub trait AsyncTransport {
+    type Error;
+
+    fn execute(
+        &self,
+        request: TransportRequest,
+    ) -> impl Future<Output = Result<TransportResponse, Self::Error>> + Send;
+}
+
+impl<T: Transport + ?Sized> Transport for &T {
+    type Error = T::Error;
+
+    fn execute(&self, request: TransportRequest) -> Result<TransportResponse, Self::Error> {
+        T::execute(self, request)
+    }
+}
+
+impl<T: AsyncTransport + Sync + ?Sized> AsyncTransport for &T {
+    type Error = T::Error;
+
+    fn execute(
+        &self,
+        request: TransportRequest,
+    ) -> impl Future<Output = Result<TransportResponse, Self::Error>> + Send {
+        T::execute(self, request)
+    }
+}
+
+/// Error returned by [`execute`] and [`execute_async`].
+#[derive(Debug)]
+pub enum TransportError<E> {
+    /// The transport failed.
+    Transport(E),
+    /// The server responded with a non success status code.
+    Status(u16),
+    /// The response could not be parsed.
+    Parse(response::Error),
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for TransportError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransportError::Transport(err) => write!(f, "transport error: {}", err),
+            TransportError::Status(status) => write!(f, "unexpected status code: {}", status),
+            TransportError::Parse(err) => write!(f, "failed to parse response: {}", err),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for TransportError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TransportError::Transport(err) => Some(err),
+            TransportError::Status(_) => None,
+            TransportError::Parse(err) => Some(err),
+        }
+    }
+}
+
+/// Executes a request using a blocking transport.
+///
+/// # Example
+/// ```rust
+/// use subsonic_types::{
+///     common::Version,
+///     request::{system::Ping, Authentication, Request},
+///     response::{Response, ResponseBody},
+///     transport::{self, Transport, TransportRequest, TransportResponse},
+/// };
+///
+/// struct Static;
+///
+/// impl Transport for Static {
+///     type Error = std::convert::Infallible;
+///
+///     fn execute(&self, request: TransportRequest) -> Result<TransportResponse, Self::Error> {
+///         assert_eq!("/rest/ping", request.path);
+///         Ok(TransportResponse {
+///             status: 200,
+///             content_type: Some("text/xml".into()),
+///             content_range: None,
+///             body: br#"<subsonic-response status="ok" version="1.16.1"/>"#.to_vec(),
+///         })
+///     }
+/// }
+///
+/// let request = Request {
+///     username: "admin".into(),
+///     authentication: Authentication::Password("admin".into()),
+///     version: Version::LATEST,
+///     client: "example".into(),
+///     format: None,
+///     body: Ping,
+/// };
+/// let response = transport::execute(&Static, &request).unwrap();
+/// assert_eq!(Response::ok(Version::LATEST, ResponseBody::Empty), response);
+/// ```
+pub fn execute<T, R>(
+    transport: &T,
+    request: &Request<R>,
+) -> Result<Response, TransportError<T::Error>>
+where
+    T: Transport + ?Sized,
+    R: SubsonicRequest,
+{
+    let request = TransportRequest::query(request);
+    let format = request.format;
+    let response = transport
+        .execute(request)
+        .map_err(TransportError::Transport)?;
+    parse_response(format, response)
+}
+
+/// Executes a request using an asynchronous transport.
+pub async fn execute_async<T, R>(
+    transport: &T,
+    request: &Request<R>,
+) -> Result<Response, TransportError<T::Error>>
+where
+    T: AsyncTransport + ?Sized,
+    R: SubsonicRequest,
+{
+    let request = TransportRequest::query(request);
+    let format = request.format;
+    let response = transport
+        .execute(request)
+        .await
+        .map_err(TransportError::Transport)?;
+    parse_response(format, response)
+}
+
+/// Executes a request to an endpoint that returns binary data using a blocking transport.
+/// See [`BinaryResponse`].
+pub fn execute_binary<T, R>(
+    transport: &T,
+    request: &Request<R>,
+) -> Result<BinaryResponse, TransportError<T::Error>>
+where
+    T: Transport + ?Sized,
+    R: SubsonicRequest,
+{
+    let response = transport
+        .execute(TransportRequest::query(request))
+        .map_err(TransportError::Transport)?;
+    parse_binary_response(response)
+}
+
+/// Executes a request to an endpoint that returns binary data using an asynchronous transport.
+/// See [`BinaryResponse`].
+pub async fn execute_binary_async<T, R>(
+    transport: &T,
+    request: &Request<R>,
+) -> Result<BinaryResponse, TransportError<T::Error>>
+where
+    T: AsyncTransport + ?Sized,
+    R: SubsonicRequest,
+{
+    let response = transport
+        .execute(TransportRequest::query(request))
+        .await
+        .map_err(TransportError::Transport)?;
+    parse_binary_response(response)
+}
+
+/// Parses the response of a transport for an endpoint that returns binary data.
+pub fn parse_binary_response<E>(
+    response: TransportResponse,
+) -> Result<BinaryResponse, TransportError<E>> {
+    let is_success = response.is_success();
+    let binary = BinaryResponse::parse(
+        response.content_type.as_deref(),
+        response.content_range.as_deref(),
+        response.body,
+    );
+    // some servers send error documents with a non success status
+    if !is_success && !binary.is_error() {
+        return Err(TransportError::Status(response.status));
+    }
+    Ok(binary)
+}
+
+/// Parses the response of a transport using [`Response::from_bytes`].
+/// The requested format is used as a hint if the response has no content type.
+pub fn parse_response<E>(
+    format: Format,
+    response: TransportResponse,
+) -> Result<Response, TransportError<E>> {
+    if !response.is_success() {
+        return Err(TransportError::Status(response.status));
+    }
+    let content_type = response.content_type.as_deref().unwrap_or(match format {
+        Format::Json => "application/json",
+        Format::Xml => "text/xml",
+    });
+    Response::from_bytes(&response.body, Some(content_type))
+        .map(|(response, _)| response)
+        .map_err(TransportError::Parse)
+}
+
+fn request_format<R: SubsonicRequest>(request: &Request<R>) -> Format {
+    request
+        .format
+        .as_deref()
+        .and_then(|format| format.parse().ok())
+        .unwrap_or(Format::Xml)
+}
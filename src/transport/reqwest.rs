@@ -0,0 +1,116 @@
+use std::future::Future;
+
+use super::{AsyncTransport, Parameters, Transport, TransportRequest, TransportResponse};
+
+const FORM_CONTENT_TYPE: &str = "application/x-www-form-urlencoded";
+
+/// An [`AsyncTransport`] using [`reqwest::Client`].
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl ReqwestTransport {
+    /// Creates a transport for the server at `base_url`, for example `http://localhost:4533`.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_client(reqwest::Client::new(), base_url)
+    }
+
+    pub fn with_client(client: reqwest::Client, base_url: impl Into<String>) -> Self {
+        Self {
+            client,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl AsyncTransport for ReqwestTransport {
+    type Error = reqwest::Error;
+
+    fn execute(
+        &self,
+        request: TransportRequest,
+    ) -> impl Future<Output = Result<TransportResponse, Self::Error>> + Send {
+        let url = format!("{}{}", self.base_url, request.path);
+        let builder = match request.parameters {
+            Parameters::Query(query) => self.client.get(format!("{}?{}", url, query)),
+            Parameters::Form(form) => self
+                .client
+                .post(url)
+                .header(reqwest::header::CONTENT_TYPE, FORM_CONTENT_TYPE)
+                .body(form),
+        };
+        async move {
+            let response = builder.send().await?;
+            let status = response.status().as_u16();
+            let content_type = header(response.headers(), reqwest::header::CONTENT_TYPE);
+            let content_range = header(response.headers(), reqwest::header::CONTENT_RANGE);
+            let body = response.bytes().await?.to_vec();
+            Ok(TransportResponse {
+                status,
+                content_type,
+                content_range,
+                body,
+            })
+        }
+    }
+}
+
+/// A [`Transport`] using [`reqwest::blocking::Client`].
+#[derive(Debug, Clone)]
+pub struct BlockingReqwestTransport {
+    client: reqwest::blocking::Client,
+    base_url: String,
+}
+
+impl BlockingReqwestTransport {
+    /// Creates a transport for the server at `base_url`, for example `http://localhost:4533`.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_client(reqwest::blocking::Client::new(), base_url)
+    }
+
+    pub fn with_client(client: reqwest::blocking::Client, base_url: impl Into<String>) -> Self {
+        Self {
+            client,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl Transport for BlockingReqwestTransport {
+    type Error = reqwest::Error;
+
+    fn execute(&self, request: TransportRequest) -> Result<TransportResponse, Self::Error> {
+        let url = format!("{}{}", self.base_url, request.path);
+        let builder = match request.parameters {
+            Parameters::Query(query) => self.client.get(format!("{}?{}", url, query)),
+            Parameters::Form(form) => self
+                .client
+                .post(url)
+                .header(reqwest::header::CONTENT_TYPE, FORM_CONTENT_TYPE)
+                .body(form),
+        };
+        let response = builder.send()?;
+        let status = response.status().as_u16();
+        let content_type = header(response.headers(), reqwest::header::CONTENT_TYPE);
+        let content_range = header(response.headers(), reqwest::header::CONTENT_RANGE);
+        let body = response.bytes()?.to_vec();
+        Ok(TransportResponse {
+            status,
+            content_type,
+            content_range,
+            body,
+        })
+    }
+}
+
+fn header(
+    headers: &reqwest::header::HeaderMap,
+    name: reqwest::header::HeaderName,
+) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
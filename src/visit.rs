@@ -0,0 +1,470 @@
+//! Traversal of response trees.
+//!
+//! Every response and request type implements [`Visit`] and [`VisitMut`], which walk the value and
+//! call the [`Visitor`] or [`VisitorMut`] for every [`Child`], [`AlbumID3`], [`ArtistID3`] and ID
+//! found in it.
+//! The hooks for a type are called before its fields are visited.
+//!
+//! # Example
+//! Collecting the IDs of the songs in a response and prefixing every album ID:
+//! ```
+//! use subsonic_types::{
+//!     common::Version,
+//!     response::{AlbumID3, AlbumWithSongsID3, Child, Response, ResponseBody},
+//!     visit::{IdKind, Visit, VisitMut, Visitor, VisitorMut},
+//! };
+//!
+//! let mut response = Response::album(
+//!     Version::LATEST,
+//!     AlbumWithSongsID3 {
+//!         album: AlbumID3::builder("1", "Album", 2, 60).build(),
+//!         song: vec![
+//!             Child::builder("10", "One").album_id("1").build(),
+//!             Child::builder("11", "Two").album_id("1").build(),
+//!         ],
+//!     },
+//! );
+//!
+//! struct SongIds<'a>(Vec<&'a str>);
+//!
+//! impl<'a> Visitor<'a> for SongIds<'a> {
+//!     fn visit_child(&mut self, child: &'a Child) {
+//!         self.0.push(child.id.as_str());
+//!     }
+//! }
+//!
+//! let mut ids = SongIds(Vec::new());
+//! response.visit(&mut ids);
+//! assert_eq!(vec!["10", "11"], ids.0);
+//!
+//! struct PrefixAlbums;
+//!
+//! impl VisitorMut for PrefixAlbums {
+//!     fn visit_id_mut(&mut self, kind: IdKind, id: &mut String) {
+//!         if kind == IdKind::Album {
+//!             id.insert_str(0, "al-");
+//!         }
+//!     }
+//! }
+//!
+//! response.visit_mut(&mut PrefixAlbums);
+//! let ResponseBody::Album(album) = response.body else { unreachable!() };
+//! assert_eq!(album.album.id, "al-1");
+//! assert!(album.song.iter().all(|song| song.album_id == Some("al-1".into())));
+//! ```
+
+use crate::{
+    common::{
+        AlbumId, ArtistId, AudioBitrate, AverageRating, CoverArtId, DateTime, DirectoryId, Id,
+        InternetRadioStationId, MediaType, Milliseconds, MusicFolderId, PlaylistId,
+        PodcastChannelId, PodcastEpisodeId, Seconds, ShareId, SongId, UserRating, Version,
+        VideoBitrate, VideoSize,
+    },
+    request::{jukebox::JukeboxAction, lists::ListType, Request, SubsonicRequest},
+    response::{
+        response_bodies, AlbumID3, ArtistID3, Child, ErrorCode, PodcastStatus, ResponseBody,
+        ResponseStatus,
+    },
+};
+
+/// The type of an ID passed to [`Visitor::visit_id`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IdKind {
+    /// See [`Id`].
+    Id,
+    Song,
+    Directory,
+    Album,
+    Artist,
+    Playlist,
+    CoverArt,
+    PodcastChannel,
+    PodcastEpisode,
+    Share,
+    InternetRadioStation,
+}
+
+/// Called by [`Visit::visit`]. All methods do nothing by default.
+pub trait Visitor<'ast> {
+    fn visit_child(&mut self, child: &'ast Child) {
+        let _ = child;
+    }
+
+    fn visit_album_id3(&mut self, album: &'ast AlbumID3) {
+        let _ = album;
+    }
+
+    fn visit_artist_id3(&mut self, artist: &'ast ArtistID3) {
+        let _ = artist;
+    }
+
+    /// Called for every ID field. [`MusicFolderId`]s are numbers and are not visited.
+    fn visit_id(&mut self, kind: IdKind, id: &'ast str) {
+        let _ = (kind, id);
+    }
+}
+
+/// Called by [`VisitMut::visit_mut`]. All methods do nothing by default.
+pub trait VisitorMut {
+    fn visit_child_mut(&mut self, child: &mut Child) {
+        let _ = child;
+    }
+
+    fn visit_album_id3_mut(&mut self, album: &mut AlbumID3) {
+        let _ = album;
+    }
+
+    fn visit_artist_id3_mut(&mut self, artist: &mut ArtistID3) {
+        let _ = artist;
+    }
+
+    /// Called for every ID field. [`MusicFolderId`]s are numbers and are not visited.
+    fn visit_id_mut(&mut self, kind: IdKind, id: &mut String) {
+        let _ = (kind, id);
+    }
+}
+
+/// A value that can be traversed by a [`Visitor`].
+pub trait Visit {
+    fn visit<'ast, V>(&'ast self, visitor: &mut V)
+    where
+        V: Visitor<'ast> + ?Sized;
+}
+
+/// A value that can be traversed and modified by a [`VisitorMut`].
+pub trait VisitMut {
+    fn visit_mut<V>(&mut self, visitor: &mut V)
+    where
+        V: VisitorMut + ?Sized;
+}
+
+impl<T: Visit> Visit for Option<T> {
+    fn visit<'ast, V>(&'ast self, visitor: &mut V)
+    where
+        V: Visitor<'ast> + ?Sized,
+    {
+        if let Some(value) = self {
+            value.visit(visitor);
+        }
+    }
+}
+
+impl<T: VisitMut> VisitMut for Option<T> {
+    fn visit_mut<V>(&mut self, visitor: &mut V)
+    where
+        V: VisitorMut + ?Sized,
+    {
+        if let Some(value) = self {
+            value.visit_mut(visitor);
+        }
+    }
+}
+
+impl<T: Visit> Visit for Vec<T> {
+    fn visit<'ast, V>(&'ast self, visitor: &mut V)
+    where
+        V: Visitor<'ast> + ?Sized,
+    {
+        self.iter().for_each(|value| value.visit(visitor));
+    }
+}
+
+impl<T: VisitMut> VisitMut for Vec<T> {
+    fn visit_mut<V>(&mut self, visitor: &mut V)
+    where
+        V: VisitorMut + ?Sized,
+    {
+        self.iter_mut().for_each(|value| value.visit_mut(visitor));
+    }
+}
+
+macro_rules! impl_visit_for_leaf {
+    ($($t:ty),* $(,)?) => {
+        $(
+            impl Visit for $t {
+                fn visit<'ast, V>(&'ast self, _: &mut V)
+                where
+                    V: Visitor<'ast> + ?Sized,
+                {
+                }
+            }
+
+            impl VisitMut for $t {
+                fn visit_mut<V>(&mut self, _: &mut V)
+                where
+                    V: VisitorMut + ?Sized,
+                {
+                }
+            }
+        )*
+    };
+}
+
+impl_visit_for_leaf!(
+    bool,
+    u32,
+    u64,
+    f32,
+    String,
+    DateTime,
+    Milliseconds,
+    Seconds,
+    VideoSize,
+    VideoBitrate,
+    AudioBitrate,
+    MediaType,
+    UserRating,
+    AverageRating,
+    Version,
+    MusicFolderId,
+    ResponseStatus,
+    PodcastStatus,
+    ErrorCode,
+    ListType,
+    JukeboxAction,
+);
+
+/// Only the body of a request contains IDs.
+impl<R: SubsonicRequest> Visit for Request<R> {
+    fn visit<'ast, V>(&'ast self, visitor: &mut V)
+    where
+        V: Visitor<'ast> + ?Sized,
+    {
+        self.body.visit(visitor);
+    }
+}
+
+impl<R: SubsonicRequest> VisitMut for Request<R> {
+    fn visit_mut<V>(&mut self, visitor: &mut V)
+    where
+        V: VisitorMut + ?Sized,
+    {
+        self.body.visit_mut(visitor);
+    }
+}
+
+macro_rules! impl_visit_for_id {
+    ($($t:ident => $kind:ident),* $(,)?) => {
+        $(
+            impl Visit for $t {
+                fn visit<'ast, V>(&'ast self, visitor: &mut V)
+                where
+                    V: Visitor<'ast> + ?Sized,
+                {
+                    visitor.visit_id(IdKind::$kind, self.as_str());
+                }
+            }
+
+            impl VisitMut for $t {
+                fn visit_mut<V>(&mut self, visitor: &mut V)
+                where
+                    V: VisitorMut + ?Sized,
+                {
+                    visitor.visit_id_mut(IdKind::$kind, self.as_mut_string());
+                }
+            }
+        )*
+    };
+}
+
+impl_visit_for_id!(
+    Id => Id,
+    SongId => Song,
+    DirectoryId => Directory,
+    AlbumId => Album,
+    ArtistId => Artist,
+    PlaylistId => Playlist,
+    CoverArtId => CoverArt,
+    PodcastChannelId => PodcastChannel,
+    PodcastEpisodeId => PodcastEpisode,
+    ShareId => Share,
+    InternetRadioStationId => InternetRadioStation,
+);
+
+macro_rules! impl_visit_for_response_body {
+    ($(($name:ident $variant:ident($ty:ty) => $request:path)),*) => {
+        impl Visit for ResponseBody {
+            fn visit<'ast, V>(&'ast self, visitor: &mut V)
+            where
+                V: Visitor<'ast> + ?Sized,
+            {
+                match self {
+                    ResponseBody::Empty => {}
+                    $(ResponseBody::$variant(body) => body.visit(visitor),)*
+                    ResponseBody::Error(body) => body.visit(visitor),
+                }
+            }
+        }
+
+        impl VisitMut for ResponseBody {
+            fn visit_mut<V>(&mut self, visitor: &mut V)
+            where
+                V: VisitorMut + ?Sized,
+            {
+                match self {
+                    ResponseBody::Empty => {}
+                    $(ResponseBody::$variant(body) => body.visit_mut(visitor),)*
+                    ResponseBody::Error(body) => body.visit_mut(visitor),
+                }
+            }
+        }
+    };
+}
+
+response_bodies!(impl_visit_for_response_body);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        response::{NowPlaying, PodcastEpisode, Response},
+        test_support::{sample_bodies, Sample},
+    };
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Collect<'a> {
+        children: Vec<&'a Child>,
+        albums: Vec<&'a AlbumID3>,
+        artists: Vec<&'a ArtistID3>,
+        ids: Vec<(IdKind, &'a str)>,
+    }
+
+    impl<'a> Visitor<'a> for Collect<'a> {
+        fn visit_child(&mut self, child: &'a Child) {
+            self.children.push(child);
+        }
+
+        fn visit_album_id3(&mut self, album: &'a AlbumID3) {
+            self.albums.push(album);
+        }
+
+        fn visit_artist_id3(&mut self, artist: &'a ArtistID3) {
+            self.artists.push(artist);
+        }
+
+        fn visit_id(&mut self, kind: IdKind, id: &'a str) {
+            self.ids.push((kind, id));
+        }
+    }
+
+    struct Prefix;
+
+    impl VisitorMut for Prefix {
+        fn visit_child_mut(&mut self, child: &mut Child) {
+            child.title.insert_str(0, "child-");
+        }
+
+        fn visit_id_mut(&mut self, _: IdKind, id: &mut String) {
+            id.insert_str(0, "x-");
+        }
+    }
+
+    #[test]
+    fn nested_children() {
+        let sample = Child::sample();
+        let cases: Vec<(ResponseBody, usize)> = vec![
+            (ResponseBody::Directory(Sample::sample()), 1),
+            (ResponseBody::Album(Sample::sample()), 1),
+            (ResponseBody::Playlist(Sample::sample()), 1),
+            (ResponseBody::NowPlaying(NowPlaying::sample()), 1),
+            (ResponseBody::NewestPodcasts(Sample::sample()), 1),
+            (ResponseBody::Podcasts(Sample::sample()), 1),
+            (ResponseBody::Bookmarks(Sample::sample()), 1),
+            (ResponseBody::PlayQueue(Sample::sample()), 1),
+            (ResponseBody::Shares(Sample::sample()), 1),
+            (ResponseBody::Starred2(Sample::sample()), 1),
+            (ResponseBody::SearchResult3(Sample::sample()), 1),
+            (ResponseBody::JukeboxPlaylist(Sample::sample()), 1),
+            (ResponseBody::License(Sample::sample()), 0),
+        ];
+        for (body, expected) in cases {
+            let mut collect = Collect::default();
+            body.visit(&mut collect);
+            assert_eq!(expected, collect.children.len(), "{:?}", body);
+            assert!(collect.children.iter().all(|child| **child == sample));
+        }
+
+        let episode = PodcastEpisode::sample();
+        let mut collect = Collect::default();
+        episode.visit(&mut collect);
+        assert_eq!(vec![&episode.child], collect.children);
+        let kinds = collect
+            .ids
+            .iter()
+            .map(|(kind, _)| *kind)
+            .collect::<Vec<_>>();
+        assert!(kinds.contains(&IdKind::PodcastChannel));
+
+        let search = ResponseBody::SearchResult3(Sample::sample());
+        let artist = ResponseBody::Artist(Sample::sample());
+        let mut collect = Collect::default();
+        search.visit(&mut collect);
+        assert_eq!(1, collect.albums.len());
+        assert_eq!(1, collect.artists.len());
+        artist.visit(&mut collect);
+        assert_eq!(2, collect.albums.len());
+        assert_eq!(2, collect.artists.len());
+    }
+
+    #[test]
+    fn visit_every_id() {
+        for body in sample_bodies() {
+            let mut response = Response::ok(Version::LATEST, body);
+            let before = {
+                let mut collect = Collect::default();
+                response.visit(&mut collect);
+                (collect.children.len(), collect.ids.len())
+            };
+
+            response.visit_mut(&mut Prefix);
+            let mut collect = Collect::default();
+            response.visit(&mut collect);
+            assert_eq!(before, (collect.children.len(), collect.ids.len()));
+            assert!(collect.ids.iter().all(|(_, id)| id.starts_with("x-")));
+            assert!(collect
+                .children
+                .iter()
+                .all(|child| child.title.starts_with("child-")
+                    && child.id.as_str().starts_with("x-")));
+        }
+
+        let mut collect = Collect::default();
+        let song = Child::sample();
+        song.visit(&mut collect);
+        let kinds = collect
+            .ids
+            .iter()
+            .map(|(kind, _)| *kind)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                IdKind::Song,
+                IdKind::Directory,
+                IdKind::CoverArt,
+                IdKind::Album,
+                IdKind::Artist
+            ],
+            kinds
+        );
+
+        // the ID of a directory entry is a directory ID
+        let directory = Child {
+            is_dir: true,
+            ..Child::sample()
+        };
+        let mut collect = Collect::default();
+        directory.visit(&mut collect);
+        assert_eq!((IdKind::Directory, directory.id.as_str()), collect.ids[0]);
+
+        struct Kind<'a>(&'a mut Option<IdKind>);
+        impl VisitorMut for Kind<'_> {
+            fn visit_id_mut(&mut self, kind: IdKind, _: &mut String) {
+                self.0.get_or_insert(kind);
+            }
+        }
+        let mut visited = None;
+        directory.clone().visit_mut(&mut Kind(&mut visited));
+        assert_eq!(Some(IdKind::Directory), visited);
+    }
+}
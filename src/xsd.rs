@@ -0,0 +1,715 @@
+//! Validation of xml responses against the Subsonic XSD schema shipped in `schema/`.
+//!
+//! Only the subset of XML Schema used by `subsonic-rest-api.xsd` is supported: global elements,
+//! complex types with sequences, choices, attributes and extensions, and simple types restricted
+//! by enumerations or inclusive bounds. Patterns are not checked.
+
+use std::collections::HashMap;
+
+use quick_xml::{events::Event, Reader};
+
+/// The schema of the Subsonic API, version 1.16.1.
+pub(crate) const SUBSONIC_XSD: &str = include_str!("../schema/subsonic-rest-api.xsd");
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ValidationError {
+    /// The path of the element the error was found in, for example `/subsonic-response/song`.
+    pub path: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Schema {
+    target_namespace: Option<String>,
+    elements: HashMap<String, String>,
+    complex_types: HashMap<String, ComplexType>,
+    simple_types: HashMap<String, SimpleType>,
+}
+
+#[derive(Debug, Default)]
+struct ComplexType {
+    base: Option<String>,
+    mixed: bool,
+    content: Vec<Particle>,
+    attributes: Vec<Attribute>,
+}
+
+#[derive(Debug)]
+enum Particle {
+    Element {
+        name: String,
+        ty: String,
+        occurs: Occurs,
+    },
+    Sequence(Vec<Particle>),
+    Choice(Vec<Particle>, Occurs),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Occurs {
+    min: u32,
+    /// `None` if unbounded.
+    max: Option<u32>,
+}
+
+#[derive(Debug)]
+struct Attribute {
+    name: String,
+    ty: String,
+    required: bool,
+}
+
+#[derive(Debug)]
+struct SimpleType {
+    base: String,
+    enumeration: Vec<String>,
+    min_inclusive: Option<f64>,
+    max_inclusive: Option<f64>,
+}
+
+/// A parsed xml element. Names are stored without their namespace prefix.
+#[derive(Debug, Default)]
+struct Node {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Node>,
+    text: String,
+}
+
+impl Node {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl Schema {
+    pub fn subsonic() -> Self {
+        Self::parse(SUBSONIC_XSD).expect("invalid subsonic schema")
+    }
+
+    pub fn parse(xsd: &str) -> Result<Self, String> {
+        let root = parse_document(xsd)?;
+        if root.name != "schema" {
+            return Err(format!("expected schema element, found `{}`", root.name));
+        }
+
+        let mut schema = Schema {
+            target_namespace: root.attribute("targetNamespace").map(str::to_string),
+            elements: Default::default(),
+            complex_types: Default::default(),
+            simple_types: Default::default(),
+        };
+        for node in root.children.iter() {
+            let name = required_attribute(node, "name")?.to_string();
+            match node.name.as_str() {
+                "element" => {
+                    let ty = type_name(required_attribute(node, "type")?);
+                    schema.elements.insert(name, ty);
+                }
+                "complexType" => {
+                    let ty = parse_complex_type(node)?;
+                    schema.complex_types.insert(name, ty);
+                }
+                "simpleType" => {
+                    let ty = parse_simple_type(node)?;
+                    schema.simple_types.insert(name, ty);
+                }
+                other => return Err(format!("unsupported schema element `{}`", other)),
+            }
+        }
+        Ok(schema)
+    }
+
+    /// The values allowed by the simple type `ty`, empty if it is not an enumeration.
+    pub fn enumeration(&self, ty: &str) -> &[String] {
+        self.simple_types
+            .get(ty)
+            .map(|simple| simple.enumeration.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Validates an xml document, returning every error found.
+    pub fn validate(&self, xml: &str) -> Result<(), Vec<ValidationError>> {
+        let root = parse_document(xml).map_err(|message| {
+            vec![ValidationError {
+                path: "/".to_string(),
+                message,
+            }]
+        })?;
+
+        let mut errors = Vec::new();
+        let path = format!("/{}", root.name);
+        match self.elements.get(&root.name) {
+            Some(ty) => {
+                if root.attribute("xmlns") != self.target_namespace.as_deref() {
+                    errors.push(ValidationError {
+                        path: path.clone(),
+                        message: format!(
+                            "expected namespace {:?}, found {:?}",
+                            self.target_namespace,
+                            root.attribute("xmlns")
+                        ),
+                    });
+                }
+                self.validate_element(&root, ty, &path, &mut errors);
+            }
+            None => errors.push(ValidationError {
+                path,
+                message: format!("undeclared root element `{}`", root.name),
+            }),
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn validate_element(
+        &self,
+        node: &Node,
+        ty: &str,
+        path: &str,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let mut error = |message: String| {
+            errors.push(ValidationError {
+                path: path.to_string(),
+                message,
+            })
+        };
+
+        let complex = match self.complex_types.get(ty) {
+            Some(complex) => complex,
+            None => {
+                if let Some(child) = node.children.first() {
+                    error(format!(
+                        "unexpected element `{}` in simple content",
+                        child.name
+                    ));
+                }
+                if let Some((key, _)) = node.attributes.iter().find(|(k, _)| !is_namespace(k)) {
+                    error(format!("unexpected attribute `{}` in simple content", key));
+                }
+                if let Err(message) = self.check_value(ty, &node.text) {
+                    error(message);
+                }
+                return;
+            }
+        };
+
+        // the attributes and content of base types come first
+        let mut chain = vec![complex];
+        while let Some(base) = chain.last().and_then(|ty| ty.base.as_ref()) {
+            match self.complex_types.get(base) {
+                Some(base) => chain.push(base),
+                None => {
+                    error(format!("unknown base type `{}`", base));
+                    return;
+                }
+            }
+        }
+        chain.reverse();
+
+        let attributes = chain.iter().flat_map(|ty| ty.attributes.iter());
+        for attribute in attributes.clone() {
+            match node.attribute(&attribute.name) {
+                Some(value) => {
+                    if let Err(message) = self.check_value(&attribute.ty, value) {
+                        error(format!("attribute `{}`: {}", attribute.name, message));
+                    }
+                }
+                None if attribute.required => {
+                    error(format!("missing required attribute `{}`", attribute.name))
+                }
+                None => {}
+            }
+        }
+        for (key, _) in node.attributes.iter() {
+            if !is_namespace(key) && !attributes.clone().any(|a| &a.name == key) {
+                error(format!("undeclared attribute `{}`", key));
+            }
+        }
+
+        let mixed = chain.iter().any(|ty| ty.mixed);
+        if !mixed && !node.text.trim().is_empty() {
+            error(format!("unexpected text {:?}", node.text));
+        }
+
+        let content = chain
+            .iter()
+            .flat_map(|ty| ty.content.iter())
+            .collect::<Vec<_>>();
+        let mut position = 0;
+        for particle in content.iter() {
+            self.match_particle(particle, &node.children, &mut position, path, errors);
+        }
+        if let Some(child) = node.children.get(position) {
+            let mut declared = Vec::new();
+            content
+                .iter()
+                .for_each(|particle| particle_names(particle, &mut declared));
+            let message = if declared.contains(&child.name.as_str()) {
+                format!("element `{}` is out of order", child.name)
+            } else {
+                format!("undeclared element `{}`", child.name)
+            };
+            errors.push(ValidationError {
+                path: path.to_string(),
+                message,
+            });
+        }
+    }
+
+    fn match_particle(
+        &self,
+        particle: &Particle,
+        children: &[Node],
+        position: &mut usize,
+        path: &str,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        match particle {
+            Particle::Element { name, ty, occurs } => {
+                let mut count = 0;
+                while occurs.max.map(|max| count < max).unwrap_or(true) {
+                    let child = match children.get(*position) {
+                        Some(child) if &child.name == name => child,
+                        _ => break,
+                    };
+                    let child_path = format!("{}/{}", path, name);
+                    self.validate_element(child, ty, &child_path, errors);
+                    *position += 1;
+                    count += 1;
+                }
+                if count < occurs.min {
+                    errors.push(ValidationError {
+                        path: path.to_string(),
+                        message: format!(
+                            "expected at least {} `{}` element(s), found {}",
+                            occurs.min, name, count
+                        ),
+                    });
+                }
+            }
+            Particle::Sequence(particles) => {
+                for particle in particles {
+                    self.match_particle(particle, children, position, path, errors);
+                }
+            }
+            Particle::Choice(particles, occurs) => {
+                let chosen = children.get(*position).and_then(|child| {
+                    particles.iter().find(|particle| {
+                        let mut names = Vec::new();
+                        particle_names(particle, &mut names);
+                        names.contains(&child.name.as_str())
+                    })
+                });
+                match chosen {
+                    Some(particle) => {
+                        self.match_particle(particle, children, position, path, errors)
+                    }
+                    None if occurs.min > 0 => errors.push(ValidationError {
+                        path: path.to_string(),
+                        message: "no alternative of a choice matched".to_string(),
+                    }),
+                    None => {}
+                }
+            }
+        }
+    }
+
+    fn check_value(&self, ty: &str, value: &str) -> Result<(), String> {
+        let simple = match self.simple_types.get(ty) {
+            Some(simple) => simple,
+            None => return check_builtin(ty, value),
+        };
+        self.check_value(&simple.base, value)?;
+        if !simple.enumeration.is_empty() && !simple.enumeration.iter().any(|e| e == value) {
+            return Err(format!(
+                "`{}` is not one of {:?} allowed by `{}`",
+                value, simple.enumeration, ty
+            ));
+        }
+        if simple.min_inclusive.is_some() || simple.max_inclusive.is_some() {
+            let number = value
+                .parse::<f64>()
+                .map_err(|_| format!("`{}` is not a number", value))?;
+            let min = simple.min_inclusive.unwrap_or(f64::NEG_INFINITY);
+            let max = simple.max_inclusive.unwrap_or(f64::INFINITY);
+            if number < min || number > max {
+                return Err(format!("`{}` is out of the range of `{}`", value, ty));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn check_builtin(ty: &str, value: &str) -> Result<(), String> {
+    let valid = match ty {
+        "xs:string" | "xs:anyURI" => true,
+        "xs:int" => value.parse::<i32>().is_ok(),
+        "xs:long" => value.parse::<i64>().is_ok(),
+        "xs:float" | "xs:double" => value.parse::<f64>().is_ok(),
+        "xs:boolean" => matches!(value, "true" | "false" | "1" | "0"),
+        "xs:dateTime" => is_date_time(value),
+        _ => return Err(format!("unsupported type `{}`", ty)),
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("`{}` is not a valid `{}`", value, ty))
+    }
+}
+
+/// Checks the lexical form `YYYY-MM-DDThh:mm:ss[.s+][Z|(+|-)hh:mm]`.
+fn is_date_time(value: &str) -> bool {
+    fn digits(s: &str, len: usize) -> bool {
+        s.len() == len && s.bytes().all(|b| b.is_ascii_digit())
+    }
+
+    let (date, time) = match value.split_once('T') {
+        Some(parts) => parts,
+        None => return false,
+    };
+    let date = date.split('-').collect::<Vec<_>>();
+    if date.len() != 3 || !digits(date[0], 4) || !digits(date[1], 2) || !digits(date[2], 2) {
+        return false;
+    }
+
+    let (time, zone) = match time.find(['Z', '+', '-']) {
+        Some(index) => time.split_at(index),
+        None => (time, ""),
+    };
+    let (time, fraction) = match time.split_once('.') {
+        Some((time, fraction)) => (time, Some(fraction)),
+        None => (time, None),
+    };
+    let time = time.split(':').collect::<Vec<_>>();
+    if time.len() != 3 || time.iter().any(|part| !digits(part, 2)) {
+        return false;
+    }
+    if let Some(fraction) = fraction {
+        if fraction.is_empty() || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+            return false;
+        }
+    }
+    match zone {
+        "" | "Z" => true,
+        zone => match zone[1..].split_once(':') {
+            Some((hours, minutes)) => digits(hours, 2) && digits(minutes, 2),
+            None => false,
+        },
+    }
+}
+
+fn is_namespace(key: &str) -> bool {
+    key == "xmlns" || key.starts_with("xmlns:")
+}
+
+fn particle_names<'a>(particle: &'a Particle, names: &mut Vec<&'a str>) {
+    match particle {
+        Particle::Element { name, .. } => names.push(name),
+        Particle::Sequence(particles) | Particle::Choice(particles, _) => particles
+            .iter()
+            .for_each(|particle| particle_names(particle, names)),
+    }
+}
+
+/// References to builtin types keep the `xs:` prefix, references to types of the schema
+/// are stored without prefix.
+fn type_name(name: &str) -> String {
+    match name.split_once(':') {
+        Some(("xs", _)) => name.to_string(),
+        Some((_, local)) => local.to_string(),
+        None => name.to_string(),
+    }
+}
+
+fn required_attribute<'a>(node: &'a Node, name: &str) -> Result<&'a str, String> {
+    node.attribute(name)
+        .ok_or_else(|| format!("`{}` is missing attribute `{}`", node.name, name))
+}
+
+fn parse_occurs(node: &Node) -> Result<Occurs, String> {
+    let min = match node.attribute("minOccurs") {
+        Some(min) => min
+            .parse()
+            .map_err(|_| format!("invalid minOccurs `{}`", min))?,
+        None => 1,
+    };
+    let max = match node.attribute("maxOccurs") {
+        Some("unbounded") => None,
+        Some(max) => Some(
+            max.parse()
+                .map_err(|_| format!("invalid maxOccurs `{}`", max))?,
+        ),
+        None => Some(1),
+    };
+    Ok(Occurs { min, max })
+}
+
+fn parse_particle(node: &Node) -> Result<Particle, String> {
+    match node.name.as_str() {
+        "element" => Ok(Particle::Element {
+            name: required_attribute(node, "name")?.to_string(),
+            ty: type_name(required_attribute(node, "type")?),
+            occurs: parse_occurs(node)?,
+        }),
+        "sequence" => Ok(Particle::Sequence(
+            node.children
+                .iter()
+                .map(parse_particle)
+                .collect::<Result<_, _>>()?,
+        )),
+        "choice" => Ok(Particle::Choice(
+            node.children
+                .iter()
+                .map(parse_particle)
+                .collect::<Result<_, _>>()?,
+            parse_occurs(node)?,
+        )),
+        other => Err(format!("unsupported particle `{}`", other)),
+    }
+}
+
+fn parse_complex_type(node: &Node) -> Result<ComplexType, String> {
+    let mut ty = ComplexType {
+        mixed: node.attribute("mixed") == Some("true"),
+        ..Default::default()
+    };
+    parse_complex_content(node, &mut ty)?;
+    Ok(ty)
+}
+
+fn parse_complex_content(node: &Node, ty: &mut ComplexType) -> Result<(), String> {
+    for child in node.children.iter() {
+        match child.name.as_str() {
+            "sequence" | "choice" => ty.content.push(parse_particle(child)?),
+            "attribute" => ty.attributes.push(Attribute {
+                name: required_attribute(child, "name")?.to_string(),
+                ty: type_name(required_attribute(child, "type")?),
+                required: child.attribute("use") == Some("required"),
+            }),
+            "complexContent" => parse_complex_content(child, ty)?,
+            "extension" => {
+                ty.base = Some(type_name(required_attribute(child, "base")?));
+                parse_complex_content(child, ty)?;
+            }
+            other => return Err(format!("unsupported complex type element `{}`", other)),
+        }
+    }
+    Ok(())
+}
+
+fn parse_simple_type(node: &Node) -> Result<SimpleType, String> {
+    let restriction = node
+        .children
+        .iter()
+        .find(|child| child.name == "restriction")
+        .ok_or_else(|| "only restrictions are supported in simple types".to_string())?;
+    let mut ty = SimpleType {
+        base: type_name(required_attribute(restriction, "base")?),
+        enumeration: Vec::new(),
+        min_inclusive: None,
+        max_inclusive: None,
+    };
+    for facet in restriction.children.iter() {
+        let value = required_attribute(facet, "value")?;
+        let number = || {
+            value
+                .parse::<f64>()
+                .map_err(|_| format!("invalid bound `{}`", value))
+        };
+        match facet.name.as_str() {
+            "enumeration" => ty.enumeration.push(value.to_string()),
+            "minInclusive" => ty.min_inclusive = Some(number()?),
+            "maxInclusive" => ty.max_inclusive = Some(number()?),
+            "pattern" => {}
+            other => return Err(format!("unsupported facet `{}`", other)),
+        }
+    }
+    Ok(ty)
+}
+
+fn parse_document(content: &str) -> Result<Node, String> {
+    fn start(e: &quick_xml::events::BytesStart) -> Result<Node, String> {
+        let mut node = Node {
+            name: String::from_utf8_lossy(e.local_name().as_ref()).into_owned(),
+            ..Default::default()
+        };
+        for attribute in e.attributes() {
+            let attribute = attribute.map_err(|e| e.to_string())?;
+            let key = String::from_utf8_lossy(attribute.key.as_ref()).into_owned();
+            let value = attribute.unescape_value().map_err(|e| e.to_string())?;
+            node.attributes.push((key, value.into_owned()));
+        }
+        Ok(node)
+    }
+
+    let mut reader = Reader::from_str(content);
+    let mut stack: Vec<Node> = Vec::new();
+    loop {
+        let event = reader.read_event().map_err(|e| e.to_string())?;
+        let finished = match event {
+            Event::Start(e) => {
+                stack.push(start(&e)?);
+                None
+            }
+            Event::Empty(e) => Some(start(&e)?),
+            Event::End(_) => stack.pop(),
+            Event::Text(e) => {
+                let text = e.unescape().map_err(|e| e.to_string())?;
+                if let Some(node) = stack.last_mut() {
+                    node.text.push_str(&text);
+                }
+                None
+            }
+            Event::CData(e) => {
+                if let Some(node) = stack.last_mut() {
+                    node.text.push_str(&String::from_utf8_lossy(&e));
+                }
+                None
+            }
+            Event::Eof => return Err("unexpected end of document".to_string()),
+            _ => None,
+        };
+        if let Some(node) = finished {
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(node),
+                None => return Ok(node),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::{MediaType, Version},
+        response::{
+            Child, Error, ErrorCode, NewestPodcasts, PodcastEpisode, PodcastStatus, Response,
+            ResponseBody, XmlOptions,
+        },
+        test_support::Sample,
+    };
+
+    use super::*;
+
+    fn assert_valid(schema: &Schema, response: &Response) {
+        let xml = response.to_xml_with(XmlOptions::spec()).unwrap();
+        if let Err(errors) = schema.validate(&xml) {
+            let errors = errors.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+            panic!("{}\n{}", xml, errors.join("\n"));
+        }
+    }
+
+    #[test]
+    fn response_bodies() {
+        let schema = Schema::subsonic();
+        for body in crate::test_support::sample_bodies() {
+            assert_valid(&schema, &Response::ok(Version::LATEST, body));
+        }
+        let error = Error::with_message(ErrorCode::Generic, "error");
+        assert_valid(&schema, &Response::failed(Version::LATEST, error));
+    }
+
+    #[test]
+    fn enum_values() {
+        fn values<T: serde::de::DeserializeOwned>(schema: &Schema, ty: &str) -> Vec<T> {
+            let values = schema.enumeration(ty);
+            assert!(!values.is_empty(), "`{}` is not an enumeration", ty);
+            values
+                .iter()
+                .map(|value| {
+                    serde_json::from_value(serde_json::Value::from(value.as_str()))
+                        .unwrap_or_else(|_| panic!("no `{}` variant for `{}`", ty, value))
+                })
+                .collect()
+        }
+
+        let schema = Schema::subsonic();
+        for status in values::<PodcastStatus>(&schema, "PodcastStatus") {
+            for media_type in values::<MediaType>(&schema, "MediaType") {
+                let episode = PodcastEpisode {
+                    child: Child {
+                        media_type: Some(media_type),
+                        ..Sample::sample()
+                    },
+                    status,
+                    ..Sample::sample()
+                };
+                let body = ResponseBody::NewestPodcasts(NewestPodcasts {
+                    episode: vec![episode],
+                });
+                assert_valid(&schema, &Response::ok(Version::LATEST, body));
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_documents() {
+        let schema = Schema::subsonic();
+        let validate = |body: &str| {
+            let xml = format!(
+                r#"<subsonic-response xmlns="http://subsonic.org/restapi" status="ok" version="1.16.1">{}</subsonic-response>"#,
+                body
+            );
+            schema
+                .validate(&xml)
+                .unwrap_err()
+                .into_iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            validate(
+                r#"<starred><song id="1" isDir="false" title="a"/><artist id="2" name="b"/></starred>"#
+            ),
+            ["/subsonic-response/starred: element `artist` is out of order"]
+        );
+        assert_eq!(
+            validate(r#"<song id="1" title="a"/>"#),
+            ["/subsonic-response/song: missing required attribute `isDir`"]
+        );
+        assert_eq!(
+            validate(r#"<song id="1" isDir="false" title="a" type="radio" rating="5"/>"#),
+            [
+                r#"/subsonic-response/song: attribute `type`: `radio` is not one of ["music", "podcast", "audiobook", "video"] allowed by `MediaType`"#,
+                "/subsonic-response/song: undeclared attribute `rating`"
+            ]
+        );
+        assert_eq!(
+            validate(r#"<song id="1" isDir="false" title="a" userRating="6" created="yesterday"/>"#),
+            [
+                "/subsonic-response/song: attribute `userRating`: `6` is out of the range of `UserRating`",
+                "/subsonic-response/song: attribute `created`: `yesterday` is not a valid `xs:dateTime`"
+            ]
+        );
+        assert_eq!(
+            validate(r#"<bookmarks><bookmark position="0" username="a" created="2023-01-01T00:00:00" changed="2023-01-01T00:00:00Z"/></bookmarks>"#),
+            ["/subsonic-response/bookmarks/bookmark: expected at least 1 `entry` element(s), found 0"]
+        );
+        assert_eq!(
+            validate(r#"<license valid="true"/><foo/>"#),
+            ["/subsonic-response: undeclared element `foo`"]
+        );
+
+        let errors = schema
+            .validate(r#"<subsonic-response status="ok" version="1.16.1"/>"#)
+            .unwrap_err();
+        assert_eq!(1, errors.len());
+        assert!(errors[0].message.starts_with("expected namespace"));
+    }
+}
@@ -3,11 +3,17 @@ use syn::Result;
 pub const RENAME: AttrName = AttrName::new("rename");
 pub const FLATTEN: AttrName = AttrName::new("flatten");
 pub const SINCE: AttrName = AttrName::new("since");
+pub const UNTIL: AttrName = AttrName::new("until");
+pub const DEPRECATED: AttrName = AttrName::new("deprecated");
 pub const PATH: AttrName = AttrName::new("path");
 pub const ATTRIBUTE: AttrName = AttrName::new("attribute");
 pub const VALUE: AttrName = AttrName::new("value");
 pub const SERDE: AttrName = AttrName::new("serde");
 pub const CHOICE: AttrName = AttrName::new("choice");
+pub const BUILDER: AttrName = AttrName::new("builder");
+pub const VISIT: AttrName = AttrName::new("visit");
+pub const VISIT_ID: AttrName = AttrName::new("visit_id");
+pub const MUTATING: AttrName = AttrName::new("mutating");
 
 pub struct AttrName(&'static str);
 
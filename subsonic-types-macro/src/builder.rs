@@ -0,0 +1,155 @@
+//! Generation of `<Type>::builder` and `<Type>Builder`, shared by the request and response derives.
+
+use proc_macro2::TokenStream;
+
+use crate::util;
+
+pub enum FieldKind<'a> {
+    /// Passed to `builder`.
+    Required,
+    /// `Option<T>`, the setter takes a `T`.
+    Optional(&'a syn::Type),
+    /// `Vec<T>`, each call of the setter adds a `T`.
+    Repeated(&'a syn::Type),
+    /// Starts as `Default::default()` and has a setter.
+    Defaulted,
+}
+
+pub struct Field<'a> {
+    pub ident: &'a syn::Ident,
+    pub ty: &'a syn::Type,
+    pub attrs: &'a [syn::Attribute],
+    pub kind: FieldKind<'a>,
+}
+
+impl<'a> Field<'a> {
+    /// Fields that are not an `Option` or a `Vec` are required.
+    pub fn from_type(
+        ident: &'a syn::Ident,
+        ty: &'a syn::Type,
+        attrs: &'a [syn::Attribute],
+    ) -> Self {
+        let kind = match util::type_argument(ty) {
+            Some(inner) if util::type_is_option(ty) => FieldKind::Optional(inner),
+            Some(inner) if util::type_is_vec(ty) => FieldKind::Repeated(inner),
+            _ => FieldKind::Required,
+        };
+        Self {
+            ident,
+            ty,
+            attrs,
+            kind,
+        }
+    }
+}
+
+pub fn expand(input: &syn::DeriveInput, fields: &[Field]) -> TokenStream {
+    let vis = &input.vis;
+    let container_ident = &input.ident;
+    let builder_ident = quote::format_ident!("{}Builder", container_ident);
+    let builder_doc = format!(
+        "Builder for [`{0}`], created with [`{0}::builder`].",
+        container_ident
+    );
+
+    let mut parameters = Vec::new();
+    let mut initializers = Vec::new();
+    let mut setters = Vec::new();
+    for field in fields {
+        let ident = field.ident;
+        let docs = field.attrs.iter().filter(|a| a.path.is_ident("doc"));
+        match field.kind {
+            FieldKind::Required => {
+                let argument = setter_argument(field.ty);
+                parameters.push(quote::quote! { #ident: #argument });
+                initializers.push(quote::quote! { #ident: #ident.into() });
+            }
+            FieldKind::Optional(inner) => {
+                let argument = setter_argument(inner);
+                initializers.push(quote::quote! { #ident: None });
+                setters.push(quote::quote! {
+                    #(#docs)*
+                    pub fn #ident(mut self, #ident: #argument) -> Self {
+                        self.value.#ident = Some(#ident.into());
+                        self
+                    }
+                });
+            }
+            FieldKind::Repeated(inner) => {
+                let argument = setter_argument(inner);
+                initializers.push(quote::quote! { #ident: Vec::new() });
+                setters.push(quote::quote! {
+                    #(#docs)*
+                    ///
+                    /// Can be called multiple times, each call adds a value.
+                    pub fn #ident(mut self, #ident: #argument) -> Self {
+                        self.value.#ident.push(#ident.into());
+                        self
+                    }
+                });
+            }
+            FieldKind::Defaulted => {
+                let argument = setter_argument(field.ty);
+                initializers.push(quote::quote! { #ident: Default::default() });
+                setters.push(quote::quote! {
+                    #(#docs)*
+                    pub fn #ident(mut self, #ident: #argument) -> Self {
+                        self.value.#ident = #ident.into();
+                        self
+                    }
+                });
+            }
+        }
+    }
+
+    quote::quote! {
+        impl #container_ident {
+            /// Creates a builder with the required fields.
+            #vis fn builder(#(#parameters),*) -> #builder_ident {
+                #builder_ident {
+                    value: #container_ident {
+                        #(#initializers,)*
+                    },
+                }
+            }
+        }
+
+        #[doc = #builder_doc]
+        #[derive(Debug, Clone, PartialEq)]
+        #[must_use]
+        #vis struct #builder_ident {
+            value: #container_ident,
+        }
+
+        impl #builder_ident {
+            #(#setters)*
+
+            pub fn build(self) -> #container_ident {
+                self.value
+            }
+        }
+
+        impl From<#builder_ident> for #container_ident {
+            fn from(builder: #builder_ident) -> Self {
+                builder.build()
+            }
+        }
+    }
+}
+
+/// Primitives are taken by value so that integer literals infer the right type,
+/// everything else accepts `impl Into<T>`.
+fn setter_argument(ty: &syn::Type) -> TokenStream {
+    const PRIMITIVES: &[&str] = &[
+        "bool", "u8", "u16", "u32", "u64", "usize", "i8", "i16", "i32", "i64", "isize", "f32",
+        "f64",
+    ];
+    match ty {
+        syn::Type::Path(syn::TypePath { qself: None, path })
+            if PRIMITIVES.iter().any(|p| path.is_ident(p)) =>
+        {
+            quote::quote! { #ty }
+        }
+        _ => quote::quote! { impl Into<#ty> },
+    }
+}
@@ -1,4 +1,5 @@
 mod attr;
+mod builder;
 mod query;
 mod request;
 mod response;
@@ -120,6 +120,7 @@ pub fn to_query(input: syn::DeriveInput) -> Result<TokenStream> {
 pub fn to_query_struct(container: &Container, fields: &[Field]) -> Result<TokenStream> {
     let container_ident = container.ident;
     let (impl_g, type_g, where_g) = container.generics.split_for_impl();
+    let query_parameters = query_parameters_impl(container, fields);
     let fields = fields_to_query(fields);
 
     let output = quote::quote! {
@@ -132,10 +133,59 @@ pub fn to_query_struct(container: &Container, fields: &[Field]) -> Result<TokenS
                 #(#fields)*
             }
         }
+
+        #query_parameters
     };
     Ok(output)
 }
 
+/// Generates the `QueryParameters` implementation used by the `openapi` feature.
+fn query_parameters_impl(container: &Container, fields: &[Field]) -> TokenStream {
+    let container_ident = container.ident;
+    let (impl_g, type_g, _) = container.generics.split_for_impl();
+    let mut predicates = container
+        .generics
+        .where_clause
+        .iter()
+        .flat_map(|w| w.predicates.iter())
+        .map(|p| quote::quote! { #p })
+        .collect::<Vec<_>>();
+    predicates.extend(fields.iter().filter(|f| f.attrs.flatten).map(|f| {
+        let ty = f.ty;
+        quote::quote! { #ty: crate::openapi::QueryParameters }
+    }));
+
+    let parameters = fields.iter().map(|field| {
+        let field_ty = field.ty;
+        if field.attrs.flatten {
+            quote::quote! {
+                <#field_ty as crate::openapi::QueryParameters>::query_parameters(generator, parameters);
+            }
+        } else {
+            let field_name = field_name(field);
+            quote::quote! {
+                crate::openapi::query_parameter::<#field_ty>(generator, #field_name, parameters);
+            }
+        }
+    });
+
+    quote::quote! {
+        #[cfg(feature = "openapi")]
+        impl #impl_g crate::openapi::QueryParameters for #container_ident #type_g
+        where
+            #(#predicates),*
+        {
+            #[allow(unused_variables)]
+            fn query_parameters(
+                generator: &mut crate::openapi::SchemaGenerator,
+                parameters: &mut Vec<serde_json::Value>,
+            ) {
+                #(#parameters)*
+            }
+        }
+    }
+}
+
 pub fn from_query(input: syn::DeriveInput) -> Result<TokenStream> {
     let container = Container::from_input(&input)?;
     match &container.data {
@@ -1,10 +1,11 @@
 use syn::Result;
 
-use crate::{attr, version::Version};
+use crate::{attr, builder, version::Version};
 
 struct ContainerAttributes {
     since: Version,
     path: String,
+    mutating: bool,
 }
 
 impl ContainerAttributes {
@@ -12,6 +13,7 @@ impl ContainerAttributes {
         let metas = attr::extract_meta_list(attrs)?;
         let mut since = None;
         let mut path = None;
+        let mut mutating = false;
 
         for meta in metas {
             match &meta {
@@ -41,6 +43,9 @@ impl ContainerAttributes {
                         path = Some(value.value());
                     }
                 }
+                syn::Meta::Path(p) if attr::MUTATING == p => {
+                    mutating = true;
+                }
                 _ => return Err(syn::Error::new_spanned(meta, "Invalid subsonic attribute")),
             }
         }
@@ -58,7 +63,11 @@ impl ContainerAttributes {
             )
         })?;
 
-        Ok(Self { since, path })
+        Ok(Self {
+            since,
+            path,
+            mutating,
+        })
     }
 }
 
@@ -68,12 +77,71 @@ pub fn expand(mut input: syn::DeriveInput) -> Result<proc_macro2::TokenStream> {
 
     let path = format!("/rest/{}", container_attrs.path);
     let since = &container_attrs.since;
+    let mutating = container_attrs.mutating;
+    let builder = builder_impl(&input);
+    let visit = visit_impl(&input);
     let output = quote::quote! {
         impl crate::request::SubsonicRequest for #container_ident {
             const PATH: &'static str = #path;
             const SINCE: crate::common::Version = #since;
+            const MUTATING: bool = #mutating;
         }
+
+        #builder
+        #visit
     };
 
     Ok(output)
 }
+
+/// Generates `Visit` and `VisitMut`, which visit every field of the request.
+fn visit_impl(input: &syn::DeriveInput) -> proc_macro2::TokenStream {
+    let container_ident = &input.ident;
+    let idents = match &input.data {
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Named(fields),
+            ..
+        }) => fields.named.iter().filter_map(|f| f.ident.as_ref()).collect(),
+        _ => Vec::new(),
+    };
+
+    quote::quote! {
+        #[automatically_derived]
+        impl crate::visit::Visit for #container_ident {
+            #[allow(unused_variables)]
+            fn visit<'ast, V>(&'ast self, visitor: &mut V)
+            where
+                V: crate::visit::Visitor<'ast> + ?Sized,
+            {
+                #(crate::visit::Visit::visit(&self.#idents, visitor);)*
+            }
+        }
+
+        #[automatically_derived]
+        impl crate::visit::VisitMut for #container_ident {
+            #[allow(unused_variables)]
+            fn visit_mut<V>(&mut self, visitor: &mut V)
+            where
+                V: crate::visit::VisitorMut + ?Sized,
+            {
+                #(crate::visit::VisitMut::visit_mut(&mut self.#idents, visitor);)*
+            }
+        }
+    }
+}
+
+/// Generates `<Request>::builder` and `<Request>Builder` for requests with named fields.
+fn builder_impl(input: &syn::DeriveInput) -> proc_macro2::TokenStream {
+    let fields = match &input.data {
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Named(fields),
+            ..
+        }) if !fields.named.is_empty() => &fields.named,
+        _ => return Default::default(),
+    };
+    let fields = fields
+        .iter()
+        .map(|f| builder::Field::from_type(f.ident.as_ref().unwrap(), &f.ty, &f.attrs))
+        .collect::<Vec<_>>();
+    builder::expand(input, &fields)
+}
@@ -7,23 +7,43 @@ pub struct ContainerAttr {
     /// This container implements Serialize/Deserialize and the implementation should
     /// delegate to those traits.
     pub serde: bool,
+    /// Generate `<Type>::builder` and `<Type>Builder`.
+    pub builder: bool,
+    /// The visitor method called with this type, e.g. `child` for `Visitor::visit_child`.
+    pub visit: Option<syn::Ident>,
 }
 
 impl ContainerAttr {
     pub fn from_attrs(attrs: &[syn::Attribute]) -> Result<Self> {
         let metas = obtain_meta_list(attrs)?;
         let mut serde = false;
+        let mut builder = false;
+        let mut visit = None;
 
         for meta in metas {
             match meta {
                 syn::Meta::Path(p) if SERDE == p => {
                     serde = true;
                 }
+                syn::Meta::Path(p) if BUILDER == p => {
+                    builder = true;
+                }
+                syn::Meta::NameValue(syn::MetaNameValue {
+                    path,
+                    lit: syn::Lit::Str(value),
+                    ..
+                }) if VISIT == path => {
+                    visit = Some(value.parse()?);
+                }
                 _ => {}
             }
         }
 
-        Ok(Self { serde })
+        Ok(Self {
+            serde,
+            builder,
+            visit,
+        })
     }
 }
 
@@ -45,6 +65,15 @@ pub struct FieldAttr {
     pub choice: bool,
     /// The version since this field was added.
     pub since: Option<Version>,
+    /// The version in which this field was removed.
+    /// The field is not serialized and is ignored when deserializing for versions `>= until`.
+    pub until: Option<Version>,
+    /// The version since this field is deprecated.
+    /// This does not change serialization, it is only exposed as metadata.
+    pub deprecated: Option<Version>,
+    /// The method of the container returning the `IdKind` of this ID field, for IDs whose kind
+    /// depends on the value, e.g. `id_kind` for `Child::id_kind`.
+    pub visit_id: Option<syn::Ident>,
 }
 
 impl FieldAttr {
@@ -56,6 +85,10 @@ impl FieldAttr {
         let mut value = false;
         let mut choice = false;
         let mut since = None;
+        let mut until = None;
+        let mut deprecated = None;
+        let mut visit_id = None;
+        let mut span = proc_macro2::Span::call_site();
 
         for meta in metas {
             match meta {
@@ -77,16 +110,30 @@ impl FieldAttr {
                     choice = true;
                 }
                 syn::Meta::NameValue(nv) if SINCE == nv.path => {
-                    if let syn::Lit::Str(s) = &nv.lit {
-                        since = Version::parse(&s.value())
-                            .ok_or_else(|| syn::Error::new_spanned(nv, "Invalid version".to_string()))
-                            .map(Some)?;
-                    }
+                    since = Some(parse_version(&nv)?);
+                    span = syn::spanned::Spanned::span(&nv);
+                }
+                syn::Meta::NameValue(nv) if UNTIL == nv.path => {
+                    until = Some(parse_version(&nv)?);
+                    span = syn::spanned::Spanned::span(&nv);
+                }
+                syn::Meta::NameValue(nv) if DEPRECATED == nv.path => {
+                    deprecated = Some(parse_version(&nv)?);
+                    span = syn::spanned::Spanned::span(&nv);
+                }
+                syn::Meta::NameValue(syn::MetaNameValue {
+                    path,
+                    lit: syn::Lit::Str(value),
+                    ..
+                }) if VISIT_ID == path => {
+                    visit_id = Some(value.parse()?);
                 }
                 _ => return Err(syn::Error::new_spanned(meta, "Invalid subsonic attribute")),
             }
         }
 
+        validate_versions(span, since, until, deprecated)?;
+
         if choice && (flatten || attribute || value || rename.is_some()) {
             return Err(syn::Error::new(
                 proc_macro2::Span::call_site(),
@@ -101,6 +148,106 @@ impl FieldAttr {
             value,
             choice,
             since,
+            until,
+            deprecated,
+            visit_id,
         })
     }
 }
+
+pub struct VariantAttr {
+    /// The version since this variant was added.
+    pub since: Option<Version>,
+    /// The version in which this variant was removed.
+    /// `deprecated` is also accepted for variants but is only validated.
+    pub until: Option<Version>,
+}
+
+impl VariantAttr {
+    pub fn from_attrs(attrs: &[syn::Attribute]) -> Result<Self> {
+        let metas = obtain_meta_list(attrs)?;
+        let mut since = None;
+        let mut until = None;
+        let mut deprecated = None;
+        let mut span = proc_macro2::Span::call_site();
+
+        for meta in metas {
+            match meta {
+                syn::Meta::NameValue(nv) if SINCE == nv.path => {
+                    since = Some(parse_version(&nv)?);
+                    span = syn::spanned::Spanned::span(&nv);
+                }
+                syn::Meta::NameValue(nv) if UNTIL == nv.path => {
+                    until = Some(parse_version(&nv)?);
+                    span = syn::spanned::Spanned::span(&nv);
+                }
+                syn::Meta::NameValue(nv) if DEPRECATED == nv.path => {
+                    deprecated = Some(parse_version(&nv)?);
+                    span = syn::spanned::Spanned::span(&nv);
+                }
+                _ => return Err(syn::Error::new_spanned(meta, "Invalid subsonic attribute")),
+            }
+        }
+
+        validate_versions(span, since, until, deprecated)?;
+
+        Ok(Self { since, until })
+    }
+}
+
+/// Returns the condition under which a field or variant with the given `since` and `until`
+/// versions is present in `version`.
+pub fn version_cond(
+    since: Option<Version>,
+    until: Option<Version>,
+    version: &syn::Ident,
+) -> proc_macro2::TokenStream {
+    match (since, until) {
+        (Some(since), Some(until)) => quote::quote! { (#version >= #since && #version < #until) },
+        (Some(since), None) => quote::quote! { #version >= #since },
+        (None, Some(until)) => quote::quote! { #version < #until },
+        (None, None) => quote::quote! { true },
+    }
+}
+
+fn parse_version(nv: &syn::MetaNameValue) -> Result<Version> {
+    match &nv.lit {
+        syn::Lit::Str(s) => Version::parse(&s.value())
+            .ok_or_else(|| syn::Error::new_spanned(nv, "Invalid version")),
+        _ => Err(syn::Error::new_spanned(nv, "Expected a version string")),
+    }
+}
+
+fn validate_versions(
+    span: proc_macro2::Span,
+    since: Option<Version>,
+    until: Option<Version>,
+    deprecated: Option<Version>,
+) -> Result<()> {
+    let error = |msg: String| syn::Error::new(span, msg);
+    if let (Some(since), Some(until)) = (since, until) {
+        if since >= until {
+            return Err(error(format!(
+                "`since` ({}) must be lower than `until` ({})",
+                since, until
+            )));
+        }
+    }
+    if let (Some(since), Some(deprecated)) = (since, deprecated) {
+        if deprecated < since {
+            return Err(error(format!(
+                "`deprecated` ({}) must not be lower than `since` ({})",
+                deprecated, since
+            )));
+        }
+    }
+    if let (Some(deprecated), Some(until)) = (deprecated, until) {
+        if deprecated >= until {
+            return Err(error(format!(
+                "`deprecated` ({}) must be lower than `until` ({})",
+                deprecated, until
+            )));
+        }
+    }
+    Ok(())
+}
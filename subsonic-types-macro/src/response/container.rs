@@ -24,6 +24,7 @@ pub struct Field<'a> {
 pub struct Variant<'a> {
     pub ident: &'a syn::Ident,
     pub ty: &'a syn::Type,
+    pub attrs: attr::VariantAttr,
 }
 
 impl<'a> Container<'a> {
@@ -42,7 +43,7 @@ impl<'a> Container<'a> {
     }
 }
 
-fn input_get_data(input: &syn::DeriveInput) -> Result<Data> {
+fn input_get_data(input: &syn::DeriveInput) -> Result<Data<'_>> {
     match &input.data {
         syn::Data::Struct(data) => data_struct_to_data(data),
         syn::Data::Enum(data) => data_enum_to_data(data),
@@ -53,7 +54,7 @@ fn input_get_data(input: &syn::DeriveInput) -> Result<Data> {
     }
 }
 
-fn data_struct_to_data(data: &syn::DataStruct) -> Result<Data> {
+fn data_struct_to_data(data: &syn::DataStruct) -> Result<Data<'_>> {
     let mut fields = Vec::with_capacity(data.fields.len());
     for field in data.fields.iter() {
         fields.push(Field {
@@ -68,7 +69,7 @@ fn data_struct_to_data(data: &syn::DataStruct) -> Result<Data> {
 }
 
 /// Zero-field variants are ignored.
-fn data_enum_to_data(data: &syn::DataEnum) -> Result<Data> {
+fn data_enum_to_data(data: &syn::DataEnum) -> Result<Data<'_>> {
     let mut variants = Vec::with_capacity(data.variants.len());
 
     for variant in data.variants.iter() {
@@ -90,6 +91,7 @@ fn data_enum_to_data(data: &syn::DataEnum) -> Result<Data> {
         variants.push(Variant {
             ident: &variant.ident,
             ty,
+            attrs: attr::VariantAttr::from_attrs(&variant.attrs)?,
         })
     }
 
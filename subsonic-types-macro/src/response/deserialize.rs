@@ -158,9 +158,14 @@ fn struct_field_option_unwrap(field: &Field) -> TokenStream {
                 )
             })?;
         };
-        if let Some(since) = field.attrs.since {
+        if field.attrs.since.is_some() || field.attrs.until.is_some() {
+            let cond = attr::version_cond(
+                field.attrs.since,
+                field.attrs.until,
+                &quote::format_ident!("__version"),
+            );
             tokens = quote::quote! {
-                let #field_ident = if __version >= #since {
+                let #field_ident = if #cond {
                     #tokens
                     #field_ident
                 } else {
@@ -212,8 +217,10 @@ fn struct_field_match_arm(field: &Field) -> TokenStream {
     let field_ty = field.ty;
     let field_ident = field.ident;
     let key_ident = struct_field_key_ident(field);
+    // Fields removed in this version are treated as unknown keys.
+    let cond = attr::version_cond(None, field.attrs.until, &quote::format_ident!("__version"));
     quote::quote! {
-        k if k == #key_ident => {
+        k if k == #key_ident && #cond => {
             #field_ident = Some(map.next_value_seed(
                 <<#field_ty as crate::deser::SubsonicDeserialize>::Seed as From<(
                     crate::common::Format,
@@ -318,6 +325,7 @@ fn expand_enum(container: &Container, variants: &[Variant]) -> Result<TokenStrea
             where
                 A: serde::de::MapAccess<'de>
             {
+                let __version = self.1;
                 while let Some(key) = map.next_key::<String>()? {
                     match key.as_str() {
                         #(#match_arms)*
@@ -365,8 +373,13 @@ fn enum_variant_match_arm(container: &Container, variant: &Variant) -> TokenStre
     let variant_ident = variant.ident;
     let variant_name = util::string_to_camel_case(&variant_ident.to_string());
     let variant_ty = variant.ty;
+    let cond = attr::version_cond(
+        variant.attrs.since,
+        variant.attrs.until,
+        &quote::format_ident!("__version"),
+    );
     quote::quote! {
-        #variant_name => {
+        #variant_name if #cond => {
             let __v = map.next_value_seed(
                 <<#variant_ty as crate::deser::SubsonicDeserialize>::Seed as From<(
                     crate::common::Format,
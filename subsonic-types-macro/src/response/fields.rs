@@ -0,0 +1,191 @@
+use proc_macro2::TokenStream;
+use syn::Result;
+
+use crate::{util, version::Version};
+
+use super::{
+    attr,
+    container::{Container, Data, Field},
+};
+
+pub fn expand(input: &syn::DeriveInput) -> Result<TokenStream> {
+    let container_attrs = attr::ContainerAttr::from_attrs(&input.attrs)?;
+    if container_attrs.serde {
+        return Ok(Default::default());
+    }
+
+    let container = Container::from_input(input)?;
+    let output = match container.data {
+        Data::Struct(ref fields) => expand_struct(&container, fields),
+        Data::Enum(_) => Default::default(),
+    };
+    Ok(output)
+}
+
+fn expand_struct(container: &Container, fields: &[Field]) -> TokenStream {
+    let container_ident = container.ident;
+    let field_infos = fields
+        .iter()
+        .filter(|f| !f.attrs.flatten)
+        .map(struct_field_info);
+    let flattened_tys = fields
+        .iter()
+        .filter(|f| f.attrs.flatten)
+        .map(|f| f.ty)
+        .collect::<Vec<_>>();
+
+    let find_field = if flattened_tys.is_empty() {
+        quote::quote! {}
+    } else {
+        quote::quote! {
+            fn find_field(name: &str) -> Option<&'static crate::capabilities::FieldInfo> {
+                Self::FIELDS
+                    .iter()
+                    .find(|f| f.name == name || f.key == name)
+                    #(
+                        .or_else(|| <#flattened_tys as crate::capabilities::SubsonicFields>::find_field(name))
+                    )*
+            }
+        }
+    };
+
+    let versions_test = struct_versions_test(container, fields);
+    let sample_impl = struct_sample_impl(container, fields);
+    let json_schema_impl = struct_json_schema_impl(container, fields);
+
+    quote::quote! {
+        impl crate::capabilities::SubsonicFields for #container_ident {
+            const FIELDS: &'static [crate::capabilities::FieldInfo] = &[
+                #(#field_infos),*
+            ];
+
+            #find_field
+        }
+
+        #versions_test
+
+        #sample_impl
+
+        #json_schema_impl
+    }
+}
+
+/// Generates the `JsonSchema` implementation used by the `openapi` feature.
+/// Properties use the same metadata as `SubsonicFields`, flattened fields are merged.
+fn struct_json_schema_impl(container: &Container, fields: &[Field]) -> TokenStream {
+    let container_ident = container.ident;
+    let name = container_ident.to_string();
+    let mut index = 0usize;
+    let entries = fields
+        .iter()
+        .map(|f| {
+            let ty = f.ty;
+            if f.attrs.flatten {
+                quote::quote! { object.flatten::<#ty>(generator); }
+            } else {
+                let field_index = index;
+                index += 1;
+                quote::quote! { object.property::<#ty>(generator, &fields[#field_index]); }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    quote::quote! {
+        #[cfg(feature = "openapi")]
+        impl crate::openapi::JsonSchema for #container_ident {
+            fn schema_name() -> Option<String> {
+                Some(#name.to_string())
+            }
+
+            #[allow(unused_variables)]
+            fn json_schema(generator: &mut crate::openapi::SchemaGenerator) -> serde_json::Value {
+                let fields = <Self as crate::capabilities::SubsonicFields>::FIELDS;
+                let mut object = crate::openapi::ObjectSchema::default();
+                #(#entries)*
+                object.into_schema()
+            }
+        }
+    }
+}
+
+/// Generates a test only implementation of `Sample` that sets every field, including optional
+/// and versioned ones, so the value exercises the whole type when serialized.
+fn struct_sample_impl(container: &Container, fields: &[Field]) -> TokenStream {
+    let container_ident = container.ident;
+    let field_idents = fields.iter().map(|f| f.ident);
+    quote::quote! {
+        #[cfg(test)]
+        impl crate::test_support::Sample for #container_ident {
+            fn sample() -> Self {
+                Self {
+                    #(#field_idents: crate::test_support::Sample::sample()),*
+                }
+            }
+        }
+    }
+}
+
+/// Generates a test that checks that serialization respects the `since`/`until` versions
+/// of each field. Only generated for containers with versioned fields, which must implement
+/// `Default`.
+fn struct_versions_test(container: &Container, fields: &[Field]) -> TokenStream {
+    let versioned = fields
+        .iter()
+        .filter(|f| !f.attrs.flatten && (f.attrs.since.is_some() || f.attrs.until.is_some()))
+        .collect::<Vec<_>>();
+    if versioned.is_empty() {
+        return Default::default();
+    }
+
+    let container_ident = container.ident;
+    let mod_ident = quote::format_ident!("__subsonic_versions_{}", container_ident);
+    let assigns = versioned
+        .iter()
+        .filter(|f| util::type_is_option(f.ty))
+        .map(|f| {
+            let field_ident = f.ident;
+            quote::quote! { value.#field_ident = Some(crate::test_support::Sample::sample()); }
+        });
+
+    quote::quote! {
+        #[cfg(test)]
+        #[allow(non_snake_case)]
+        mod #mod_ident {
+            #[test]
+            fn versioned_fields() {
+                let mut value = <super::#container_ident as Default>::default();
+                #(#assigns)*
+                crate::test_support::assert_versioned_fields(&value);
+            }
+        }
+    }
+}
+
+fn struct_field_info(field: &Field) -> TokenStream {
+    let name = field.ident.to_string();
+    let key = match field.attrs.rename {
+        Some(ref key) => util::string_to_camel_case(key),
+        None => util::string_to_camel_case(&name),
+    };
+    let attribute = field.attrs.attribute;
+    let since = option_version(field.attrs.since);
+    let until = option_version(field.attrs.until);
+    let deprecated = option_version(field.attrs.deprecated);
+    quote::quote! {
+        crate::capabilities::FieldInfo {
+            name: #name,
+            key: #key,
+            attribute: #attribute,
+            since: #since,
+            until: #until,
+            deprecated: #deprecated,
+        }
+    }
+}
+
+fn option_version(version: Option<Version>) -> TokenStream {
+    match version {
+        Some(version) => quote::quote! { Some(#version) },
+        None => quote::quote! { None },
+    }
+}
@@ -1,16 +1,59 @@
 mod attr;
 mod container;
 mod deserialize;
+mod fields;
 mod serialize;
+mod visit;
 
 pub fn expand(input: syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
     let serialize_tokens = serialize::expand(&input)?;
     let deserialize_tokens = deserialize::expand(&input)?;
+    let fields_tokens = fields::expand(&input)?;
+    let builder_tokens = builder(&input)?;
+    let visit_tokens = visit::expand(&input)?;
 
     let output = quote::quote! {
         #serialize_tokens
         #deserialize_tokens
+        #fields_tokens
+        #builder_tokens
+        #visit_tokens
     };
 
     Ok(output)
 }
+
+/// Generates a builder for structs with `#[subsonic(builder)]`.
+///
+/// Flattened fields are required, `bool` fields default to `false`.
+fn builder(input: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    if !attr::ContainerAttr::from_attrs(&input.attrs)?.builder {
+        return Ok(Default::default());
+    }
+    let container = container::Container::from_input(input)?;
+    let (fields, syn_fields) = match (&container.data, &input.data) {
+        (container::Data::Struct(fields), syn::Data::Struct(data)) => (fields, &data.fields),
+        _ => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "builder is only supported on structs",
+            ))
+        }
+    };
+
+    let fields = fields
+        .iter()
+        .zip(syn_fields)
+        .map(|(field, syn_field)| {
+            let mut builder_field =
+                crate::builder::Field::from_type(field.ident, field.ty, &syn_field.attrs);
+            if field.attrs.flatten {
+                builder_field.kind = crate::builder::FieldKind::Required;
+            } else if matches!(field.ty, syn::Type::Path(p) if p.path.is_ident("bool")) {
+                builder_field.kind = crate::builder::FieldKind::Defaulted;
+            }
+            builder_field
+        })
+        .collect::<Vec<_>>();
+    Ok(crate::builder::expand(input, &fields))
+}
@@ -81,10 +81,11 @@ fn struct_field_serialize_entry(field: &Field) -> TokenStream {
     let field_ty = field.ty;
     let field_ident = field.ident;
     let key_ident = struct_field_key_ident(field);
-    let cond = match field.attrs.since {
-        Some(ref since) => quote::quote! { version >= #since },
-        None => quote::quote! { true },
-    };
+    let cond = attr::version_cond(
+        field.attrs.since,
+        field.attrs.until,
+        &quote::format_ident!("version"),
+    );
     let output = if field.attrs.flatten {
         quote::quote! {
             <#field_ty as crate::deser::SubsonicSerialize>::serialize(
@@ -138,7 +139,7 @@ fn struct_field_key_decl(field: &Field) -> TokenStream {
         "$text".to_string()
     } else {
         match field.attrs.attribute {
-            true => format!("@{}", key),
+            true => format!("@{}", key_json),
             false => key_json.clone(),
         }
     };
@@ -162,6 +163,13 @@ fn expand_enum(container: &Container, variants: &[Variant]) -> Result<TokenStrea
     let variants_name = variants
         .iter()
         .map(|v| util::string_to_camel_case(&v.ident.to_string()));
+    let variants_cond = variants.iter().map(|v| {
+        attr::version_cond(
+            v.attrs.since,
+            v.attrs.until,
+            &quote::format_ident!("version"),
+        )
+    });
 
     let output = quote::quote! {
         impl crate::deser::SubsonicSerialize for #container_ident {
@@ -175,10 +183,12 @@ fn expand_enum(container: &Container, variants: &[Variant]) -> Result<TokenStrea
                 match self {
                     #(
                         Self::#variants_ident(v) => {
-                            map.serialize_entry(
-                                #variants_name,
-                                &crate::deser::SubsonicSerializeWrapper(v, format, version)
-                            )?;
+                            if #variants_cond {
+                                map.serialize_entry(
+                                    #variants_name,
+                                    &crate::deser::SubsonicSerializeWrapper(v, format, version)
+                                )?;
+                            }
                         },
                     )*
                 }
@@ -0,0 +1,109 @@
+use proc_macro2::TokenStream;
+use syn::Result;
+
+use super::{
+    attr,
+    container::{Container, Data, Field, Variant},
+};
+
+pub fn expand(input: &syn::DeriveInput) -> Result<TokenStream> {
+    let container_attrs = attr::ContainerAttr::from_attrs(&input.attrs)?;
+    if container_attrs.serde {
+        return Ok(Default::default());
+    }
+
+    let container = Container::from_input(input)?;
+    let (visit, visit_mut) = match container.data {
+        Data::Struct(ref fields) => expand_struct(fields),
+        Data::Enum(ref variants) => expand_enum(variants),
+    };
+
+    let (hook, hook_mut) = match container.attrs.visit {
+        Some(ref name) => {
+            let method = quote::format_ident!("visit_{}", name);
+            let method_mut = quote::format_ident!("visit_{}_mut", name);
+            (
+                quote::quote! { visitor.#method(self); },
+                quote::quote! { visitor.#method_mut(self); },
+            )
+        }
+        None => Default::default(),
+    };
+
+    let container_ident = container.ident;
+    let (impl_g, type_g, where_g) = container.generics.split_for_impl();
+    let output = quote::quote! {
+        #[automatically_derived]
+        impl #impl_g crate::visit::Visit for #container_ident #type_g #where_g {
+            #[allow(unused_variables)]
+            fn visit<'ast, V>(&'ast self, visitor: &mut V)
+            where
+                V: crate::visit::Visitor<'ast> + ?Sized,
+            {
+                #hook
+                #visit
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_g crate::visit::VisitMut for #container_ident #type_g #where_g {
+            #[allow(unused_variables)]
+            fn visit_mut<V>(&mut self, visitor: &mut V)
+            where
+                V: crate::visit::VisitorMut + ?Sized,
+            {
+                #hook_mut
+                #visit_mut
+            }
+        }
+    };
+    Ok(output)
+}
+
+/// Fields with a `visit_id` method are visited as an ID of the kind returned by that method.
+fn expand_struct(fields: &[Field]) -> (TokenStream, TokenStream) {
+    let (visit, visit_mut): (Vec<_>, Vec<_>) = fields
+        .iter()
+        .map(|f| {
+            let ident = f.ident;
+            match f.attrs.visit_id {
+                Some(ref method) => (
+                    quote::quote! { visitor.visit_id(self.#method(), self.#ident.as_str()); },
+                    quote::quote! {
+                        let kind = self.#method();
+                        visitor.visit_id_mut(kind, self.#ident.as_mut_string());
+                    },
+                ),
+                None => (
+                    quote::quote! { crate::visit::Visit::visit(&self.#ident, visitor); },
+                    quote::quote! { crate::visit::VisitMut::visit_mut(&mut self.#ident, visitor); },
+                ),
+            }
+        })
+        .unzip();
+    (
+        quote::quote! { #(#visit)* },
+        quote::quote! { #(#visit_mut)* },
+    )
+}
+
+/// Zero-field variants have nothing to visit.
+fn expand_enum(variants: &[Variant]) -> (TokenStream, TokenStream) {
+    let idents = variants.iter().map(|v| v.ident).collect::<Vec<_>>();
+    (
+        quote::quote! {
+            match self {
+                #(Self::#idents(value) => crate::visit::Visit::visit(value, visitor),)*
+                #[allow(unreachable_patterns)]
+                _ => {}
+            }
+        },
+        quote::quote! {
+            match self {
+                #(Self::#idents(value) => crate::visit::VisitMut::visit_mut(value, visitor),)*
+                #[allow(unreachable_patterns)]
+                _ => {}
+            }
+        },
+    )
+}
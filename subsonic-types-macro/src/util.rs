@@ -46,6 +46,23 @@ pub fn type_is_option(ty: &syn::Type) -> bool {
     }
 }
 
+/// Returns the first generic type argument of a type, for example `T` in `Option<T>`.
+pub fn type_argument(ty: &syn::Type) -> Option<&syn::Type> {
+    if let syn::Type::Path(syn::TypePath { path, .. }) = ty {
+        if let Some(syn::PathSegment {
+            arguments: syn::PathArguments::AngleBracketed(arguments),
+            ..
+        }) = path.segments.last()
+        {
+            return arguments.args.iter().find_map(|arg| match arg {
+                syn::GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            });
+        }
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -72,4 +89,12 @@ mod tests {
         assert!(type_is_option(&syn::parse_quote! { Option<foo::Bar> }));
         assert!(!type_is_option(&syn::parse_quote! { foo::Bar }));
     }
+
+    #[test]
+    fn test_type_argument() {
+        let ty: syn::Type = syn::parse_quote! { Option<foo::Bar> };
+        let expected: syn::Type = syn::parse_quote! { foo::Bar };
+        assert_eq!(type_argument(&ty), Some(&expected));
+        assert_eq!(type_argument(&expected), None);
+    }
 }